@@ -0,0 +1,27 @@
+use std::fmt;
+
+/// Reasons why a table operation failed, as reported by the fallible
+/// `try_*` methods of the tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheError {
+    /// The key hashes to zero, which is reserved for empty entries.
+    ZeroHash,
+    /// All probed slots are occupied by other keys.
+    RegionFull { probes: usize },
+    /// Every slot of the table is occupied.
+    CapacityExceeded,
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::ZeroHash => write!(f, "key hashes to the reserved value zero"),
+            CacheError::RegionFull { probes } => {
+                write!(f, "no free slot found after {} probes", probes)
+            }
+            CacheError::CapacityExceeded => write!(f, "table is at full capacity"),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
@@ -1,11 +1,17 @@
 #![cfg_attr(feature = "nightly", feature(new_uninit))]
 
+mod error;
+pub mod probe;
 mod quadratic_probing;
 pub mod traits;
 
+pub use error::CacheError;
 pub use traits::TranspositionHash;
 pub use traits::TranspositionTable;
 
 pub type QuadraticProbingTable64<K, V> =
     quadratic_probing::QuadraticProbingTableBase<K, V, traits::Entry64<K, V>>;
 pub type QuadraticProbingTable<K, V, E> = quadratic_probing::QuadraticProbingTableBase<K, V, E>;
+pub type LinearProbingTable64<K, V> =
+    quadratic_probing::QuadraticProbingTableBase<K, V, traits::Entry64<K, V>, probe::Linear>;
+pub type ProbingTable<K, V, E, P> = quadratic_probing::QuadraticProbingTableBase<K, V, E, P>;
@@ -0,0 +1,105 @@
+/// The order in which slots are probed when the home slot of a key is taken.
+pub trait ProbeSequence {
+    /// Returns the slot to probe after `index` on the given attempt, starting
+    /// at attempt 1. The result is masked to the table size by the caller.
+    fn next(index: usize, attempt: usize) -> usize;
+}
+
+/// Adds `C1 * n + C2 * n^2` to the previously probed slot on the n-th
+/// attempt. The offsets accumulate, so the n-th probe lies
+/// `C1 * n(n+1)/2 + C2 * n(n+1)(2n+1)/6` slots after the home slot.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quadratic<const C1: usize = 1, const C2: usize = 2>;
+
+impl<const C1: usize, const C2: usize> ProbeSequence for Quadratic<C1, C2> {
+    #[inline(always)]
+    fn next(index: usize, attempt: usize) -> usize {
+        index
+            .wrapping_add(C1.wrapping_mul(attempt))
+            .wrapping_add(C2.wrapping_mul(attempt).wrapping_mul(attempt))
+    }
+}
+
+/// Probes the next slot on each attempt.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Linear;
+
+impl ProbeSequence for Linear {
+    #[inline(always)]
+    fn next(index: usize, _attempt: usize) -> usize {
+        index.wrapping_add(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quadratic_probing::QuadraticProbingTableBase;
+    use crate::traits::{Entry64, TranspositionHash};
+
+    #[derive(Debug, Clone, Copy)]
+    struct Key(u64);
+
+    impl TranspositionHash for Key {
+        fn hash(&self) -> u64 {
+            self.0
+        }
+    }
+
+    /// Distinct, pseudo-random, non-zero keys (the SplitMix64 finalizer,
+    /// which is a bijection, of 1 to `n`).
+    fn keys(n: u64) -> impl Iterator<Item = Key> {
+        (1..=n).map(|i| {
+            let z = (i ^ (i >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            let z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            Key(z ^ (z >> 31))
+        })
+    }
+
+    /// Fills a table of 1024 slots with `n` keys, returning the number of
+    /// keys that could not be inserted, after checking that all others are found.
+    fn failed_inserts<P: ProbeSequence>(n: u64, probe_limit: usize) -> usize {
+        let mut table = QuadraticProbingTableBase::<Key, u64, Entry64<Key, u64>, P>::new(1024)
+            .with_probe_limit(probe_limit);
+        let failed = keys(n)
+            .filter(|&key| table.try_insert(key, key.0).is_err())
+            .count();
+        let found = keys(n)
+            .filter(|key| table.try_get(key) == Ok(Some(&key.0)))
+            .count();
+        assert_eq!(found + failed, n as usize);
+        assert_eq!(table.size(), found);
+        failed
+    }
+
+    #[test]
+    fn quadratic_offsets_accumulate() {
+        let mut index = 0;
+        let probes = (1..=4)
+            .map(|n| {
+                index = Quadratic::<1, 2>::next(index, n);
+                index
+            })
+            .collect::<Vec<usize>>();
+        assert_eq!(probes, [3, 13, 34, 70]);
+    }
+
+    #[test]
+    fn keys_are_found_under_load() {
+        assert_eq!(failed_inserts::<Quadratic>(768, 64), 0);
+        assert_eq!(failed_inserts::<Linear>(768, 64), 0);
+    }
+
+    #[test]
+    fn lower_probe_limits_fail_more_inserts() {
+        for failed in [failed_inserts::<Quadratic>, failed_inserts::<Linear>] {
+            let (short, long) = (failed(900, 2), failed(900, 16));
+            assert!(
+                short > long,
+                "{} failures with 2 probes, {} with 16",
+                short,
+                long
+            );
+        }
+    }
+}
@@ -1,23 +1,27 @@
+use crate::error::CacheError;
+use crate::probe::{ProbeSequence, Quadratic};
 use crate::traits::{AlwaysReplacePolicy, Entry, Entry64, TranspositionHash, TranspositionTable};
 use std::mem;
 use std::mem::MaybeUninit;
 
 const RETRIES: usize = 8;
-const C1: usize = 1;
-const C2: usize = 2;
 
-pub struct QuadraticProbingTableBase<K, V, E> {
+/// An open addressing table. Despite the name, the probe sequence is
+/// configurable through `P`, see [crate::probe].
+pub struct QuadraticProbingTableBase<K, V, E, P = Quadratic> {
     entries: Box<[MaybeUninit<E>]>,
     capacity: usize,
     size: usize,
     mask: usize,
-    _marker: std::marker::PhantomData<(K, V)>,
+    probe_limit: usize,
+    _marker: std::marker::PhantomData<(K, V, P)>,
 }
 
-impl<K, V, E> QuadraticProbingTableBase<K, V, E>
+impl<K, V, E, P> QuadraticProbingTableBase<K, V, E, P>
 where
     K: TranspositionHash,
     E: Entry<Key = K, Value = V, RawKey = u64>,
+    P: ProbeSequence,
 {
     /// Creates a new table with the given number of slots.
     ///
@@ -33,6 +37,7 @@ where
                 capacity: slots,
                 size: 0,
                 mask: slots - 1,
+                probe_limit: RETRIES,
                 _marker: Default::default(),
             }
         }
@@ -56,21 +61,52 @@ where
                 capacity: slots,
                 size: 0,
                 mask: slots - 1,
+                probe_limit: RETRIES,
                 _marker: Default::default(),
             }
         }
     }
 
+    /// Sets the maximum number of slots probed per operation, 8 by default.
+    /// Must be set before inserting, as keys inserted beyond a lowered limit
+    /// can no longer be found.
+    ///
+    /// # Panics
+    /// Panics if `limit` is zero.
+    pub fn with_probe_limit(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "probe limit must be positive");
+        self.probe_limit = limit;
+        self
+    }
+
+    pub fn probe_limit(&self) -> usize {
+        self.probe_limit
+    }
+
+    #[inline]
     fn get(&self, k: &K) -> Option<&V> {
+        self.try_get(k).ok().flatten()
+    }
+
+    #[inline]
+    fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.try_insert(k, v).unwrap_or(None)
+    }
+
+    /// Like [TranspositionTable::get], but distinguishes why a key was not
+    /// found. Returns `Ok(None)` if the key is not in the table, and an error
+    /// if the key hashes to zero or its probe sequence is fully occupied by
+    /// other keys.
+    pub fn try_get(&self, k: &K) -> Result<Option<&V>, CacheError> {
         let hash = k.hash();
         if hash == 0 {
-            return None;
+            return Err(CacheError::ZeroHash);
         } // 0 is reserved for empty entries
 
         let mut i = hash as usize & self.mask;
         let mut attempts = 0;
 
-        while attempts < RETRIES {
+        while attempts < self.probe_limit {
             // SAFETY: All non-zero entries are initialized and we only read
             // entries that are non-zero. As for the length, i is always in
             // bounds since we mask it with the capacity.
@@ -78,33 +114,38 @@ where
                 let entry = self.entries.get_unchecked(i).assume_init_ref();
                 if *entry.raw_key() == hash {
                     // Key found, therefore we return the value
-                    return Some(&entry.value());
+                    return Ok(Some(entry.value()));
                 }
                 if *entry.raw_key() == 0 {
                     // Found an uninitialized entry, therefore key doesn't exist
-                    return None;
+                    return Ok(None);
                 }
             };
 
             attempts += 1;
-            i = (i + C1 * attempts + C2 * attempts * attempts) & self.mask;
+            i = P::next(i, attempts) & self.mask;
         }
-        // The entry was not found in the table after RETRIES accesses.
-        // However, since we never try to write for more than RETRIES
-        // times, we can safely assume that the entry is not in the table.
-        None
+        // The entry was not found in the table after probe_limit accesses.
+        // However, since we never try to write for more than probe_limit
+        // times, we can safely assume that the entry is not in the table,
+        // and that the region is full.
+        Err(CacheError::RegionFull {
+            probes: self.probe_limit,
+        })
     }
 
-    fn insert(&mut self, k: K, v: V) -> Option<V> {
+    /// Like [TranspositionTable::insert], but reports why a key could not be
+    /// inserted instead of returning `None`.
+    pub fn try_insert(&mut self, k: K, v: V) -> Result<Option<V>, CacheError> {
         let hash = k.hash();
         if hash == 0 {
-            return None;
+            return Err(CacheError::ZeroHash);
         } // 0 is reserved for empty entries
 
         let mut i = hash as usize & self.mask;
         let mut attempts = 0;
 
-        while attempts < RETRIES {
+        while attempts < self.probe_limit {
             // SAFETY: All non-zero entries are initialized and we only use
             // entries that are non-zero. As for the length, i is always in
             // bounds since we mask it with the capacity.
@@ -114,7 +155,7 @@ where
                     // Key already exists, therefore we replace the value
                     // SAFETY: entry is initialized, therefore we can safely
                     // read from it and replace the value
-                    return Some(entry.replace(v));
+                    return Ok(Some(entry.replace(v)));
                 }
                 if *entry.raw_key() == 0 {
                     // We found an empty slot, therefore we insert the key-value pair
@@ -122,17 +163,23 @@ where
                     *entry.raw_key_mut() = hash;
                     *entry.value_mut() = v;
                     self.size += 1;
-                    return None;
+                    return Ok(None);
                 }
             };
 
             attempts += 1;
-            i = (i + C1 * attempts + C2 * attempts * attempts) & self.mask;
+            i = P::next(i, attempts) & self.mask;
         }
         // If we've reached here, it means we couldn't insert the
-        // key-value pair after RETRIES attempts. This means that
+        // key-value pair after probe_limit attempts. This means that
         // the table is full in that region.
-        None
+        if self.size == self.capacity {
+            Err(CacheError::CapacityExceeded)
+        } else {
+            Err(CacheError::RegionFull {
+                probes: self.probe_limit,
+            })
+        }
     }
 
     pub fn size(&self) -> usize {
@@ -152,10 +199,11 @@ where
     }
 }
 
-impl<K, V, E> TranspositionTable<K, V> for QuadraticProbingTableBase<K, V, E>
+impl<K, V, E, P> TranspositionTable<K, V> for QuadraticProbingTableBase<K, V, E, P>
 where
     K: TranspositionHash,
     E: Entry<Key = K, Value = V, RawKey = u64>,
+    P: ProbeSequence,
 {
     fn get<'a>(&'a self, k: &K) -> Option<&'a V>
     where
@@ -169,4 +217,89 @@ where
     }
 }
 
-impl<K, V, E> AlwaysReplacePolicy for QuadraticProbingTableBase<K, V, E> {}
+impl<K, V, E, P> AlwaysReplacePolicy for QuadraticProbingTableBase<K, V, E, P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probe::Linear;
+    use crate::QuadraticProbingTable64;
+
+    /// A key that hashes to itself.
+    #[derive(Debug, Clone, Copy)]
+    struct Key(u64);
+
+    impl TranspositionHash for Key {
+        fn hash(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn inserted_keys_are_found_and_replaced() {
+        let mut table = QuadraticProbingTable64::<Key, u32>::new(16);
+        assert_eq!(table.try_get(&Key(5)), Ok(None));
+        assert_eq!(table.try_insert(Key(5), 1), Ok(None));
+        assert_eq!(table.try_get(&Key(5)), Ok(Some(&1)));
+        assert_eq!(table.try_insert(Key(5), 2), Ok(Some(1)));
+        assert_eq!(table.try_get(&Key(5)), Ok(Some(&2)));
+        assert_eq!(table.size(), 1);
+    }
+
+    #[test]
+    fn zero_hash_is_rejected() {
+        let mut table = QuadraticProbingTable64::<Key, u32>::new(16);
+        assert_eq!(table.try_insert(Key(0), 1), Err(CacheError::ZeroHash));
+        assert_eq!(table.try_get(&Key(0)), Err(CacheError::ZeroHash));
+        assert_eq!(table.size(), 0);
+    }
+
+    #[test]
+    fn full_region_is_reported() {
+        // keys 1, 17 and 33 share the home slot 1 of a table of 16 slots
+        let mut table = QuadraticProbingTable64::<Key, u32>::new(16).with_probe_limit(2);
+        assert_eq!(table.try_insert(Key(1), 1), Ok(None));
+        assert_eq!(table.try_insert(Key(17), 2), Ok(None));
+        assert_eq!(
+            table.try_insert(Key(33), 3),
+            Err(CacheError::RegionFull { probes: 2 })
+        );
+        assert_eq!(
+            table.try_get(&Key(33)),
+            Err(CacheError::RegionFull { probes: 2 })
+        );
+        assert_eq!(table.try_get(&Key(17)), Ok(Some(&2)));
+        assert_eq!(table.size(), 2);
+    }
+
+    #[test]
+    fn full_table_is_reported() {
+        let mut table = QuadraticProbingTable64::<Key, u32>::new(2);
+        assert_eq!(table.try_insert(Key(1), 1), Ok(None));
+        assert_eq!(table.try_insert(Key(2), 2), Ok(None));
+        assert_eq!(
+            table.try_insert(Key(3), 3),
+            Err(CacheError::CapacityExceeded)
+        );
+        assert_eq!(table.try_insert(Key(2), 4), Ok(Some(2)));
+    }
+
+    #[test]
+    fn linear_probing_reports_the_same_errors() {
+        let mut table = QuadraticProbingTableBase::<Key, u32, Entry64<Key, u32>, Linear>::new(4)
+            .with_probe_limit(2);
+        assert_eq!(table.try_insert(Key(0), 1), Err(CacheError::ZeroHash));
+        assert_eq!(table.try_insert(Key(1), 1), Ok(None));
+        assert_eq!(table.try_insert(Key(5), 2), Ok(None));
+        assert_eq!(
+            table.try_insert(Key(9), 3),
+            Err(CacheError::RegionFull { probes: 2 })
+        );
+        assert_eq!(table.try_insert(Key(3), 4), Ok(None));
+        assert_eq!(table.try_insert(Key(4), 5), Ok(None));
+        assert_eq!(
+            table.try_insert(Key(8), 6),
+            Err(CacheError::CapacityExceeded)
+        );
+    }
+}
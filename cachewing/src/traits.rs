@@ -56,7 +56,7 @@ where
     /// - `None` if insertion was successful and the key did not exist yet
     /// - `Some(old)` when the key already existed and was replaced by the new key
     /// - `None` if the key could not be inserted. This can happen if the table
-    /// is full (type-2 error).
+    ///   is full (type-2 error).
     fn insert(&mut self, k: K, v: V) -> Option<V>;
 }
 
@@ -1,17 +1,85 @@
-use crate::agents::Evaluator;
+use crate::agents::{short_type_name, Evaluator};
 use crate::core::{Game, GwState, MatchError};
 use anyhow::Error;
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::SeedableRng;
+use std::borrow::Cow;
 use std::fmt;
 use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Information passed to [Agent::prepare] before a game starts.
+pub struct PrepareInfo<G: Game> {
+    /// The team the agent plays for.
+    pub team: G::Team,
+}
 
 pub trait Agent<G: Game> {
     fn select_action(&mut self, state: &G::State) -> Result<G::Action, Error>;
+
+    /// Called once before the first turn of a game, outside of any move
+    /// timing. Agents can use it for expensive setup such as allocating
+    /// tables. Does nothing by default.
+    fn prepare(&mut self, _initial_state: &G::State, _info: &PrepareInfo<G>) {}
+
+    /// Called after every action applied in a game, including the agent's
+    /// own, so that agents can track the game incrementally. Does nothing
+    /// by default.
+    fn observe(&mut self, _prev: &G::State, _action: &G::Action, _next: &G::State) {}
+
+    /// Asked of the second agent after the first ply when the pie rule is in
+    /// effect, see [Pit::with_swap_rule](crate::train::Pit::with_swap_rule).
+    /// Returning `true` takes over the first mover's team for the rest of the
+    /// game. Declines by default.
+    fn accept_swap(&mut self, _state: &G::State) -> bool {
+        false
+    }
+
+    /// Told before a turn how long the agent may think about it: the soft
+    /// limit is what it should aim to spend, the hard limit what it must not
+    /// exceed, see [TimeManager](crate::agents::TimeManager). Does nothing by
+    /// default.
+    fn set_time_budget(&mut self, _soft: Duration, _hard: Duration) {}
+
+    /// Told how many nodes the agent may search per move, e.g. to give one
+    /// side odds in a [MatchSeries](crate::train::MatchSeries::with_odds).
+    /// Does nothing by default.
+    fn set_node_budget(&mut self, _nodes: u64) {}
+
+    /// The time the agent itself spent computing its most recent action, for
+    /// agents whose measured time includes overhead such as transport to a
+    /// thread or process. `None` by default.
+    fn reported_time(&self) -> Option<Duration> {
+        None
+    }
+
+    /// A human readable name for the agent, used in logs. Defaults to the
+    /// type name without module path and generic parameters.
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(short_type_name(std::any::type_name::<Self>()))
+    }
+}
+
+/// How [MaximisingAgent] chooses among actions with the same best evaluation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TieBreak {
+    /// The first in the order the actions are generated.
+    First,
+    /// The last in the order the actions are generated.
+    #[default]
+    Last,
+    /// A random one, from a generator seeded with the given seed when the
+    /// policy is set.
+    Random(u64),
 }
 
 /// An agent which selects the best action for the current player according
 /// to an evaluator.
 pub struct MaximisingAgent<G: Game, E: Evaluator<G>> {
     evaluator: E,
+    tie_break: TieBreak,
+    rng: StdRng,
     _marker: PhantomData<G>,
 }
 
@@ -19,10 +87,27 @@ impl<G: Game, E: Evaluator<G>> MaximisingAgent<G, E> {
     pub fn new(evaluator: E) -> Self {
         MaximisingAgent {
             evaluator,
+            tie_break: TieBreak::default(),
+            rng: StdRng::seed_from_u64(0),
             _marker: PhantomData,
         }
     }
 
+    /// Sets how ties between equally evaluated actions are broken, see
+    /// [TieBreak]. To prefer faster wins, score them higher, e.g. with
+    /// [win_in](crate::agents::scores::win_in).
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        if let TieBreak::Random(seed) = tie_break {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        self.tie_break = tie_break;
+        self
+    }
+
+    pub fn tie_break(&self) -> TieBreak {
+        self.tie_break
+    }
+
     pub fn evaluator(&self) -> &E {
         &self.evaluator
     }
@@ -42,7 +127,8 @@ where
     fn select_action(&mut self, state: &G::State) -> Result<G::Action, Error> {
         let actions = state.actions().into_iter().collect::<Vec<G::Action>>();
 
-        let best = actions
+        self.evaluator.begin_move();
+        let evaluated = actions
             .iter()
             .map(|x| {
                 let evaluation =
@@ -51,8 +137,22 @@ where
                 println!("Considering Action {:?} with eval: {:?}", x, evaluation);
                 (x, evaluation)
             })
-            .max_by_key(|(_, evaluation)| evaluation.clone());
+            .collect::<Vec<_>>();
+        self.evaluator.end_move();
 
+        if let Some(err) = self.evaluator.take_error() {
+            return Err(err.into());
+        }
+        let best_eval = evaluated.iter().map(|(_, eval)| eval).max();
+        let mut ties = evaluated
+            .iter()
+            .filter(|(_, eval)| Some(eval) == best_eval)
+            .map(|(action, eval)| (*action, eval));
+        let best = match self.tie_break {
+            TieBreak::First => ties.next(),
+            TieBreak::Last => ties.last(),
+            TieBreak::Random(_) => ties.choose(&mut self.rng),
+        };
         if let Some((action, eval)) = best {
             println!("Selected {:?} / eval: {:?}", action, eval);
             Ok(action.clone())
@@ -60,4 +160,75 @@ where
             Err(MatchError::<G>::NoAvailableActions(state.clone()).into())
         }
     }
+
+    fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+        self.evaluator.set_time_budget(soft, hard);
+    }
+
+    fn set_node_budget(&mut self, nodes: u64) {
+        self.evaluator.set_node_budget(nodes);
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Maximising({})", self.evaluator.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Team;
+    use crate::testing::counting::{CountingEvaluator, CountingGame, CountingState};
+    use std::collections::BTreeSet;
+
+    fn agent(tie_break: TieBreak) -> impl Agent<CountingGame> {
+        MaximisingAgent::new(CountingEvaluator { perfect: true }).with_tie_break(tie_break)
+    }
+
+    #[test]
+    fn fixed_tie_breaks_pick_the_ends() {
+        // every action leaves the opponent a winning count
+        let lost = CountingState::new(1, Team::One);
+        assert_eq!(agent(TieBreak::First).select_action(&lost).unwrap(), 1);
+        assert_eq!(agent(TieBreak::Last).select_action(&lost).unwrap(), 3);
+    }
+
+    #[test]
+    fn random_tie_break_spreads_over_equal_actions() {
+        let lost = CountingState::new(1, Team::One);
+        let mut evaluator = CountingEvaluator { perfect: true };
+        let best = [1, 2, 3]
+            .iter()
+            .map(|action| evaluator.evaluate_action_for(&lost, action, &Team::One))
+            .max()
+            .unwrap();
+
+        let mut chosen = BTreeSet::new();
+        for seed in 0..32 {
+            let action = agent(TieBreak::Random(seed)).select_action(&lost).unwrap();
+            assert_eq!(
+                evaluator.evaluate_action_for(&lost, &action, &Team::One),
+                best
+            );
+            chosen.insert(action);
+        }
+        assert_eq!(chosen, BTreeSet::from([1, 2, 3]));
+
+        // a single agent varies its choice between moves as well
+        let mut agent = agent(TieBreak::Random(7));
+        let chosen = (0..32)
+            .map(|_| agent.select_action(&lost).unwrap())
+            .collect::<BTreeSet<u32>>();
+        assert_eq!(chosen.len(), 3);
+    }
+
+    #[test]
+    fn random_tie_break_keeps_a_unique_best_action() {
+        // only 1 leaves the opponent a losing count of 20
+        let state = CountingState::new(0, Team::One);
+        for seed in 0..32 {
+            let action = agent(TieBreak::Random(seed)).select_action(&state).unwrap();
+            assert_eq!(action, 1);
+        }
+    }
 }
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+/// Limits on the work a single search may do. Node budgets are reproducible
+/// across machines, time budgets are not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchBudget {
+    Time(Duration),
+    Nodes(u64),
+    /// Whichever of the two limits is reached first.
+    Both(Duration, u64),
+}
+
+impl SearchBudget {
+    pub fn time(&self) -> Option<Duration> {
+        match *self {
+            SearchBudget::Time(time) | SearchBudget::Both(time, _) => Some(time),
+            SearchBudget::Nodes(_) => None,
+        }
+    }
+
+    pub fn nodes(&self) -> Option<u64> {
+        match *self {
+            SearchBudget::Nodes(nodes) | SearchBudget::Both(_, nodes) => Some(nodes),
+            SearchBudget::Time(_) => None,
+        }
+    }
+
+    /// Whether a search that has visited `nodes` nodes in `elapsed` time has
+    /// used up this budget.
+    #[inline]
+    pub fn is_exhausted(&self, nodes: u64, elapsed: Duration) -> bool {
+        self.nodes().is_some_and(|limit| nodes >= limit)
+            || self.time().is_some_and(|limit| elapsed >= limit)
+    }
+}
+
+/// Reasons for which a search was aborted, see [Evaluator::take_error](crate::agents::Evaluator::take_error).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SearchError {
+    #[error("Search exceeded the hard cap of {nodes} nodes")]
+    NodeCapExceeded { nodes: u64 },
+}
@@ -0,0 +1,201 @@
+use crate::agents::{Evaluator, SearchError};
+use crate::core::{Game, GwState};
+use crate::tuning::Tunable;
+use cachewing::TranspositionHash;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Caches the evaluations of an evaluator, keyed by the [TranspositionHash]
+/// of the state and whether the evaluation is for the team to move.
+///
+/// Every entry is stored with the version of the cache it was computed in.
+/// [CachedEvaluator::invalidate_all] and changing the parameters through
+/// [Tunable::set_params] start a new version, so stale values are never
+/// returned.
+pub struct CachedEvaluator<G: Game, E> {
+    inner: E,
+    cache: HashMap<(u64, bool), (u64, G::EvalType)>,
+    version: u64,
+    hits: u64,
+    misses: u64,
+    error: Option<SearchError>,
+    _game: PhantomData<G>,
+}
+
+impl<G: Game, E> CachedEvaluator<G, E> {
+    pub fn new(inner: E) -> Self {
+        CachedEvaluator {
+            inner,
+            cache: HashMap::new(),
+            version: 0,
+            hits: 0,
+            misses: 0,
+            error: None,
+            _game: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Marks every cached value as stale.
+    pub fn invalidate_all(&mut self) {
+        self.version += 1;
+        self.cache.clear();
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+impl<G, E> Evaluator<G> for CachedEvaluator<G, E>
+where
+    G: Game,
+    G::State: TranspositionHash,
+    G::EvalType: Clone,
+    E: Evaluator<G>,
+{
+    fn evaluate_for(&mut self, state: &G::State, team: &G::Team) -> G::EvalType {
+        let key = (state.hash(), state.team_to_move() == *team);
+        if let Some((version, eval)) = self.cache.get(&key) {
+            if *version == self.version {
+                self.hits += 1;
+                return eval.clone();
+            }
+        }
+        self.misses += 1;
+        let eval = self.inner.evaluate_for(state, team);
+        // the scores of aborted evaluations are not cached
+        if let Some(err) = self.inner.take_error() {
+            self.error.get_or_insert(err);
+            return eval;
+        }
+        self.cache.insert(key, (self.version, eval.clone()));
+        eval
+    }
+
+    fn begin_move(&mut self) {
+        self.inner.begin_move();
+    }
+
+    fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+        self.inner.set_time_budget(soft, hard);
+    }
+
+    fn set_node_budget(&mut self, nodes: u64) {
+        self.inner.set_node_budget(nodes);
+    }
+
+    fn end_move(&mut self) {
+        self.inner.end_move();
+    }
+
+    fn take_error(&mut self) -> Option<SearchError> {
+        self.error.take()
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Cached({})", self.inner.name()))
+    }
+}
+
+impl<G: Game, E: Tunable> Tunable for CachedEvaluator<G, E> {
+    fn params(&self) -> Vec<f64> {
+        self.inner.params()
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        self.inner.set_params(params);
+        self.invalidate_all();
+    }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Team;
+    use crate::testing::counting::{CountingGame, CountingState};
+
+    /// Scores every state with its weight and records each evaluation.
+    struct Recording {
+        weight: f64,
+        calls: u64,
+    }
+
+    impl Evaluator<CountingGame> for Recording {
+        fn evaluate_for(&mut self, _: &CountingState, _: &Team) -> i32 {
+            self.calls += 1;
+            self.weight as i32
+        }
+    }
+
+    impl Tunable for Recording {
+        fn params(&self) -> Vec<f64> {
+            vec![self.weight]
+        }
+
+        fn set_params(&mut self, params: &[f64]) {
+            self.weight = params[0];
+        }
+    }
+
+    #[test]
+    fn repeated_evaluations_are_cached() {
+        let mut cached = CachedEvaluator::new(Recording {
+            weight: 1.0,
+            calls: 0,
+        });
+        let state = CountingState::new(5, Team::One);
+        assert_eq!(cached.evaluate_for(&state, &Team::One), 1);
+        assert_eq!(cached.evaluate_for(&state, &Team::One), 1);
+        // the perspective is part of the key
+        assert_eq!(cached.evaluate_for(&state, &Team::Two), 1);
+        assert_eq!(cached.inner().calls, 2);
+        assert_eq!((cached.hits(), cached.misses()), (1, 2));
+    }
+
+    #[test]
+    fn no_stale_value_after_set_params() {
+        let mut cached = CachedEvaluator::new(Recording {
+            weight: 1.0,
+            calls: 0,
+        });
+        let state = CountingState::new(5, Team::One);
+        assert_eq!(cached.evaluate_for(&state, &Team::One), 1);
+
+        let version = cached.version();
+        cached.set_params(&[7.0]);
+        assert!(cached.version() > version);
+        assert_eq!(cached.params(), vec![7.0]);
+        assert_eq!(cached.evaluate_for(&state, &Team::One), 7);
+        assert_eq!(cached.inner().calls, 2);
+        assert_eq!(cached.evaluate_for(&state, &Team::One), 7);
+        assert_eq!(cached.inner().calls, 2);
+    }
+
+    #[test]
+    fn invalidate_all_forces_reevaluation() {
+        let mut cached = CachedEvaluator::new(Recording {
+            weight: 3.0,
+            calls: 0,
+        });
+        let state = CountingState::new(5, Team::One);
+        cached.evaluate_for(&state, &Team::One);
+        cached.invalidate_all();
+        cached.evaluate_for(&state, &Team::One);
+        assert_eq!(cached.inner().calls, 2);
+        assert_eq!(cached.hits(), 0);
+    }
+}
@@ -0,0 +1,18 @@
+/// Statistics collected by a search, reset at the start of each move, see
+/// [Evaluator::begin_move](crate::agents::Evaluator::begin_move), or root search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchCounters {
+    /// Nodes visited, including the root and the leaves.
+    pub nodes: u64,
+    /// Leaves scored by the evaluator. Evaluations used only for move
+    /// ordering are not counted.
+    pub leaves: u64,
+    /// Nodes at which the remaining actions were pruned.
+    pub cutoffs: u64,
+}
+
+impl SearchCounters {
+    pub fn reset(&mut self) {
+        *self = SearchCounters::default();
+    }
+}
@@ -1,4 +1,11 @@
-use crate::core::{Game, GwState};
+use crate::agents::{short_type_name, SearchError};
+use crate::core::{Game, GwGameResult, GwState, GwTeam};
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::ops::Neg;
+use std::time::Duration;
 
 /// Evaluators provide functions for evaluating a game state. These evaluations
 /// are always **relative** to a team. That is, the evaluator should return
@@ -39,4 +46,280 @@ pub trait Evaluator<G: Game> {
     ) -> G::EvalType {
         self.evaluate_for(&state.apply_action(action), team)
     }
+
+    /// Called by agents before they evaluate the actions of the state they
+    /// are to move in, so that evaluators can limit the work done for the
+    /// whole move rather than for each action, see [NegaMax::with_budget](crate::agents::NegaMax::with_budget).
+    /// Does nothing by default.
+    fn begin_move(&mut self) {}
+
+    /// Passed on by agents from [Agent::set_time_budget](crate::agents::Agent::set_time_budget)
+    /// for the next move. Does nothing by default.
+    fn set_time_budget(&mut self, _soft: Duration, _hard: Duration) {}
+
+    /// Passed on by agents from [Agent::set_node_budget](crate::agents::Agent::set_node_budget).
+    /// Does nothing by default.
+    fn set_node_budget(&mut self, _nodes: u64) {}
+
+    /// Called by agents once they have evaluated the actions of a move, so
+    /// that later evaluations outside of a move, e.g. for analysis, are not
+    /// limited by the budget of the move. Does nothing by default.
+    fn end_move(&mut self) {}
+
+    /// Takes the error that aborted an evaluation since the last call, if
+    /// any. Evaluations return a score regardless, so agents check this to
+    /// avoid acting on the score of an aborted search. `None` by default.
+    fn take_error(&mut self) -> Option<SearchError> {
+        None
+    }
+
+    /// A human readable name for the evaluator, used in agent names. Defaults
+    /// to the type name without module path and generic parameters.
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(short_type_name(std::any::type_name::<Self>()))
+    }
+}
+
+/// An evaluator that is symmetric by construction: it only scores a state
+/// relative to the team to move. Wrap it in [AsSymmetric] to use it as an
+/// [Evaluator].
+pub trait SymmetricEvaluator<G: Game> {
+    /// Evaluate the state relative to the team to move, returning a score.
+    fn evaluate_relative(&mut self, state: &G::State) -> G::EvalType;
+
+    /// A human readable name for the evaluator, see [Evaluator::name].
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(short_type_name(std::any::type_name::<Self>()))
+    }
+}
+
+/// Adapts a [SymmetricEvaluator] into an [Evaluator], negating the score
+/// for the team not to move.
+#[derive(Debug, Clone, Default)]
+pub struct AsSymmetric<E>(pub E);
+
+impl<E> AsSymmetric<E> {
+    pub fn new(evaluator: E) -> Self {
+        AsSymmetric(evaluator)
+    }
+
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+impl<G, E> Evaluator<G> for AsSymmetric<E>
+where
+    G: Game,
+    G::EvalType: Neg<Output = G::EvalType>,
+    E: SymmetricEvaluator<G>,
+{
+    #[inline]
+    fn evaluate(&mut self, state: &G::State) -> G::EvalType {
+        self.0.evaluate_relative(state)
+    }
+
+    #[inline]
+    fn evaluate_for(&mut self, state: &G::State, team: &G::Team) -> G::EvalType {
+        let score = self.0.evaluate_relative(state);
+        if *team == state.team_to_move() {
+            score
+        } else {
+            -score
+        }
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.0.name()
+    }
+}
+
+/// Checks that the evaluator is symmetric on the given states, i.e. that
+/// `eval(S, A) = -eval(S, B)` for both teams.
+///
+/// # Panics
+/// Panics with the offending state if the evaluator is not symmetric.
+pub fn assert_symmetric<'a, G, E>(evaluator: &mut E, states: impl IntoIterator<Item = &'a G::State>)
+where
+    G: Game,
+    G::EvalType: Neg<Output = G::EvalType> + PartialEq + Clone + Debug,
+    E: Evaluator<G>,
+{
+    for state in states {
+        let team = state.team_to_move();
+        let own = evaluator.evaluate_for(state, &team);
+        let other = evaluator.evaluate_for(state, &team.opponent());
+        assert!(
+            own == -other.clone(),
+            "asymmetric evaluation {:?} for {:?} and {:?} for {:?} in state {:?}",
+            own,
+            team,
+            other,
+            team.opponent(),
+            state
+        );
+    }
+}
+
+/// Settings for [check_contract].
+#[derive(Debug, Clone)]
+pub struct ContractConfig {
+    /// Largest absolute score accepted for drawn states.
+    pub draw_tolerance: f64,
+    /// Whether to check that [Evaluator::evaluate_action_for] agrees with
+    /// evaluating the resulting state, as the default implementation does.
+    pub check_actions: bool,
+}
+
+impl Default for ContractConfig {
+    fn default() -> Self {
+        ContractConfig {
+            draw_tolerance: 0.0,
+            check_actions: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViolationKind<T> {
+    /// A won state does not score positive for the winner.
+    WinNotPositive { team: T, score: f64 },
+    /// A won state does not score negative for the loser.
+    LossNotNegative { team: T, score: f64 },
+    /// A drawn state scores outside the draw tolerance.
+    DrawNotNeutral { team: T, score: f64 },
+    /// The evaluation of an action differs from the evaluation of the
+    /// state it leads to.
+    ActionInconsistent { team: T, action: f64, state: f64 },
+}
+
+/// A state on which an evaluator breaks the [Evaluator] contract.
+#[derive(Debug, Clone)]
+pub struct ContractViolation<G: Game> {
+    pub state: G::State,
+    pub kind: ViolationKind<G::Team>,
+}
+
+/// Checks the sign conventions of an evaluator on the given states, for both
+/// teams: won states must score positive for the winner and negative for the
+/// loser, and drawn states must score close to zero. Returns every violation
+/// found, see [random_states] for generating states.
+pub fn check_contract<'a, G, E>(
+    evaluator: &mut E,
+    states: impl IntoIterator<Item = &'a G::State>,
+    config: &ContractConfig,
+) -> Vec<ContractViolation<G>>
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G>,
+{
+    let mut violations = Vec::new();
+    for state in states {
+        let to_move = state.team_to_move();
+        for team in [to_move.clone(), to_move.opponent()] {
+            let score = evaluator.evaluate_for(state, &team).into();
+            let kind = match state.game_result().map(|result| result.winner()) {
+                Some(Some(winner)) if winner == team && score <= 0.0 => {
+                    Some(ViolationKind::WinNotPositive { team, score })
+                }
+                Some(Some(winner)) if winner != team && score >= 0.0 => {
+                    Some(ViolationKind::LossNotNegative { team, score })
+                }
+                Some(None) if score.abs() > config.draw_tolerance => {
+                    Some(ViolationKind::DrawNotNeutral { team, score })
+                }
+                None if config.check_actions => state.actions().into_iter().find_map(|action| {
+                    let by_action = evaluator.evaluate_action_for(state, &action, &team).into();
+                    let by_state = evaluator
+                        .evaluate_for(&state.apply_action(&action), &team)
+                        .into();
+                    (by_action != by_state).then(|| ViolationKind::ActionInconsistent {
+                        team: team.clone(),
+                        action: by_action,
+                        state: by_state,
+                    })
+                }),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                violations.push(ContractViolation {
+                    state: state.clone(),
+                    kind,
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Collects every state visited in `games` random playouts from the initial state.
+pub fn random_states<G: Game, R: Rng>(games: usize, rng: &mut R) -> Vec<G::State> {
+    let mut states = Vec::new();
+    for _ in 0..games {
+        let mut state = G::initial_state();
+        loop {
+            states.push(state.clone());
+            match state.actions().into_iter().choose(rng) {
+                Some(action) if !state.is_terminal() => state = state.apply_action(&action),
+                _ => break,
+            }
+        }
+    }
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Team;
+    use crate::testing::counting::{CountingEvaluator, CountingGame, CountingState, TARGET};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Scores the distance to the next losing count for the team to move.
+    struct Distance;
+
+    impl SymmetricEvaluator<CountingGame> for Distance {
+        fn evaluate_relative(&mut self, state: &CountingState) -> i32 {
+            ((TARGET - state.count) % 4) as i32
+        }
+    }
+
+    /// Scores the count for either team.
+    struct Count;
+
+    impl Evaluator<CountingGame> for Count {
+        fn evaluate_for(&mut self, state: &CountingState, _team: &Team) -> i32 {
+            state.count as i32
+        }
+    }
+
+    fn states() -> Vec<CountingState> {
+        random_states::<CountingGame, _>(20, &mut StdRng::seed_from_u64(3))
+    }
+
+    #[test]
+    fn as_symmetric_negates_for_the_other_team() {
+        let mut evaluator = AsSymmetric::new(Distance);
+        let state = CountingState::new(2, Team::Two);
+        assert_eq!(evaluator.evaluate(&state), 3);
+        assert_eq!(evaluator.evaluate_for(&state, &Team::Two), 3);
+        assert_eq!(evaluator.evaluate_for(&state, &Team::One), -3);
+        assert_eq!(Evaluator::<CountingGame>::name(&evaluator), "Distance");
+    }
+
+    #[test]
+    fn symmetric_evaluators_pass_on_random_states() {
+        let states = states();
+        assert!(states.len() > 20);
+        assert_symmetric(&mut AsSymmetric::new(Distance), &states);
+        assert_symmetric(&mut CountingEvaluator { perfect: true }, &states);
+    }
+
+    #[test]
+    #[should_panic(expected = "asymmetric evaluation")]
+    fn asymmetric_evaluator_is_caught() {
+        assert_symmetric(&mut Count, &states());
+    }
 }
@@ -1,31 +1,78 @@
 pub mod agent;
+pub mod budget;
+pub mod cached_evaluator;
+pub mod counters;
 pub mod evaluator;
 pub mod functional_agent;
 pub mod human_agent;
 pub mod negamax;
+pub mod policy_agent;
 pub mod random_agent;
+pub mod scores;
 pub mod simple_agent;
+pub mod softmax_agent;
+#[cfg(feature = "test-agents")]
+pub mod test_agents;
+#[cfg(feature = "threads")]
+pub mod threaded_agent;
+pub mod time_manager;
+pub mod tree_recorder;
 
 pub use agent::*;
+pub use budget::{SearchBudget, SearchError};
+pub use cached_evaluator::CachedEvaluator;
+pub use counters::SearchCounters;
 pub use evaluator::*;
 pub use human_agent::HumanAgent;
 pub use negamax::NegaMax;
+pub use policy_agent::PolicyAgent;
 pub use random_agent::RandomAgent;
+pub use scores::ScoreBound;
 pub use simple_agent::SimpleAgent;
+pub use softmax_agent::SoftmaxAgent;
+#[cfg(feature = "threads")]
+pub use threaded_agent::ThreadedAgent;
+pub use time_manager::{LengthEstimate, MoveTimer, TimeManager};
+pub use tree_recorder::TreeRecorder;
 
-use crate::core::Game;
+use crate::core::{Game, GwState};
 
-/// Utility function to stable sort actions in descending order of heuristic value
-/// according to the given evaluator
+/// The key by which [sort_actions] orders an action: its heuristic value,
+/// its static order key and its index.
+type SortKey<T> = (T, i32, usize);
+
+/// Utility function to sort actions in ascending order of heuristic value
+/// according to the given evaluator, breaking ties by
+/// [static_order_key](crate::core::GwState::static_order_key) and then by
+/// position, as a stable sort would. The sorted keys are written into `keys`
+/// rather than reordering the actions, so that both buffers can be reused
+/// without allocating.
 fn sort_actions<G, E>(
     state: &G::State,
-    actions: &mut [G::Action],
+    actions: &[G::Action],
     evaluator: &mut E,
     for_team: &G::Team,
+    keys: &mut Vec<SortKey<G::EvalType>>,
 ) where
     G: Game,
     E: Evaluator<G>,
     G::EvalType: Ord,
 {
-    actions.sort_by_cached_key(|action| evaluator.evaluate_action_for(state, action, for_team));
+    keys.clear();
+    keys.extend(actions.iter().enumerate().map(|(i, action)| {
+        (
+            evaluator.evaluate_action_for(state, action, for_team),
+            state.static_order_key(action),
+            i,
+        )
+    }));
+    keys.sort_unstable();
+}
+
+/// Shortens a type name as returned by [std::any::type_name] by stripping
+/// module paths and generic parameters, e.g. `glasswing::agents::NegaMax<..>`
+/// becomes `NegaMax`.
+fn short_type_name(name: &str) -> &str {
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
 }
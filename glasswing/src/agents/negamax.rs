@@ -1,97 +1,598 @@
-use crate::agents::{sort_actions, Evaluator};
-use crate::core::{Game, GwState};
-use num_traits::Bounded;
-use smallvec::SmallVec;
+use crate::agents::{
+    sort_actions, Evaluator, ScoreBound, SearchBudget, SearchCounters, SearchError, SortKey,
+    TreeRecorder,
+};
+use crate::core::{Clock, Game, GwState, SystemClock};
+use std::borrow::Cow;
 use std::marker::PhantomData;
-use std::ops::Neg;
+use std::time::Duration;
 
-pub struct NegaMax<G, E>
+/// Number of nodes between two readings of the clock, see
+/// [NegaMax::with_check_interval].
+pub const DEFAULT_CHECK_INTERVAL: u64 = 256;
+
+type PlyBuffers<G> = (
+    Vec<<G as Game>::Action>,
+    Vec<SortKey<<G as Game>::EvalType>>,
+);
+
+pub struct NegaMax<G, E, C = SystemClock>
 where
     G: Game,
-    G::EvalType: Ord + Bounded + Neg<Output = G::EvalType> + Copy,
+    G::EvalType: ScoreBound,
     E: Evaluator<G>,
 {
     depth: u32,
     evaluator: E,
+    recorder: Option<TreeRecorder<G>>,
+    counters: SearchCounters,
+    budget: Option<SearchBudget>,
+    check_interval: u64,
+    clock: C,
+    // whether budgets span a move, see Evaluator::begin_move
+    per_move: bool,
+    // time at which the current move or root search started
+    started: Duration,
+    exhausted: bool,
+    hard_node_cap: Option<u64>,
+    return_best_on_cap: bool,
+    cap_exceeded: bool,
+    error: Option<SearchError>,
+    // one buffer of actions and one of their sort keys per ply, reused
+    // across nodes
+    buffers: Vec<PlyBuffers<G>>,
     _game: PhantomData<G>,
 }
 
 impl<G, E> NegaMax<G, E>
 where
     G: Game,
-    G::EvalType: Ord + Bounded + Neg<Output = G::EvalType> + Copy,
+    G::EvalType: ScoreBound,
     E: Evaluator<G>,
 {
     pub fn new(depth: u32, evaluator: E) -> Self {
         NegaMax {
             depth,
             evaluator,
+            recorder: None,
+            counters: SearchCounters::default(),
+            budget: None,
+            check_interval: DEFAULT_CHECK_INTERVAL,
+            clock: SystemClock::new(),
+            per_move: false,
+            started: Duration::ZERO,
+            exhausted: false,
+            hard_node_cap: None,
+            return_best_on_cap: false,
+            cap_exceeded: false,
+            error: None,
+            buffers: Vec::new(),
+            _game: PhantomData,
+        }
+    }
+}
+
+impl<G, E, C> NegaMax<G, E, C>
+where
+    G: Game,
+    G::EvalType: ScoreBound,
+    E: Evaluator<G>,
+    C: Clock,
+{
+    /// Replaces the clock used to measure time budgets.
+    pub fn with_clock<D: Clock>(self, clock: D) -> NegaMax<G, E, D> {
+        NegaMax {
+            depth: self.depth,
+            evaluator: self.evaluator,
+            recorder: self.recorder,
+            counters: self.counters,
+            budget: self.budget,
+            check_interval: self.check_interval,
+            clock,
+            per_move: self.per_move,
+            started: self.started,
+            exhausted: self.exhausted,
+            hard_node_cap: self.hard_node_cap,
+            return_best_on_cap: self.return_best_on_cap,
+            cap_exceeded: self.cap_exceeded,
+            error: self.error,
+            buffers: self.buffers,
             _game: PhantomData,
         }
     }
 
+    /// Limits the work done for each move. Agents announce a move with
+    /// [Evaluator::begin_move] before evaluating its actions, and the budget
+    /// covers all of them until [Evaluator::end_move]. Outside of a move,
+    /// each root search has its own budget.
+    ///
+    /// Once the budget is used up, no further children are searched and every
+    /// node returns the best value among its fully searched children, so
+    /// partly searched subtrees never outweigh completed ones. Actions of the
+    /// move evaluated afterwards are not searched and score the minimum.
+    pub fn with_budget(mut self, budget: SearchBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Reads the clock only every `interval` nodes when enforcing a time
+    /// budget, so a search may overrun it by up to `interval` nodes.
+    /// Node budgets are always checked exactly.
+    pub fn with_check_interval(mut self, interval: u64) -> Self {
+        self.check_interval = interval.max(1);
+        self
+    }
+
+    /// Aborts any move, or root search outside of a move, that visits more
+    /// than `nodes` nodes, as a guard against runaway searches. Unlike a budget, exceeding the cap is
+    /// an error: the search stops and reports
+    /// [SearchError::NodeCapExceeded] through [Evaluator::take_error], unless
+    /// [NegaMax::with_return_best_on_cap] is set.
+    pub fn with_hard_node_cap(mut self, nodes: u64) -> Self {
+        self.hard_node_cap = Some(nodes);
+        self
+    }
+
+    /// Whether a search that exceeds the hard node cap returns its best
+    /// score so far, like an exhausted budget, instead of reporting an error.
+    pub fn with_return_best_on_cap(mut self, return_best: bool) -> Self {
+        self.return_best_on_cap = return_best;
+        self
+    }
+
+    pub fn budget(&self) -> Option<SearchBudget> {
+        self.budget
+    }
+
+    /// Whether the current move, or the most recent root search, ran out of budget.
+    pub fn budget_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    pub fn evaluator_mut(&mut self) -> &mut E {
+        &mut self.evaluator
+    }
+
+    /// Records the tree explored by each search, up to `max_ply` plies below
+    /// the root and at most `max_nodes` nodes. Recording is off by default.
+    pub fn with_tree_recorder(mut self, max_ply: u32, max_nodes: usize) -> Self {
+        self.recorder = Some(TreeRecorder::new(max_ply, max_nodes));
+        self
+    }
+
+    /// The tree explored by the most recent search, if recording is enabled.
+    pub fn tree_recorder(&self) -> Option<&TreeRecorder<G>> {
+        self.recorder.as_ref()
+    }
+
+    /// Statistics of the current move, or of the most recent root search.
+    pub fn counters(&self) -> &SearchCounters {
+        &self.counters
+    }
+
+    /// Searches `state` as a root of its own, with a fresh budget.
     pub fn negamax(
+        &mut self,
+        state: &G::State,
+        depth: u32,
+        alpha: G::EvalType,
+        beta: G::EvalType,
+    ) -> G::EvalType {
+        self.start_budget();
+        self.search_root(state, depth, alpha, beta)
+    }
+
+    fn start_budget(&mut self) {
+        self.counters.reset();
+        self.started = self.clock.now();
+        self.exhausted = false;
+        self.cap_exceeded = false;
+    }
+
+    fn search_root(
+        &mut self,
+        state: &G::State,
+        depth: u32,
+        alpha: G::EvalType,
+        beta: G::EvalType,
+    ) -> G::EvalType {
+        let (alpha, beta) = (alpha.clamp_score(), beta.clamp_score());
+        let root = self.recorder.as_mut().and_then(|recorder| {
+            recorder.clear();
+            recorder.record(None, None, 0, alpha, beta)
+        });
+        self.search(state, depth, alpha, beta, root, 0)
+    }
+
+    fn search(
         &mut self,
         state: &G::State,
         depth: u32,
         mut alpha: G::EvalType,
         beta: G::EvalType,
+        node: Option<usize>,
+        ply: u32,
     ) -> G::EvalType {
+        self.counters.nodes += 1;
+        if !self.exhausted {
+            if let Some(budget) = self.budget {
+                let elapsed = match budget.time() {
+                    Some(_) if self.counters.nodes.is_multiple_of(self.check_interval) => {
+                        self.clock.elapsed_since(self.started)
+                    }
+                    _ => Duration::ZERO,
+                };
+                self.exhausted = budget.is_exhausted(self.counters.nodes, elapsed);
+            }
+            if self
+                .hard_node_cap
+                .is_some_and(|cap| self.counters.nodes > cap)
+            {
+                self.cap_exceeded = true;
+                self.exhausted = true;
+            }
+        }
+
         // In most games we hit the depth limit before we hit a terminal state,
         // therefore it is more efficient to check for the depth limit first.
-        if depth == 0 || state.is_terminal() {
-            return self.evaluator.evaluate_for(state, &state.team_to_move());
+        if depth == 0 || self.exhausted || state.is_terminal() {
+            self.counters.leaves += 1;
+            let eval = self
+                .evaluator
+                .evaluate_for(state, &state.team_to_move())
+                .clamp_score();
+            self.record_eval(node, eval);
+            return eval;
         }
 
         // Generate all legal actions from the current state and sort in ascending order of heuristic.
-        let mut actions = state
-            .actions()
-            .into_iter()
-            .collect::<SmallVec<[G::Action; 8]>>();
+        // The buffers are taken out of the pool for the duration of this node.
+        let ply_idx = ply as usize;
+        if self.buffers.len() <= ply_idx {
+            let capacity = G::MAX_ACTIONS.unwrap_or(0);
+            self.buffers.resize_with(ply_idx + 1, || {
+                (Vec::with_capacity(capacity), Vec::with_capacity(capacity))
+            });
+        }
+        let (mut actions, mut keys) = std::mem::take(&mut self.buffers[ply_idx]);
+        state.actions_into(&mut actions);
         sort_actions(
             state,
-            &mut actions,
+            &actions,
             &mut self.evaluator,
             &state.team_to_move(),
+            &mut keys,
         );
 
         // iterate in descending order as per negamax optimisation
-        let mut value = -G::EvalType::max_value();
-        for action in actions.iter().rev() {
-            let new_state = state.apply_action(&action);
-            let eval = -self.negamax(&new_state, depth - 1, -beta, -alpha);
+        let mut value = G::EvalType::score_min();
+        let mut searched = false;
+        for &(_, _, i) in keys.iter().rev() {
+            let action = &actions[i];
+            let new_state = state.apply_action(action);
+            // games may let a team move several times in a row, in which case
+            // the child is searched from the same point of view
+            let same_team = new_state.team_to_move() == state.team_to_move();
+            let (child_alpha, child_beta) = if same_team {
+                (alpha, beta)
+            } else {
+                (-beta, -alpha)
+            };
+            let child = match (node, self.recorder.as_mut()) {
+                (Some(node), Some(recorder)) => {
+                    recorder.record(Some(node), Some(action), ply + 1, child_alpha, child_beta)
+                }
+                _ => None,
+            };
+            let eval = self.search(
+                &new_state,
+                depth - 1,
+                child_alpha,
+                child_beta,
+                child,
+                ply + 1,
+            );
+            let eval = if same_team {
+                eval
+            } else {
+                eval.saturating_negate()
+            };
+            if self.exhausted {
+                // the budget ran out inside this child, so its value is only
+                // used if no sibling has been searched in full
+                if !searched {
+                    value = value.max(eval);
+                }
+                break;
+            }
+            searched = true;
             value = value.max(eval);
             alpha = alpha.max(value);
             if alpha >= beta {
+                self.counters.cutoffs += 1;
+                if let (Some(node), Some(recorder)) = (node, self.recorder.as_mut()) {
+                    recorder.set_cutoff(node);
+                }
                 break; // (* cut-off *)
             }
         }
+        self.buffers[ply_idx] = (actions, keys);
+        self.record_eval(node, value);
         value
     }
+
+    #[inline]
+    fn record_eval(&mut self, node: Option<usize>, eval: G::EvalType) {
+        if let (Some(node), Some(recorder)) = (node, self.recorder.as_mut()) {
+            recorder.set_eval(node, eval);
+        }
+    }
 }
 
-impl<G, E> Evaluator<G> for NegaMax<G, E>
+impl<G, E, C> Evaluator<G> for NegaMax<G, E, C>
 where
     G: Game,
-    G::EvalType: Ord + Bounded + Neg<Output = G::EvalType> + Copy,
+    G::EvalType: ScoreBound,
     E: Evaluator<G>,
+    C: Clock,
 {
     fn evaluate_for(&mut self, state: &G::State, for_team: &G::Team) -> G::EvalType {
+        if !self.per_move {
+            self.start_budget();
+        } else if self.exhausted {
+            // the budget of the move ran out before this action
+            return G::EvalType::score_min();
+        }
+        let eval = self.search_root(
+            state,
+            self.depth,
+            G::EvalType::score_min(),
+            G::EvalType::score_max(),
+        );
+        if self.cap_exceeded && !self.return_best_on_cap {
+            let nodes = self.hard_node_cap.unwrap_or_default();
+            self.error
+                .get_or_insert(SearchError::NodeCapExceeded { nodes });
+        }
         if state.team_to_move() == *for_team {
-            // Hacky workaround to avoid overflow. TODO fix properly.
-            self.negamax(
-                state,
-                self.depth,
-                -G::EvalType::max_value(),
-                G::EvalType::max_value(),
-            )
+            eval
         } else {
-            -self.negamax(
-                state,
-                self.depth,
-                -G::EvalType::max_value(),
-                G::EvalType::max_value(),
-            )
+            eval.saturating_negate()
+        }
+    }
+
+    fn begin_move(&mut self) {
+        self.per_move = true;
+        self.start_budget();
+    }
+
+    fn end_move(&mut self) {
+        self.per_move = false;
+    }
+
+    /// Limits the time of the following moves to the hard limit, keeping a
+    /// node budget if there is one. NegaMax searches to a fixed depth, so
+    /// there is no point at which it could stop early after the soft limit.
+    fn set_time_budget(&mut self, _soft: Duration, hard: Duration) {
+        self.budget = Some(match self.budget.and_then(|budget| budget.nodes()) {
+            Some(nodes) => SearchBudget::Both(hard, nodes),
+            None => SearchBudget::Time(hard),
+        });
+    }
+
+    /// Limits the nodes of the following moves, keeping a time budget if
+    /// there is one.
+    fn set_node_budget(&mut self, nodes: u64) {
+        self.budget = Some(match self.budget.and_then(|budget| budget.time()) {
+            Some(time) => SearchBudget::Both(time, nodes),
+            None => SearchBudget::Nodes(nodes),
+        });
+    }
+
+    fn take_error(&mut self) -> Option<SearchError> {
+        self.error.take()
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("NegaMax(d={})", self.depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{Agent, MaximisingAgent, TimeManager};
+    use crate::core::{ManualClock, Team};
+    use crate::testing::counting::{CountingEvaluator, CountingGame, CountingState, TARGET};
+
+    /// Scores positions won or lost by the perfect play rule with the
+    /// extremes of `i32`.
+    struct ExtremeEvaluator;
+
+    impl Evaluator<CountingGame> for ExtremeEvaluator {
+        fn evaluate_for(&mut self, state: &CountingState, team: &Team) -> i32 {
+            let mut perfect = CountingEvaluator { perfect: true };
+            match perfect.evaluate_for(state, team) {
+                score if score > 0 => i32::MAX,
+                score if score < 0 => i32::MIN,
+                _ => 0,
+            }
+        }
+    }
+
+    #[test]
+    fn extreme_evaluations_are_clamped() {
+        let mut search = NegaMax::new(5, ExtremeEvaluator);
+        for count in 0..TARGET {
+            let state = CountingState::new(count, Team::One);
+            let eval = search.negamax(&state, 5, i32::MIN, i32::MAX);
+            let expected = if state.is_lost() { -i32::MAX } else { i32::MAX };
+            assert_eq!(eval, expected, "count {}", count);
+        }
+
+        let mut agent = MaximisingAgent::new(NegaMax::new(4, ExtremeEvaluator));
+        assert_eq!(
+            agent.select_action(&CountingGame::initial_state()).unwrap(),
+            1
+        );
+    }
+
+    fn budgeted(
+        depth: u32,
+        nodes: u64,
+    ) -> MaximisingAgent<CountingGame, NegaMax<CountingGame, CountingEvaluator>> {
+        let search = NegaMax::new(depth, CountingEvaluator { perfect: false })
+            .with_budget(SearchBudget::Nodes(nodes));
+        MaximisingAgent::new(search)
+    }
+
+    #[test]
+    fn node_budget_covers_the_whole_move() {
+        let state = CountingGame::initial_state();
+        for nodes in [1, 7, 100, 1000] {
+            let mut agent = budgeted(12, nodes);
+            agent.select_action(&state).unwrap();
+            assert!(agent.evaluator().budget_exhausted());
+            assert_eq!(agent.evaluator().counters().nodes, nodes);
+        }
+    }
+
+    #[test]
+    fn budget_is_reset_for_every_move() {
+        let mut agent = budgeted(12, 100);
+        let mut state = CountingGame::initial_state();
+        for _ in 0..3 {
+            let action = agent.select_action(&state).unwrap();
+            assert_eq!(agent.evaluator().counters().nodes, 100);
+            state = state.apply_action(&action);
+        }
+    }
+
+    #[test]
+    fn evaluations_after_a_move_have_their_own_budget() {
+        let mut agent = budgeted(12, 10);
+        let state = CountingGame::initial_state();
+        agent.select_action(&state).unwrap();
+        assert!(agent.evaluator().budget_exhausted());
+
+        // e.g. analysing the position once the move has been played
+        let search = agent.evaluator_mut();
+        for _ in 0..2 {
+            let eval = search.evaluate_for(&state, &Team::One);
+            assert_ne!(eval, i32::score_min());
+            assert_eq!(search.counters().nodes, 10);
+        }
+    }
+
+    #[test]
+    fn unsearched_actions_are_not_chosen() {
+        // with the perfect evaluator, searching only the first action
+        // already finds a forced win, which must not be overridden by the
+        // actions left unsearched
+        let search = NegaMax::new(1, CountingEvaluator { perfect: true })
+            .with_budget(SearchBudget::Nodes(1));
+        let mut agent = MaximisingAgent::new(search);
+        let state = CountingState::new(16, Team::One);
+        assert_eq!(agent.select_action(&state).unwrap(), 1);
+
+        // the winning action 3 is never searched, so the agent settles for
+        // the best action it has searched
+        let mut agent = budgeted(1, 2);
+        let state = CountingState::new(18, Team::One);
+        assert_eq!(agent.select_action(&state).unwrap(), 1);
+        assert_eq!(agent.evaluator().counters().nodes, 2);
+    }
+
+    #[test]
+    fn budgeted_moves_are_reproducible() {
+        let play = || {
+            let mut agent = budgeted(10, 500);
+            let mut state = CountingGame::initial_state();
+            let mut moves = Vec::new();
+            while !state.is_terminal() {
+                let action = agent.select_action(&state).unwrap();
+                moves.push((action, *agent.evaluator().counters()));
+                state = state.apply_action(&action);
+            }
+            moves
+        };
+        let first = play();
+        assert!(first.len() > 1);
+        for _ in 0..5 {
+            assert_eq!(play(), first);
+        }
+    }
+
+    /// Advances the clock by a millisecond per evaluation.
+    struct SlowEvaluator(ManualClock);
+
+    impl Evaluator<CountingGame> for SlowEvaluator {
+        fn evaluate_for(&mut self, state: &CountingState, team: &Team) -> i32 {
+            self.0.advance(Duration::from_millis(1));
+            CountingEvaluator { perfect: false }.evaluate_for(state, team)
         }
     }
+
+    #[test]
+    fn time_budget_covers_the_whole_move() {
+        let clock = ManualClock::new();
+        let limit = Duration::from_millis(50);
+        let search = NegaMax::new(12, SlowEvaluator(clock.clone()))
+            .with_clock(clock.clone())
+            .with_check_interval(1)
+            .with_budget(SearchBudget::Time(limit));
+        let mut agent = MaximisingAgent::new(search);
+
+        let mut state = CountingGame::initial_state();
+        for _ in 0..3 {
+            let started = clock.now();
+            let action = agent.select_action(&state).unwrap();
+            // a node evaluates at most three actions for move ordering
+            // before the next clock reading
+            let elapsed = clock.elapsed_since(started);
+            assert!(elapsed >= limit, "{:?}", elapsed);
+            assert!(elapsed <= limit + Duration::from_millis(4), "{:?}", elapsed);
+            state = state.apply_action(&action);
+        }
+    }
+
+    #[test]
+    fn time_manager_budgets_a_game() {
+        let clock = ManualClock::new();
+        let search = NegaMax::new(12, SlowEvaluator(clock.clone()))
+            .with_clock(clock.clone())
+            .with_check_interval(1)
+            .with_budget(SearchBudget::Nodes(1_000_000));
+        let mut agent = MaximisingAgent::new(search);
+        let manager = TimeManager::new().with_fraction(0.1);
+
+        let mut remaining = Duration::from_secs(10);
+        let mut state = CountingGame::initial_state();
+        for _ in 0..10 {
+            let (soft, hard) = manager.budget(remaining);
+            agent.set_time_budget(soft, hard);
+            assert_eq!(
+                agent.evaluator().budget(),
+                Some(SearchBudget::Both(hard, 1_000_000))
+            );
+
+            let started = clock.now();
+            let action = agent.select_action(&state).unwrap();
+            let elapsed = clock.elapsed_since(started);
+            assert!(elapsed <= hard + Duration::from_millis(4), "{:?}", elapsed);
+            remaining = remaining.saturating_sub(elapsed);
+            state = state.apply_action(&action);
+        }
+        // every move used at most 30% of the time left, plus the overshoot
+        let expected = Duration::from_secs(10).mul_f64(0.7f64.powi(10));
+        assert!(
+            remaining + Duration::from_millis(40) >= expected,
+            "{:?}",
+            remaining
+        );
+    }
 }
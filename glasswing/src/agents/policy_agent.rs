@@ -0,0 +1,11 @@
+use crate::agents::Agent;
+use crate::core::Game;
+use anyhow::Error;
+
+/// An agent that can report the full distribution over actions it selects
+/// from, e.g. as a training target for a policy.
+pub trait PolicyAgent<G: Game>: Agent<G> {
+    /// The probability of each legal action being selected. The
+    /// probabilities sum to 1 and only legal actions are included.
+    fn action_distribution(&mut self, state: &G::State) -> Result<Vec<(G::Action, f32)>, Error>;
+}
@@ -1,5 +1,5 @@
-use crate::agents::Agent;
-use crate::core::{Game, GwState};
+use crate::agents::{Agent, PolicyAgent};
+use crate::core::{Game, GwState, MatchError};
 use anyhow::Error;
 use rand::prelude::{IteratorRandom, ThreadRng};
 use rand::Rng;
@@ -35,3 +35,17 @@ impl<G: Game, R: Rng> Agent<G> for RandomAgent<G, R> {
             .clone())
     }
 }
+
+impl<G: Game, R: Rng> PolicyAgent<G> for RandomAgent<G, R> {
+    fn action_distribution(&mut self, state: &G::State) -> Result<Vec<(G::Action, f32)>, Error> {
+        let actions = state.actions().into_iter().collect::<Vec<G::Action>>();
+        if actions.is_empty() {
+            return Err(MatchError::<G>::NoAvailableActions(state.clone()).into());
+        }
+        let probability = 1.0 / actions.len() as f32;
+        Ok(actions
+            .into_iter()
+            .map(|action| (action, probability))
+            .collect())
+    }
+}
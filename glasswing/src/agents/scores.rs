@@ -0,0 +1,202 @@
+use num_traits::Bounded;
+use std::ops::Neg;
+
+/// A symmetric window of scores that can always be negated without
+/// overflow. For two's complement integers, `-i32::MIN` overflows, so the
+/// window is `[-i32::MAX, i32::MAX]`.
+///
+/// Searches clamp evaluator outputs into this window, so evaluators may
+/// return any value of their type. Floating point scores can be used by
+/// wrapping them in `ordered_float::OrderedFloat`, which is totally ordered
+/// and bounded.
+pub trait ScoreBound: Copy + Ord + Neg<Output = Self> {
+    fn score_max() -> Self;
+
+    #[inline]
+    fn score_min() -> Self {
+        -Self::score_max()
+    }
+
+    #[inline]
+    fn clamp_score(self) -> Self {
+        self.clamp(Self::score_min(), Self::score_max())
+    }
+
+    /// Negates the score after clamping it into the window.
+    #[inline]
+    fn saturating_negate(self) -> Self {
+        -self.clamp_score()
+    }
+}
+
+impl<T> ScoreBound for T
+where
+    T: Copy + Ord + Neg<Output = T> + Bounded,
+{
+    #[inline]
+    fn score_max() -> Self {
+        T::max_value()
+    }
+}
+
+/// Score of a won game, before subtracting the ply of the win.
+///
+/// Decisive scores encode the ply at which the game ends, counted from the
+/// start of the game: a win after `n` plies scores `WIN_SCORE - n` and a
+/// loss after `n` plies scores `LOSS_SCORE + n`, so that quicker wins and
+/// slower losses are preferred. As the count does not depend on the search
+/// root, a terminal state scores the same in every search. Scores counted
+/// from another position instead, such as those of a transposition table or
+/// [TbValue](crate::tablebase::TbValue), are converted with [score_to_table]
+/// and [score_from_table]. Heuristic scores must stay within
+/// `±(WIN_SCORE - MAX_DECISIVE_PLIES)`.
+///
+/// Only `i32` scores are supported. Floating point evaluators can wrap
+/// integer decisive scores, but the helpers are not provided for them.
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// Score of a lost game, before adding the ply of the loss.
+pub const LOSS_SCORE: i32 = -WIN_SCORE;
+
+/// Largest ply that a decisive score can encode.
+pub const MAX_DECISIVE_PLIES: i32 = 10_000;
+
+/// The score of a win after `plies` plies.
+#[inline]
+pub fn win_in(plies: u32) -> i32 {
+    WIN_SCORE - plies.min(MAX_DECISIVE_PLIES as u32) as i32
+}
+
+/// The score of a loss after `plies` plies.
+#[inline]
+pub fn loss_in(plies: u32) -> i32 {
+    -win_in(plies)
+}
+
+/// Whether the score is a forced win or loss rather than a heuristic.
+#[inline]
+pub fn is_decisive(score: i32) -> bool {
+    score.saturating_abs() >= WIN_SCORE - MAX_DECISIVE_PLIES
+}
+
+/// The ply at which the game is won, if the score is a decisive win.
+#[inline]
+pub fn plies_to_win(score: i32) -> Option<u32> {
+    (is_decisive(score) && score > 0).then(|| (WIN_SCORE - score.min(WIN_SCORE)) as u32)
+}
+
+/// The ply at which the game is lost, if the score is a decisive loss.
+#[inline]
+pub fn plies_to_loss(score: i32) -> Option<u32> {
+    (is_decisive(score) && score < 0).then(|| (score.max(LOSS_SCORE) - LOSS_SCORE) as u32)
+}
+
+/// Converts a decisive score counted from the start of the game into one
+/// counted from a position `ply` plies into the game, for storing it in a
+/// transposition table that is shared between positions at different plies.
+/// Heuristic scores are returned unchanged.
+#[inline]
+pub fn score_to_table(score: i32, ply: u32) -> i32 {
+    let ply = ply.min(MAX_DECISIVE_PLIES as u32) as i32;
+    if !is_decisive(score) {
+        score
+    } else if score > 0 {
+        score.saturating_add(ply).min(WIN_SCORE)
+    } else {
+        score.saturating_sub(ply).max(LOSS_SCORE)
+    }
+}
+
+/// Inverse of [score_to_table]: converts a score counted from a position
+/// `ply` plies into the game back into one counted from the start.
+#[inline]
+pub fn score_from_table(score: i32, ply: u32) -> i32 {
+    if !is_decisive(score) {
+        score
+    } else if score > 0 {
+        win_in(plies_to_win(score).unwrap().saturating_add(ply))
+    } else {
+        loss_in(plies_to_loss(score).unwrap().saturating_add(ply))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ordered_float::OrderedFloat;
+
+    const THRESHOLD: i32 = WIN_SCORE - MAX_DECISIVE_PLIES;
+
+    #[test]
+    fn negating_the_extremes_does_not_overflow() {
+        assert_eq!(i32::MIN.clamp_score(), -i32::MAX);
+        assert_eq!(i32::MIN.saturating_negate(), i32::MAX);
+        assert_eq!(i32::MAX.saturating_negate(), -i32::MAX);
+        assert_eq!(i64::MIN.saturating_negate(), i64::MAX);
+        assert_eq!(
+            OrderedFloat(f64::NEG_INFINITY).saturating_negate(),
+            OrderedFloat(f64::MAX)
+        );
+    }
+
+    #[test]
+    fn decisive_boundaries() {
+        assert!(is_decisive(THRESHOLD));
+        assert!(is_decisive(-THRESHOLD));
+        assert!(!is_decisive(THRESHOLD - 1));
+        assert!(!is_decisive(-THRESHOLD + 1));
+        assert!(is_decisive(i32::MAX));
+        assert!(is_decisive(i32::MIN));
+        assert!(!is_decisive(0));
+    }
+
+    #[test]
+    fn plies_at_the_boundaries() {
+        assert_eq!(plies_to_win(WIN_SCORE), Some(0));
+        assert_eq!(plies_to_win(THRESHOLD), Some(MAX_DECISIVE_PLIES as u32));
+        assert_eq!(plies_to_win(THRESHOLD - 1), None);
+        assert_eq!(plies_to_win(i32::MAX), Some(0));
+        assert_eq!(plies_to_win(LOSS_SCORE), None);
+
+        assert_eq!(plies_to_loss(LOSS_SCORE), Some(0));
+        assert_eq!(plies_to_loss(-THRESHOLD), Some(MAX_DECISIVE_PLIES as u32));
+        assert_eq!(plies_to_loss(-THRESHOLD + 1), None);
+        assert_eq!(plies_to_loss(i32::MIN), Some(0));
+        assert_eq!(plies_to_loss(WIN_SCORE), None);
+
+        assert_eq!(
+            plies_to_win(win_in(u32::MAX)),
+            Some(MAX_DECISIVE_PLIES as u32)
+        );
+        assert_eq!(plies_to_loss(loss_in(7)), Some(7));
+    }
+
+    #[test]
+    fn win_in_three_from_the_table_at_another_ply() {
+        // found in a search of a position at ply 4, winning at ply 7
+        let stored = score_to_table(win_in(7), 4);
+        assert_eq!(plies_to_win(stored), Some(3));
+
+        // the same position reached at ply 10 in another game still wins in 3
+        let loaded = score_from_table(stored, 10);
+        assert_eq!(plies_to_win(loaded), Some(13));
+        assert_eq!(score_to_table(loaded, 10), stored);
+
+        let stored = score_to_table(loss_in(7), 4);
+        assert_eq!(plies_to_loss(score_from_table(stored, 10)), Some(13));
+    }
+
+    #[test]
+    fn heuristic_scores_are_stored_unchanged() {
+        assert_eq!(score_to_table(THRESHOLD - 1, 50), THRESHOLD - 1);
+        assert_eq!(score_from_table(-THRESHOLD + 1, 50), -THRESHOLD + 1);
+    }
+
+    #[test]
+    fn table_conversion_saturates() {
+        assert_eq!(score_to_table(i32::MAX, 5), WIN_SCORE);
+        assert_eq!(score_to_table(i32::MIN, 5), LOSS_SCORE);
+        assert_eq!(score_from_table(WIN_SCORE, u32::MAX), win_in(u32::MAX));
+        assert_eq!(score_from_table(LOSS_SCORE, u32::MAX), loss_in(u32::MAX));
+    }
+}
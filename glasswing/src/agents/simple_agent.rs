@@ -1,4 +1,4 @@
-use crate::agents::Agent;
+use crate::agents::{Agent, PolicyAgent};
 use crate::core::state::*;
 use crate::core::{Game, MatchError};
 use anyhow::Error;
@@ -29,3 +29,18 @@ impl<G: Game> Agent<G> for SimpleAgent<G> {
             .ok_or_else(|| MatchError::<G>::NoAvailableActions(state.clone()).into())
     }
 }
+
+impl<G: Game> PolicyAgent<G> for SimpleAgent<G> {
+    fn action_distribution(&mut self, state: &G::State) -> Result<Vec<(G::Action, f32)>, Error> {
+        let distribution = state
+            .actions()
+            .into_iter()
+            .enumerate()
+            .map(|(i, action)| (action, if i == 0 { 1.0 } else { 0.0 }))
+            .collect::<Vec<_>>();
+        if distribution.is_empty() {
+            return Err(MatchError::<G>::NoAvailableActions(state.clone()).into());
+        }
+        Ok(distribution)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::agents::{Agent, Evaluator, PolicyAgent};
+use crate::core::{Game, GwState, MatchError};
+use anyhow::Error;
+use rand::Rng;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Selects actions at random, weighted by the softmax of their evaluations
+/// divided by the temperature. A temperature of zero always selects the
+/// first action with the best evaluation.
+pub struct SoftmaxAgent<G, E, R>
+where
+    G: Game,
+    E: Evaluator<G>,
+    R: Rng,
+{
+    evaluator: E,
+    temperature: f64,
+    rng: R,
+    _game: PhantomData<G>,
+}
+
+impl<G, E, R> SoftmaxAgent<G, E, R>
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G>,
+    R: Rng,
+{
+    pub fn new(evaluator: E, temperature: f64, rng: R) -> Self {
+        SoftmaxAgent {
+            evaluator,
+            temperature: temperature.max(0.0),
+            rng,
+            _game: PhantomData,
+        }
+    }
+
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    pub fn evaluator_mut(&mut self) -> &mut E {
+        &mut self.evaluator
+    }
+
+    fn weights(&mut self, state: &G::State) -> Result<(Vec<G::Action>, Vec<f64>), Error> {
+        let team = state.team_to_move();
+        let actions = state.actions().into_iter().collect::<Vec<G::Action>>();
+        if actions.is_empty() {
+            return Err(MatchError::<G>::NoAvailableActions(state.clone()).into());
+        }
+        self.evaluator.begin_move();
+        let evals = actions
+            .iter()
+            .map(|action| {
+                self.evaluator
+                    .evaluate_action_for(state, action, &team)
+                    .into()
+            })
+            .collect::<Vec<f64>>();
+        self.evaluator.end_move();
+        if let Some(err) = self.evaluator.take_error() {
+            return Err(err.into());
+        }
+        let best = evals.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        let weights = if self.temperature == 0.0 {
+            let first_best = evals.iter().position(|&eval| eval == best).unwrap();
+            (0..evals.len())
+                .map(|i| if i == first_best { 1.0 } else { 0.0 })
+                .collect()
+        } else {
+            // shift by the best evaluation to avoid overflowing exp
+            evals
+                .iter()
+                .map(|eval| ((eval - best) / self.temperature).exp())
+                .collect()
+        };
+        Ok((actions, weights))
+    }
+}
+
+impl<G, E, R> Agent<G> for SoftmaxAgent<G, E, R>
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G>,
+    R: Rng,
+{
+    fn select_action(&mut self, state: &G::State) -> Result<G::Action, Error> {
+        let (mut actions, weights) = self.weights(state)?;
+        let mut target = self.rng.gen::<f64>() * weights.iter().sum::<f64>();
+        let mut selected = actions.len() - 1;
+        for (i, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                selected = i;
+                break;
+            }
+            target -= weight;
+        }
+        Ok(actions.swap_remove(selected))
+    }
+
+    fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+        self.evaluator.set_time_budget(soft, hard);
+    }
+
+    fn set_node_budget(&mut self, nodes: u64) {
+        self.evaluator.set_node_budget(nodes);
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!(
+            "Softmax({}, t={})",
+            self.evaluator.name(),
+            self.temperature
+        ))
+    }
+}
+
+impl<G, E, R> PolicyAgent<G> for SoftmaxAgent<G, E, R>
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G>,
+    R: Rng,
+{
+    fn action_distribution(&mut self, state: &G::State) -> Result<Vec<(G::Action, f32)>, Error> {
+        let (actions, weights) = self.weights(state)?;
+        let total = weights.iter().sum::<f64>();
+        Ok(actions
+            .into_iter()
+            .zip(weights)
+            .map(|(action, weight)| (action, (weight / total) as f32))
+            .collect())
+    }
+}
@@ -0,0 +1,119 @@
+//! Pathological agents for testing the rules of games.
+
+use crate::agents::{Agent, Evaluator, PrepareInfo};
+use crate::core::{Game, GwState, MatchError};
+use anyhow::Error;
+use std::marker::PhantomData;
+
+/// Plays the action its opponent played last, if it is legal, and the first
+/// legal action otherwise. The opponent's actions are tracked through
+/// [Agent::observe].
+pub struct MirrorAgent<G: Game> {
+    team: Option<G::Team>,
+    last: Option<G::Action>,
+}
+
+impl<G: Game> MirrorAgent<G> {
+    pub fn new() -> Self {
+        MirrorAgent {
+            team: None,
+            last: None,
+        }
+    }
+}
+
+impl<G: Game> Default for MirrorAgent<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G> Agent<G> for MirrorAgent<G>
+where
+    G: Game,
+    G::Action: PartialEq,
+{
+    fn select_action(&mut self, state: &G::State) -> Result<G::Action, Error> {
+        self.team = Some(state.team_to_move());
+        let actions = state.actions().into_iter().collect::<Vec<G::Action>>();
+
+        match self.last.take() {
+            Some(action) if actions.contains(&action) => Ok(action),
+            _ => actions
+                .into_iter()
+                .next()
+                .ok_or_else(|| MatchError::<G>::NoAvailableActions(state.clone()).into()),
+        }
+    }
+
+    fn prepare(&mut self, _initial_state: &G::State, info: &PrepareInfo<G>) {
+        self.team = Some(info.team.clone());
+        self.last = None;
+    }
+
+    fn observe(&mut self, prev: &G::State, action: &G::Action, _next: &G::State) {
+        if self.team.as_ref() != Some(&prev.team_to_move()) {
+            self.last = Some(action.clone());
+        }
+    }
+}
+
+/// Plays the action after which the opponent has the most legal actions.
+pub struct StallAgent<G> {
+    _marker: PhantomData<G>,
+}
+
+impl<G: Game> StallAgent<G> {
+    pub fn new() -> Self {
+        StallAgent {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<G: Game> Default for StallAgent<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: Game> Agent<G> for StallAgent<G> {
+    fn select_action(&mut self, state: &G::State) -> Result<G::Action, Error> {
+        state
+            .actions()
+            .into_iter()
+            .max_by_key(|action| state.apply_action(action).count_actions())
+            .ok_or_else(|| MatchError::<G>::NoAvailableActions(state.clone()).into())
+    }
+}
+
+/// Plays the action that the evaluator rates worst for itself.
+pub struct SuicideAgent<G, E> {
+    evaluator: E,
+    _marker: PhantomData<G>,
+}
+
+impl<G: Game, E: Evaluator<G>> SuicideAgent<G, E> {
+    pub fn new(evaluator: E) -> Self {
+        SuicideAgent {
+            evaluator,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<G, E> Agent<G> for SuicideAgent<G, E>
+where
+    G: Game,
+    G::EvalType: Ord,
+    E: Evaluator<G>,
+{
+    fn select_action(&mut self, state: &G::State) -> Result<G::Action, Error> {
+        let team = state.team_to_move();
+        state
+            .actions()
+            .into_iter()
+            .min_by_key(|action| self.evaluator.evaluate_action_for(state, action, &team))
+            .ok_or_else(|| MatchError::<G>::NoAvailableActions(state.clone()).into())
+    }
+}
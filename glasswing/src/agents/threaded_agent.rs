@@ -0,0 +1,379 @@
+use crate::agents::{Agent, PrepareInfo};
+use crate::core::{Clock, Game, MatchError, SystemClock};
+use anyhow::{anyhow, Error};
+use std::borrow::Cow;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A call forwarded to the agent thread, handled in the order it was sent.
+enum Request<G: Game> {
+    Select(u64, G::State),
+    Prepare(G::State, G::Team),
+    Observe(G::State, G::Action, G::State),
+    AcceptSwap(u64, G::State),
+    TimeBudget(Duration, Duration),
+    NodeBudget(u64),
+}
+
+enum Reply<G: Game> {
+    Action(u64, Result<G::Action, Error>, Duration),
+    Swap(u64, bool),
+}
+
+/// Runs an agent on its own thread and enforces a time limit per move.
+///
+/// This is meant for agents that block, such as human or network agents.
+/// If the agent does not reply in time, [MatchError::TimeLimitExceeded] is
+/// returned; its late reply is discarded once it arrives. A limit of
+/// [Duration::MAX] waits for the agent indefinitely. Calls to
+/// [Agent::prepare], [Agent::observe], [Agent::set_time_budget] and
+/// [Agent::set_node_budget] are passed on without waiting,
+/// and [Agent::accept_swap] declines if the agent does not answer in time.
+///
+/// When dropped, the thread is joined if the agent is idle. An agent still
+/// busy with a timed out request cannot be interrupted, so its thread is
+/// detached instead and exits once the agent returns.
+pub struct ThreadedAgent<G: Game, C = SystemClock> {
+    requests: Option<mpsc::Sender<Request<G>>>,
+    replies: mpsc::Receiver<Reply<G>>,
+    handle: Option<JoinHandle<()>>,
+    clock: C,
+    time_limit: Duration,
+    next_id: u64,
+    outstanding: u64,
+    reported: Option<Duration>,
+    name: String,
+}
+
+impl<G> ThreadedAgent<G>
+where
+    G: Game,
+    G::State: Send,
+    G::Action: Send,
+    G::Team: Send,
+{
+    pub fn spawn<A>(agent: A, time_limit: Duration) -> Self
+    where
+        A: Agent<G> + Send + 'static,
+    {
+        Self::spawn_with_clock(agent, time_limit, SystemClock::new())
+    }
+}
+
+impl<G, C> ThreadedAgent<G, C>
+where
+    G: Game,
+    G::State: Send,
+    G::Action: Send,
+    G::Team: Send,
+    C: Clock + Clone + Send + 'static,
+{
+    /// Like [ThreadedAgent::spawn], measuring time with the given clock.
+    pub fn spawn_with_clock<A>(mut agent: A, time_limit: Duration, clock: C) -> Self
+    where
+        A: Agent<G> + Send + 'static,
+    {
+        let name = format!("Threaded({})", agent.name());
+        let (requests, worker_requests) = mpsc::channel::<Request<G>>();
+        let (worker_replies, replies) = mpsc::channel::<Reply<G>>();
+        let worker_clock = clock.clone();
+        let handle = thread::spawn(move || {
+            for request in worker_requests {
+                let reply = match request {
+                    Request::Select(id, state) => {
+                        let start = worker_clock.now();
+                        let result = agent.select_action(&state);
+                        Reply::Action(id, result, worker_clock.elapsed_since(start))
+                    }
+                    Request::Prepare(state, team) => {
+                        agent.prepare(&state, &PrepareInfo { team });
+                        continue;
+                    }
+                    Request::Observe(prev, action, next) => {
+                        agent.observe(&prev, &action, &next);
+                        continue;
+                    }
+                    Request::AcceptSwap(id, state) => Reply::Swap(id, agent.accept_swap(&state)),
+                    Request::TimeBudget(soft, hard) => {
+                        agent.set_time_budget(soft, hard);
+                        continue;
+                    }
+                    Request::NodeBudget(nodes) => {
+                        agent.set_node_budget(nodes);
+                        continue;
+                    }
+                };
+                if worker_replies.send(reply).is_err() {
+                    break;
+                }
+            }
+        });
+
+        ThreadedAgent {
+            requests: Some(requests),
+            replies,
+            handle: Some(handle),
+            clock,
+            time_limit,
+            next_id: 0,
+            outstanding: 0,
+            reported: None,
+            name,
+        }
+    }
+}
+
+impl<G: Game, C> ThreadedAgent<G, C> {
+    pub fn time_limit(&self) -> Duration {
+        self.time_limit
+    }
+
+    fn send(&self, request: Request<G>) -> Result<(), Error> {
+        self.requests
+            .as_ref()
+            .and_then(|requests| requests.send(request).ok())
+            .ok_or_else(|| anyhow!("agent thread stopped"))
+    }
+}
+
+impl<G: Game, C: Clock> ThreadedAgent<G, C> {
+    /// Sends a request that expects a reply and returns its id.
+    fn request(&mut self, request: impl FnOnce(u64) -> Request<G>) -> Result<u64, Error> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(request(id))?;
+        self.outstanding += 1;
+        Ok(id)
+    }
+
+    /// Waits for the reply to the request `id`, discarding late replies to
+    /// earlier requests, until the time limit runs out.
+    fn wait_for(&mut self, id: u64) -> Result<Reply<G>, Error> {
+        let deadline = self.clock.now().checked_add(self.time_limit);
+        loop {
+            let received = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_sub(self.clock.now());
+                    self.replies.recv_timeout(remaining)
+                }
+                None => self
+                    .replies
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(reply) => {
+                    self.outstanding -= 1;
+                    let reply_id = match &reply {
+                        Reply::Action(reply_id, ..) | Reply::Swap(reply_id, _) => *reply_id,
+                    };
+                    if reply_id == id {
+                        return Ok(reply);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(MatchError::<G>::TimeLimitExceeded(self.time_limit).into());
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(anyhow!("agent thread stopped"));
+                }
+            }
+        }
+    }
+}
+
+impl<G, C> Agent<G> for ThreadedAgent<G, C>
+where
+    G: Game,
+    C: Clock,
+{
+    fn select_action(&mut self, state: &G::State) -> Result<G::Action, Error> {
+        self.reported = None;
+        let id = self.request(|id| Request::Select(id, state.clone()))?;
+        match self.wait_for(id)? {
+            Reply::Action(_, result, thinking) => {
+                self.reported = Some(thinking);
+                result
+            }
+            Reply::Swap(..) => unreachable!("reply ids are unique"),
+        }
+    }
+
+    fn prepare(&mut self, initial_state: &G::State, info: &PrepareInfo<G>) {
+        let _ = self.send(Request::Prepare(initial_state.clone(), info.team.clone()));
+    }
+
+    fn observe(&mut self, prev: &G::State, action: &G::Action, next: &G::State) {
+        let _ = self.send(Request::Observe(prev.clone(), action.clone(), next.clone()));
+    }
+
+    fn accept_swap(&mut self, state: &G::State) -> bool {
+        let Ok(id) = self.request(|id| Request::AcceptSwap(id, state.clone())) else {
+            return false;
+        };
+        matches!(self.wait_for(id), Ok(Reply::Swap(_, true)))
+    }
+
+    fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+        let _ = self.send(Request::TimeBudget(soft, hard));
+    }
+
+    fn set_node_budget(&mut self, nodes: u64) {
+        let _ = self.send(Request::NodeBudget(nodes));
+    }
+
+    /// The time spent by the agent on its own thread, excluding the time
+    /// spent waiting for the request and reply to be passed along.
+    fn reported_time(&self) -> Option<Duration> {
+        self.reported
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.name)
+    }
+}
+
+impl<G: Game, C> Drop for ThreadedAgent<G, C> {
+    fn drop(&mut self) {
+        // closing the channel stops the worker once it is idle
+        self.requests = None;
+        if let Some(handle) = self.handle.take() {
+            if self.outstanding == 0 {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Team;
+    use crate::testing::counting::{CountingGame, CountingState};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    /// Records every call, sleeping for `delays[n]` before the n-th action.
+    struct Recording {
+        log: Arc<Mutex<Vec<String>>>,
+        delays: Vec<Duration>,
+        dropped: Arc<AtomicBool>,
+    }
+
+    impl Recording {
+        fn new(delays: Vec<Duration>) -> (Self, Arc<Mutex<Vec<String>>>, Arc<AtomicBool>) {
+            let log = Arc::new(Mutex::new(Vec::new()));
+            let dropped = Arc::new(AtomicBool::new(false));
+            let agent = Recording {
+                log: log.clone(),
+                delays,
+                dropped: dropped.clone(),
+            };
+            (agent, log, dropped)
+        }
+    }
+
+    impl Agent<CountingGame> for Recording {
+        fn select_action(&mut self, state: &CountingState) -> Result<u32, Error> {
+            let mut log = self.log.lock().unwrap();
+            let calls = log.iter().filter(|call| call.starts_with("select")).count();
+            log.push(format!("select {}", state.count));
+            drop(log);
+            if let Some(&delay) = self.delays.get(calls) {
+                thread::sleep(delay);
+            }
+            Ok(1 + state.count % 3)
+        }
+
+        fn prepare(&mut self, initial_state: &CountingState, info: &PrepareInfo<CountingGame>) {
+            let mut log = self.log.lock().unwrap();
+            log.push(format!("prepare {} {:?}", initial_state.count, info.team));
+        }
+
+        fn observe(&mut self, prev: &CountingState, action: &u32, next: &CountingState) {
+            let mut log = self.log.lock().unwrap();
+            log.push(format!("observe {} {} {}", prev.count, action, next.count));
+        }
+
+        fn accept_swap(&mut self, state: &CountingState) -> bool {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("swap {}", state.count));
+            true
+        }
+    }
+
+    impl Drop for Recording {
+        fn drop(&mut self) {
+            self.dropped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn forwards_every_call_in_order() {
+        let (agent, log, _) = Recording::new(Vec::new());
+        let mut threaded = ThreadedAgent::spawn(agent, Duration::from_secs(10));
+        let initial = CountingState::new(0, Team::One);
+        let next = CountingState::new(2, Team::Two);
+
+        threaded.prepare(&initial, &PrepareInfo { team: Team::Two });
+        threaded.observe(&initial, &2, &next);
+        assert!(threaded.accept_swap(&next));
+        assert_eq!(threaded.select_action(&next).unwrap(), 3);
+        assert!(threaded.reported_time().is_some());
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["prepare 0 Team(Two)", "observe 0 2 2", "swap 2", "select 2"]
+        );
+    }
+
+    #[test]
+    fn slow_agent_exceeds_time_limit() {
+        let delays = vec![Duration::from_millis(500)];
+        let (agent, _, dropped) = Recording::new(delays);
+        let limit = Duration::from_millis(50);
+        let mut threaded = ThreadedAgent::spawn(agent, limit);
+
+        let start = Instant::now();
+        let err = threaded
+            .select_action(&CountingState::new(1, Team::One))
+            .unwrap_err();
+        assert!(start.elapsed() < Duration::from_millis(400));
+        assert!(matches!(
+            err.downcast_ref::<MatchError<CountingGame>>(),
+            Some(MatchError::TimeLimitExceeded(l)) if *l == limit
+        ));
+        assert!(threaded.reported_time().is_none());
+
+        // the late reply to the first request is discarded
+        threaded.time_limit = Duration::from_secs(10);
+        let action = threaded.select_action(&CountingState::new(4, Team::One));
+        assert_eq!(action.unwrap(), 2);
+
+        drop(threaded);
+        assert!(dropped.load(Ordering::SeqCst), "idle thread is joined");
+    }
+
+    #[test]
+    fn unlimited_time_waits_for_the_agent() {
+        let delays = vec![Duration::from_millis(20)];
+        let (agent, _, _) = Recording::new(delays);
+        let mut threaded = ThreadedAgent::spawn(agent, Duration::MAX);
+        let action = threaded.select_action(&CountingState::new(0, Team::One));
+        assert_eq!(action.unwrap(), 1);
+    }
+
+    #[test]
+    fn reported_time_uses_the_clock() {
+        let (agent, _, _) = Recording::new(Vec::new());
+        let clock = crate::core::ManualClock::new();
+        let mut threaded = ThreadedAgent::spawn_with_clock(agent, Duration::MAX, clock);
+        threaded
+            .select_action(&CountingState::new(0, Team::One))
+            .unwrap();
+        assert_eq!(threaded.reported_time(), Some(Duration::ZERO));
+    }
+}
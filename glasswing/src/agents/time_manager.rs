@@ -0,0 +1,345 @@
+use crate::core::Clock;
+use std::time::Duration;
+
+/// Decides how much of the remaining time on a chess clock to spend on a
+/// move. Each move gets a soft deadline, after which no new iteration of an
+/// iterative-deepening search should be started, and a hard deadline, after
+/// which the search must stop.
+///
+/// The budgets reach agents through [Agent::set_time_budget](crate::agents::Agent::set_time_budget),
+/// and [NegaMax](crate::agents::NegaMax) searches within the hard one.
+#[derive(Debug, Clone)]
+pub struct TimeManager {
+    fraction: f64,
+    max_fraction: f64,
+    min_per_move: Duration,
+    max_per_move: Duration,
+    hard_factor: f64,
+    panic_extension: f64,
+    length: Option<LengthEstimate>,
+}
+
+impl TimeManager {
+    /// Spends 5% of the remaining time per move, with a hard limit of three
+    /// times that but never more than half of the remaining time.
+    pub fn new() -> Self {
+        TimeManager {
+            fraction: 0.05,
+            max_fraction: 0.5,
+            min_per_move: Duration::ZERO,
+            max_per_move: Duration::MAX,
+            hard_factor: 3.0,
+            panic_extension: 0.5,
+            length: None,
+        }
+    }
+
+    /// The fraction of the remaining time used as the soft budget.
+    pub fn with_fraction(mut self, fraction: f64) -> Self {
+        self.fraction = fraction;
+        self
+    }
+
+    /// The largest fraction of the remaining time a single move may use.
+    pub fn with_max_fraction(mut self, max_fraction: f64) -> Self {
+        self.max_fraction = max_fraction;
+        self
+    }
+
+    /// Bounds for the soft budget. The maximum fraction of the remaining time
+    /// takes precedence over the minimum.
+    pub fn with_per_move_limits(mut self, min: Duration, max: Duration) -> Self {
+        self.min_per_move = min;
+        self.max_per_move = max;
+        self
+    }
+
+    /// The hard budget as a multiple of the soft budget.
+    pub fn with_hard_factor(mut self, hard_factor: f64) -> Self {
+        self.hard_factor = hard_factor;
+        self
+    }
+
+    /// How much the soft budget grows, relative to the initial soft budget,
+    /// each time the best move changes.
+    pub fn with_panic_extension(mut self, panic_extension: f64) -> Self {
+        self.panic_extension = panic_extension;
+        self
+    }
+
+    /// Divides the remaining time by the estimated number of moves left in
+    /// the game instead of using a fixed fraction, see
+    /// [TimeManager::budget_at].
+    pub fn with_length_estimate(mut self, estimate: LengthEstimate) -> Self {
+        self.length = Some(estimate);
+        self
+    }
+
+    pub fn length_estimate(&self) -> Option<&LengthEstimate> {
+        self.length.as_ref()
+    }
+
+    /// Allows updating the estimate with the lengths of finished games.
+    pub fn length_estimate_mut(&mut self) -> Option<&mut LengthEstimate> {
+        self.length.as_mut()
+    }
+
+    /// The soft and hard budget for a move, given the remaining time.
+    pub fn budget(&self, remaining: Duration) -> (Duration, Duration) {
+        self.scaled_budget(remaining, self.fraction)
+    }
+
+    /// The soft and hard budget for a move after `ply` plies, given the
+    /// remaining time. With a [length estimate](TimeManager::with_length_estimate),
+    /// the soft budget is the remaining time divided by the estimated number
+    /// of the agent's moves left, otherwise this is [TimeManager::budget].
+    /// Either way, no move may use more than the maximum fraction of the
+    /// remaining time.
+    pub fn budget_at(&self, remaining: Duration, ply: usize) -> (Duration, Duration) {
+        let fraction = match &self.length {
+            Some(length) => 1.0 / length.moves_left(ply),
+            None => self.fraction,
+        };
+        self.scaled_budget(remaining, fraction)
+    }
+
+    fn scaled_budget(&self, remaining: Duration, fraction: f64) -> (Duration, Duration) {
+        let cap = remaining.mul_f64(self.max_fraction);
+        let soft = remaining
+            .mul_f64(fraction)
+            .clamp(self.min_per_move, self.max_per_move.max(self.min_per_move))
+            .min(cap);
+        let hard = soft.mul_f64(self.hard_factor).clamp(soft, cap.max(soft));
+        (soft, hard)
+    }
+
+    /// Starts timing a move at the current time of the clock.
+    pub fn start<C: Clock>(&self, clock: &C, remaining: Duration) -> MoveTimer {
+        self.timer(clock, self.budget(remaining))
+    }
+
+    /// Starts timing a move after `ply` plies, see [TimeManager::budget_at].
+    pub fn start_at<C: Clock>(&self, clock: &C, remaining: Duration, ply: usize) -> MoveTimer {
+        self.timer(clock, self.budget_at(remaining, ply))
+    }
+
+    fn timer<C: Clock>(&self, clock: &C, (soft, hard): (Duration, Duration)) -> MoveTimer {
+        let start = clock.now();
+        MoveTimer {
+            soft: start.saturating_add(soft),
+            hard: start.saturating_add(hard),
+            extension: soft.mul_f64(self.panic_extension),
+        }
+    }
+}
+
+impl Default for TimeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Estimates how many plies are left in a game, from the average length of
+/// the games observed so far, or an initial guess before any game finished.
+#[derive(Debug, Clone)]
+pub struct LengthEstimate {
+    initial: f64,
+    min_plies_left: f64,
+    total_plies: u64,
+    games: u64,
+}
+
+impl LengthEstimate {
+    /// Expects games of `plies` plies until games are observed, and at
+    /// least 10 more plies in any position.
+    pub fn new(plies: usize) -> Self {
+        LengthEstimate {
+            initial: plies as f64,
+            min_plies_left: 10.0,
+            total_plies: 0,
+            games: 0,
+        }
+    }
+
+    /// The fewest plies expected to be left in any position, which keeps
+    /// games longer than the estimate from spending all their time at once.
+    pub fn with_min_plies_left(mut self, plies: usize) -> Self {
+        self.min_plies_left = plies.max(1) as f64;
+        self
+    }
+
+    /// Updates the estimate with the length of a finished game.
+    pub fn observe_game(&mut self, plies: usize) {
+        self.total_plies += plies as u64;
+        self.games += 1;
+    }
+
+    /// The expected length of a game in plies.
+    pub fn expected_length(&self) -> f64 {
+        if self.games == 0 {
+            self.initial
+        } else {
+            self.total_plies as f64 / self.games as f64
+        }
+    }
+
+    /// The expected number of plies left after `ply` plies.
+    pub fn plies_left(&self, ply: usize) -> f64 {
+        (self.expected_length() - ply as f64).max(self.min_plies_left)
+    }
+
+    /// The expected number of moves left for the team to move after `ply`
+    /// plies, assuming the teams alternate.
+    pub fn moves_left(&self, ply: usize) -> f64 {
+        (self.plies_left(ply) / 2.0).ceil().max(1.0)
+    }
+}
+
+/// The deadlines of a single move, see [TimeManager::start]. Deadlines are
+/// readings of the clock the timer was started with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveTimer {
+    soft: Duration,
+    hard: Duration,
+    extension: Duration,
+}
+
+impl MoveTimer {
+    pub fn soft_deadline(&self) -> Duration {
+        self.soft
+    }
+
+    pub fn hard_deadline(&self) -> Duration {
+        self.hard
+    }
+
+    /// Whether a new search iteration should not be started.
+    #[inline]
+    pub fn soft_expired<C: Clock>(&self, clock: &C) -> bool {
+        clock.now() >= self.soft
+    }
+
+    /// Whether the search must stop now.
+    #[inline]
+    pub fn hard_expired<C: Clock>(&self, clock: &C) -> bool {
+        clock.now() >= self.hard
+    }
+
+    /// Extends the soft deadline, up to the hard deadline, because the best
+    /// move changed and the search is unstable.
+    pub fn best_move_changed(&mut self) {
+        self.soft = self.soft.saturating_add(self.extension).min(self.hard);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ManualClock;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn budgets_shrink_with_remaining_time() {
+        let clock = ManualClock::new();
+        let manager = TimeManager::new();
+        let mut remaining = Duration::from_secs(60);
+        let mut previous = Duration::MAX;
+
+        for _ in 0..40 {
+            let timer = manager.start(&clock, remaining);
+            let start = clock.now();
+            let (soft, hard) = (timer.soft_deadline() - start, timer.hard_deadline() - start);
+            assert_eq!(soft, remaining.mul_f64(0.05));
+            assert_eq!(hard, soft * 3);
+            assert!(soft < previous);
+            previous = soft;
+
+            // the agent thinks until just before the soft deadline
+            clock.advance(soft - MS);
+            assert!(!timer.soft_expired(&clock));
+            clock.advance(MS);
+            assert!(timer.soft_expired(&clock));
+            assert!(!timer.hard_expired(&clock));
+            remaining -= soft;
+        }
+        // 95% of the time is left after each move
+        let expected = Duration::from_secs(60).mul_f64(0.95f64.powi(40));
+        assert!(remaining.abs_diff(expected) < MS, "{:?}", remaining);
+    }
+
+    #[test]
+    fn hard_budget_never_overdraws_the_clock() {
+        let manager = TimeManager::new().with_fraction(0.4).with_hard_factor(10.0);
+        let mut remaining = Duration::from_secs(10);
+        while remaining > MS {
+            let (soft, hard) = manager.budget(remaining);
+            assert!(soft <= hard);
+            assert!(
+                hard <= remaining.mul_f64(0.5),
+                "{:?} of {:?}",
+                hard,
+                remaining
+            );
+            // the agent uses its whole hard budget
+            remaining -= hard;
+        }
+    }
+
+    #[test]
+    fn per_move_limits() {
+        let manager = TimeManager::new()
+            .with_per_move_limits(Duration::from_millis(100), Duration::from_secs(1));
+        assert_eq!(
+            manager.budget(Duration::from_secs(600)).0,
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            manager.budget(Duration::from_secs(1)).0,
+            Duration::from_millis(100)
+        );
+        // the maximum fraction takes precedence over the minimum
+        let (soft, hard) = manager.budget(Duration::from_millis(100));
+        assert_eq!(
+            (soft, hard),
+            (Duration::from_millis(50), Duration::from_millis(50))
+        );
+    }
+
+    #[test]
+    fn panic_extension_stops_at_hard_deadline() {
+        let clock = ManualClock::new();
+        clock.set(Duration::from_secs(5));
+        let manager = TimeManager::new();
+        let mut timer = manager.start(&clock, Duration::from_secs(10));
+        assert_eq!(timer.soft_deadline(), Duration::from_millis(5500));
+        timer.best_move_changed();
+        assert_eq!(timer.soft_deadline(), Duration::from_millis(5750));
+        for _ in 0..10 {
+            timer.best_move_changed();
+        }
+        assert_eq!(timer.soft_deadline(), timer.hard_deadline());
+        assert_eq!(timer.hard_deadline(), Duration::from_millis(6500));
+    }
+
+    #[test]
+    fn length_estimate_divides_remaining_time() {
+        let mut estimate = LengthEstimate::new(40);
+        assert_eq!(estimate.moves_left(0), 20.0);
+        // never fewer than the minimum of 10 plies, i.e. 5 moves
+        assert_eq!(estimate.moves_left(39), 5.0);
+        estimate.observe_game(20);
+        estimate.observe_game(30);
+        assert_eq!(estimate.expected_length(), 25.0);
+
+        let manager = TimeManager::new().with_length_estimate(LengthEstimate::new(40));
+        let clock = ManualClock::new();
+        let remaining = Duration::from_secs(20);
+        let timer = manager.start_at(&clock, remaining, 0);
+        assert_eq!(timer.soft_deadline(), Duration::from_secs(1));
+        assert_eq!(manager.budget_at(remaining, 30).0, Duration::from_secs(4));
+        // the maximum fraction still applies
+        let manager = manager.with_max_fraction(0.1);
+        assert_eq!(manager.budget_at(remaining, 30).0, Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::core::Game;
+use std::fmt::{Debug, Write};
+
+/// A node of a search tree, as recorded by a [TreeRecorder].
+pub struct TreeNode<G: Game> {
+    pub parent: Option<usize>,
+    /// The action leading from the parent to this node, `None` for the root.
+    pub action: Option<G::Action>,
+    pub ply: u32,
+    pub alpha: G::EvalType,
+    pub beta: G::EvalType,
+    /// The value of the node, `None` if the search of this node never finished.
+    pub eval: Option<G::EvalType>,
+    /// Whether the remaining children of this node were pruned.
+    pub cutoff: bool,
+}
+
+/// Records the nodes visited by a search for debugging. The number of
+/// recorded nodes is bounded by a depth and a node cap, nodes beyond these
+/// limits are silently skipped.
+pub struct TreeRecorder<G: Game> {
+    max_ply: u32,
+    max_nodes: usize,
+    nodes: Vec<TreeNode<G>>,
+}
+
+impl<G: Game> TreeRecorder<G> {
+    pub fn new(max_ply: u32, max_nodes: usize) -> Self {
+        TreeRecorder {
+            max_ply,
+            max_nodes,
+            nodes: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    pub fn nodes(&self) -> &[TreeNode<G>] {
+        &self.nodes
+    }
+
+    /// Records a new node and returns its index, or `None` if the node lies
+    /// beyond the recording limits.
+    pub fn record(
+        &mut self,
+        parent: Option<usize>,
+        action: Option<&G::Action>,
+        ply: u32,
+        alpha: G::EvalType,
+        beta: G::EvalType,
+    ) -> Option<usize> {
+        if ply > self.max_ply || self.nodes.len() >= self.max_nodes {
+            return None;
+        }
+        self.nodes.push(TreeNode {
+            parent,
+            action: action.cloned(),
+            ply,
+            alpha,
+            beta,
+            eval: None,
+            cutoff: false,
+        });
+        Some(self.nodes.len() - 1)
+    }
+
+    pub fn set_eval(&mut self, node: usize, eval: G::EvalType) {
+        self.nodes[node].eval = Some(eval);
+    }
+
+    pub fn set_cutoff(&mut self, node: usize) {
+        self.nodes[node].cutoff = true;
+    }
+
+    /// Exports the recorded tree in the Graphviz DOT format. Edges are
+    /// labelled with the `Debug` representation of the actions.
+    pub fn to_dot(&self) -> String
+    where
+        G::EvalType: Debug,
+    {
+        let mut dot = String::from("digraph search {\n    node [shape=box];\n");
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let eval = match &node.eval {
+                Some(eval) => format!("{:?}", eval),
+                None => String::from("?"),
+            };
+            let cutoff = if node.cutoff { "\\ncutoff" } else { "" };
+            writeln!(
+                dot,
+                "    n{} [label=\"{}\\n[{:?}, {:?}]{}\"];",
+                i, eval, node.alpha, node.beta, cutoff
+            )
+            .unwrap();
+
+            if let (Some(parent), Some(action)) = (node.parent, &node.action) {
+                let label = format!("{:?}", action).replace('"', "\\\"");
+                writeln!(dot, "    n{} -> n{} [label=\"{}\"];", parent, i, label).unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,86 @@
+use crate::agents::{Evaluator, SearchError};
+use crate::core::{Game, GwState};
+use crate::train::TurnRecord;
+
+/// A move that a reference evaluator scores at least some threshold below
+/// the best move in the same position.
+#[derive(Debug, Clone)]
+pub struct Blunder<G: Game> {
+    /// Number of plies played before the move.
+    pub ply: usize,
+    pub played: G::Action,
+    pub best: G::Action,
+    /// How much better the best move scores than the played one.
+    pub delta: f64,
+}
+
+/// The result of [blunder_report].
+#[derive(Debug, Clone)]
+pub struct BlunderReport<G: Game> {
+    /// The blunders found, largest delta first.
+    pub blunders: Vec<Blunder<G>>,
+    /// Positions, by the number of plies played before them, that were
+    /// skipped because the reference evaluator reported an error.
+    pub errors: Vec<(usize, SearchError)>,
+}
+
+/// Re-evaluates every move of a game with `reference` and reports the moves
+/// scoring at least `threshold` below the best move, largest delta first.
+///
+/// Each action is scored with [Evaluator::evaluate_action_for] for the team
+/// that made the move, and every position is evaluated as one move between
+/// [Evaluator::begin_move] and [Evaluator::end_move], so a search such as
+/// [NegaMax](crate::agents::NegaMax) with a
+/// [budget](crate::agents::NegaMax::with_budget) bounds the work per position.
+/// Positions whose evaluation reports an error through
+/// [Evaluator::take_error] are skipped and listed in [BlunderReport::errors].
+pub fn blunder_report<G, E>(
+    history: &[TurnRecord<G>],
+    reference: &mut E,
+    threshold: f64,
+) -> BlunderReport<G>
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G>,
+{
+    let mut blunders = Vec::new();
+    let mut errors = Vec::new();
+    for turn in history {
+        reference.begin_move();
+        let played: f64 = reference
+            .evaluate_action_for(&turn.pre, &turn.action, &turn.team)
+            .into();
+        let mut best: Option<(G::Action, f64)> = None;
+        for action in turn.pre.actions() {
+            let score = reference
+                .evaluate_action_for(&turn.pre, &action, &turn.team)
+                .into();
+            if best
+                .as_ref()
+                .is_none_or(|&(_, best_score)| score > best_score)
+            {
+                best = Some((action, score));
+            }
+        }
+        reference.end_move();
+        if let Some(err) = reference.take_error() {
+            errors.push((turn.ply, err));
+            continue;
+        }
+        let Some((best, best_score)) = best else {
+            continue;
+        };
+        let delta = best_score - played;
+        if delta >= threshold {
+            blunders.push(Blunder {
+                ply: turn.ply,
+                played: turn.action.clone(),
+                best,
+                delta,
+            });
+        }
+    }
+    blunders.sort_by(|a, b| b.delta.total_cmp(&a.delta));
+    BlunderReport { blunders, errors }
+}
@@ -0,0 +1,196 @@
+use crate::agents::Evaluator;
+use crate::core::Game;
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+/// The scores of several evaluators on the same positions, see
+/// [compare_evaluators].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonReport {
+    names: Vec<String>,
+    /// One row per position, one column per evaluator.
+    scores: Vec<Vec<f64>>,
+}
+
+impl ComparisonReport {
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The scores of every evaluator for each position, in the order of
+    /// [ComparisonReport::names].
+    pub fn scores(&self) -> &[Vec<f64>] {
+        &self.scores
+    }
+
+    fn column(&self, evaluator: usize) -> Vec<f64> {
+        self.scores.iter().map(|row| row[evaluator]).collect()
+    }
+
+    /// Spearman's rank correlation between the scores of evaluators `a` and
+    /// `b`. `None` if either scores every position equally, or there are
+    /// fewer than two positions.
+    pub fn rank_correlation(&self, a: usize, b: usize) -> Option<f64> {
+        let (x, y) = (ranks(&self.column(a)), ranks(&self.column(b)));
+        let n = x.len() as f64;
+        if x.len() < 2 {
+            return None;
+        }
+        let (mean_x, mean_y) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+        let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+        for (x, y) in x.iter().zip(&y) {
+            cov += (x - mean_x) * (y - mean_y);
+            var_x += (x - mean_x).powi(2);
+            var_y += (y - mean_y).powi(2);
+        }
+        (var_x > 0.0 && var_y > 0.0).then(|| cov / (var_x * var_y).sqrt())
+    }
+
+    /// The `k` positions on which evaluators `a` and `b` disagree most, as
+    /// indices into the positions with the absolute score difference,
+    /// largest first.
+    pub fn top_disagreements(&self, a: usize, b: usize, k: usize) -> Vec<(usize, f64)> {
+        let mut disagreements = self
+            .scores
+            .iter()
+            .map(|row| (row[a] - row[b]).abs())
+            .enumerate()
+            .collect::<Vec<_>>();
+        disagreements.sort_by(|x, y| y.1.total_cmp(&x.1));
+        disagreements.truncate(k);
+        disagreements
+    }
+
+    /// Writes one line per position, with the position's index followed by
+    /// the score of each evaluator, after a header of evaluator names.
+    /// Names containing commas, quotes or line breaks are quoted.
+    pub fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(writer, "position")?;
+        for name in &self.names {
+            write!(writer, ",{}", csv_field(name))?;
+        }
+        writeln!(writer)?;
+        for (i, row) in self.scores.iter().enumerate() {
+            write!(writer, "{}", i)?;
+            for score in row {
+                write!(writer, ",{}", score)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes a CSV field if needed, doubling the quotes inside it.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Fractional ranks, averaging the ranks of tied values.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order = (0..values.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end - 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Scores every position with each of the named evaluators, relative to the
+/// team to move, for comparing versions of an evaluator. Positions can be
+/// collected with [random_states](crate::agents::random_states) or from the
+/// states of a game's [TurnRecords](crate::train::TurnRecord).
+pub fn compare_evaluators<G>(
+    positions: &[G::State],
+    mut evaluators: Vec<(&str, &mut dyn Evaluator<G>)>,
+) -> ComparisonReport
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+{
+    let names = evaluators
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
+    let scores = positions
+        .iter()
+        .map(|state| {
+            evaluators
+                .iter_mut()
+                .map(|(_, evaluator)| evaluator.evaluate(state).into())
+                .collect()
+        })
+        .collect();
+    ComparisonReport { names, scores }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::random_states;
+    use crate::testing::counting::{CountingEvaluator, CountingGame};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn report() -> ComparisonReport {
+        let positions = random_states::<CountingGame, _>(5, &mut StdRng::seed_from_u64(1));
+        let mut perfect = CountingEvaluator { perfect: true };
+        let mut flat = CountingEvaluator { perfect: false };
+        compare_evaluators::<CountingGame>(
+            &positions,
+            vec![("perfect, v2", &mut perfect), ("flat \"zero\"", &mut flat)],
+        )
+    }
+
+    #[test]
+    fn report_has_a_score_per_position_and_evaluator() {
+        let report = report();
+        assert_eq!(report.names(), ["perfect, v2", "flat \"zero\""]);
+        assert!(report.scores().len() > 5);
+        assert!(report.scores().iter().all(|row| row.len() == 2));
+        assert_eq!(report.rank_correlation(0, 0), Some(1.0));
+    }
+
+    #[test]
+    fn disagreements_are_found() {
+        let report = report();
+        let top = report.top_disagreements(0, 1, 3);
+        assert_eq!(top.len(), 3);
+        // only unfinished positions are scored differently, by 1
+        assert!(top.iter().all(|&(_, difference)| difference == 1.0));
+        let (position, _) = top[0];
+        let row = &report.scores()[position];
+        assert_ne!(row[0], row[1]);
+        assert!(report
+            .top_disagreements(1, 1, 3)
+            .iter()
+            .all(|&(_, d)| d == 0.0));
+    }
+
+    #[test]
+    fn csv_quotes_names_with_separators() {
+        let report = report();
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "position,\"perfect, v2\",\"flat \"\"zero\"\"\"");
+        assert_eq!(lines.len(), report.scores().len() + 1);
+        assert!(lines[1..].iter().all(|line| line.split(',').count() == 3));
+        assert_eq!(csv_field("plain"), "plain");
+    }
+}
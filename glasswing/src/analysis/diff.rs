@@ -0,0 +1,136 @@
+use crate::core::Game;
+use crate::train::TurnRecord;
+use cachewing::TranspositionHash;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum HistoryDiffError {
+    #[error("histories start from different positions")]
+    DifferentInitialStates,
+}
+
+/// The first ply at which two games differ.
+#[derive(Debug, Clone)]
+pub struct Divergence<G: Game> {
+    /// Number of plies played before the divergence.
+    pub ply: usize,
+    /// The action played in the first game, `None` if it had already ended.
+    pub a: Option<G::Action>,
+    /// The action played in the second game, `None` if it had already ended.
+    pub b: Option<G::Action>,
+}
+
+/// The result of [diff_histories].
+#[derive(Debug, Clone)]
+pub struct HistoryDiff<G: Game> {
+    /// Number of plies both games have in common.
+    pub shared_plies: usize,
+    /// `None` if the games are identical.
+    pub divergence: Option<Divergence<G>>,
+}
+
+/// Compares two games move by move and reports where they diverge, e.g. to
+/// find the first move a change to an agent affected.
+///
+/// Fails if the games start from different positions, as told by their
+/// [TranspositionHash].
+pub fn diff_histories<G>(
+    a: &[TurnRecord<G>],
+    b: &[TurnRecord<G>],
+) -> Result<HistoryDiff<G>, HistoryDiffError>
+where
+    G: Game,
+    G::State: TranspositionHash,
+    G::Action: PartialEq,
+{
+    if let (Some(first_a), Some(first_b)) = (a.first(), b.first()) {
+        if first_a.pre.hash() != first_b.pre.hash() {
+            return Err(HistoryDiffError::DifferentInitialStates);
+        }
+    }
+    let shared_plies = a
+        .iter()
+        .zip(b)
+        .take_while(|(a, b)| a.action == b.action)
+        .count();
+    let (next_a, next_b) = (a.get(shared_plies), b.get(shared_plies));
+    let divergence = (next_a.is_some() || next_b.is_some()).then(|| Divergence {
+        ply: shared_plies,
+        a: next_a.map(|turn| turn.action.clone()),
+        b: next_b.map(|turn| turn.action.clone()),
+    });
+    Ok(HistoryDiff {
+        shared_plies,
+        divergence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{GwState, Team};
+    use crate::testing::counting::{CountingGame, CountingState};
+    use std::time::Duration;
+
+    fn history(start: u32, actions: &[u32]) -> Vec<TurnRecord<CountingGame>> {
+        let mut state = CountingState::new(start, Team::One);
+        actions
+            .iter()
+            .enumerate()
+            .map(|(ply, &action)| {
+                let post = state.apply_action(&action);
+                let turn = TurnRecord {
+                    ply,
+                    team: state.team_to_move(),
+                    agent: ply % 2,
+                    action,
+                    pre: state.clone(),
+                    post: post.clone(),
+                    elapsed: Duration::ZERO,
+                    reported: None,
+                    budget: None,
+                };
+                state = post;
+                turn
+            })
+            .collect()
+    }
+
+    #[test]
+    fn identical_games_do_not_diverge() {
+        let game = history(0, &[1, 3, 2, 2, 1]);
+        let diff = diff_histories(&game, &game).unwrap();
+        assert_eq!(diff.shared_plies, 5);
+        assert!(diff.divergence.is_none());
+    }
+
+    #[test]
+    fn altered_move_is_pinpointed() {
+        let a = history(0, &[1, 3, 2, 2, 1, 3]);
+        let b = history(0, &[1, 3, 2, 1, 1, 3]);
+        let diff = diff_histories(&a, &b).unwrap();
+        assert_eq!(diff.shared_plies, 3);
+        let divergence = diff.divergence.unwrap();
+        assert_eq!(divergence.ply, 3);
+        assert_eq!((divergence.a, divergence.b), (Some(2), Some(1)));
+    }
+
+    #[test]
+    fn longer_game_diverges_where_the_shorter_ends() {
+        let a = history(0, &[1, 3]);
+        let b = history(0, &[1, 3, 2]);
+        let divergence = diff_histories(&a, &b).unwrap().divergence.unwrap();
+        assert_eq!(divergence.ply, 2);
+        assert_eq!((divergence.a, divergence.b), (None, Some(2)));
+    }
+
+    #[test]
+    fn different_initial_states_are_rejected() {
+        let a = history(0, &[1, 3]);
+        let b = history(1, &[1, 3]);
+        assert_eq!(
+            diff_histories(&a, &b).unwrap_err(),
+            HistoryDiffError::DifferentInitialStates
+        );
+        assert!(diff_histories(&a, &[]).unwrap().divergence.is_some());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod blunders;
+pub mod compare;
+pub mod diff;
+pub mod opening_tree;
+
+pub use blunders::*;
+pub use compare::*;
+pub use diff::*;
+pub use opening_tree::*;
@@ -0,0 +1,141 @@
+use crate::core::{Game, GwGameResult};
+use crate::train::{OpeningSuite, TurnRecord, Wdl};
+use cachewing::TranspositionHash;
+use std::collections::HashMap;
+
+/// How often a move was played in a position, and the results of those
+/// games from the point of view of the team that played it.
+#[derive(Debug, Clone)]
+pub struct MoveStats<G: Game> {
+    pub action: G::Action,
+    pub wdl: Wdl,
+}
+
+#[derive(Debug, Clone)]
+struct Node<G: Game> {
+    state: G::State,
+    ply: usize,
+    games: usize,
+    moves: Vec<MoveStats<G>>,
+}
+
+/// Aggregates the openings of many games: for every position reached in the
+/// first plies, which moves were played and how the games ended.
+///
+/// Positions are keyed by their [TranspositionHash], so transpositions
+/// share statistics. Only the first `max_ply` plies of each game are added,
+/// which bounds the size of the tree.
+#[derive(Debug, Clone)]
+pub struct OpeningTree<G: Game> {
+    max_ply: usize,
+    games: usize,
+    nodes: HashMap<u64, Node<G>>,
+}
+
+impl<G> OpeningTree<G>
+where
+    G: Game,
+    G::State: TranspositionHash,
+    G::Action: PartialEq,
+{
+    pub fn new(max_ply: usize) -> Self {
+        OpeningTree {
+            max_ply,
+            games: 0,
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Builds a tree from the turns of finished games and their results.
+    pub fn from_games<'a>(
+        games: impl IntoIterator<Item = (&'a [TurnRecord<G>], Option<&'a G::GameResult>)>,
+        max_ply: usize,
+    ) -> Self
+    where
+        G: 'a,
+    {
+        let mut tree = OpeningTree::new(max_ply);
+        for (history, result) in games {
+            tree.add_game(history, result);
+        }
+        tree
+    }
+
+    /// Adds the opening of a game. Games without a result count as draws.
+    pub fn add_game(&mut self, history: &[TurnRecord<G>], result: Option<&G::GameResult>) {
+        let winner = result.and_then(|result| result.winner());
+        for turn in history.iter().take(self.max_ply) {
+            let node = self.node_mut(&turn.pre, turn.ply);
+            node.games += 1;
+            let index = match node.moves.iter().position(|m| m.action == turn.action) {
+                Some(index) => index,
+                None => {
+                    node.moves.push(MoveStats {
+                        action: turn.action.clone(),
+                        wdl: Wdl::default(),
+                    });
+                    node.moves.len() - 1
+                }
+            };
+            let wdl = &mut node.moves[index].wdl;
+            match &winner {
+                None => wdl.draws += 1,
+                Some(winner) if *winner == turn.team => wdl.wins += 1,
+                Some(_) => wdl.losses += 1,
+            }
+        }
+        if let Some(last) = history.get(self.max_ply.wrapping_sub(1)) {
+            self.node_mut(&last.post, last.ply + 1).games += 1;
+        }
+        self.games += 1;
+    }
+
+    fn node_mut(&mut self, state: &G::State, ply: usize) -> &mut Node<G> {
+        self.nodes.entry(state.hash()).or_insert_with(|| Node {
+            state: state.clone(),
+            ply,
+            games: 0,
+            moves: Vec::new(),
+        })
+    }
+
+    /// Number of games added.
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    /// Number of games that reached `state`.
+    pub fn games_through(&self, state: &G::State) -> usize {
+        self.nodes.get(&state.hash()).map_or(0, |node| node.games)
+    }
+
+    /// The moves played in `state`, in the order they were first seen.
+    pub fn children(&self, state: &G::State) -> &[MoveStats<G>] {
+        self.nodes
+            .get(&state.hash())
+            .map_or(&[], |node| node.moves.as_slice())
+    }
+
+    /// The move with the best score among those played in at least
+    /// `min_games` games, counting a draw as half a win.
+    pub fn best_by_winrate(&self, state: &G::State, min_games: usize) -> Option<&MoveStats<G>> {
+        self.children(state)
+            .iter()
+            .filter(|m| m.wdl.total() >= min_games)
+            .max_by(|a, b| a.wdl.score().total_cmp(&b.wdl.score()))
+    }
+
+    /// The positions after `max_ply` plies reached by at least `min_games`
+    /// games, as starting positions for a
+    /// [MatchSeries](crate::train::MatchSeries).
+    pub fn to_suite(&self, min_games: usize) -> OpeningSuite<G> {
+        let mut nodes = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.ply == self.max_ply && node.games >= min_games)
+            .collect::<Vec<_>>();
+        // most frequent first, by hash for a stable order among equals
+        nodes.sort_by_key(|&(hash, node)| (std::cmp::Reverse(node.games), *hash));
+        OpeningSuite::new(nodes.iter().map(|(_, node)| node.state.clone()).collect())
+    }
+}
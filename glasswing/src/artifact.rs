@@ -0,0 +1,142 @@
+//! Headers identifying the game and version of files written by glasswing.
+
+use crate::core::Game;
+use std::io::{self, Read, Write};
+
+/// The first bytes of every artifact with a header.
+pub const MAGIC: [u8; 8] = *b"GLASSWNG";
+
+/// The longest string accepted in a header, to reject corrupt lengths
+/// before allocating for them.
+pub const MAX_STRING_LEN: usize = 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArtifactError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("artifact was written for game {found}, expected {expected}")]
+    WrongGame { expected: String, found: String },
+    #[error("unsupported format version {found}, at most {supported} is supported")]
+    UnsupportedVersion { found: u32, supported: u32 },
+}
+
+/// Identifies the game, format version and glasswing version of an artifact.
+/// Games are identified by [Game::name].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactHeader {
+    pub format_version: u32,
+    pub game: String,
+    pub crate_version: String,
+}
+
+impl ArtifactHeader {
+    pub fn for_game<G: Game>(format_version: u32) -> Self {
+        ArtifactHeader {
+            format_version,
+            game: G::name().into_owned(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// Writes the header, starting with [MAGIC].
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&self.format_version.to_le_bytes())?;
+        write_string(writer, &self.game)?;
+        write_string(writer, &self.crate_version)
+    }
+
+    /// Reads a header whose [MAGIC] has already been consumed.
+    pub fn read_after_magic(reader: &mut impl Read) -> io::Result<Self> {
+        let mut u32_buf = [0u8; 4];
+        reader.read_exact(&mut u32_buf)?;
+        Ok(ArtifactHeader {
+            format_version: u32::from_le_bytes(u32_buf),
+            game: read_string(reader)?,
+            crate_version: read_string(reader)?,
+        })
+    }
+
+    /// Checks that the artifact was written for `G` in a format version of
+    /// at most `supported`.
+    pub fn validate<G: Game>(&self, supported: u32) -> Result<(), ArtifactError> {
+        let expected = G::name();
+        if self.game != expected {
+            return Err(ArtifactError::WrongGame {
+                expected: expected.into_owned(),
+                found: self.game.clone(),
+            });
+        }
+        if self.format_version > supported {
+            return Err(ArtifactError::UnsupportedVersion {
+                found: self.format_version,
+                supported,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn write_string(writer: &mut impl Write, s: &str) -> io::Result<()> {
+    if s.len() > MAX_STRING_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "string of {} bytes exceeds the limit of {MAX_STRING_LEN}",
+                s.len()
+            ),
+        ));
+    }
+    writer.write_all(&(s.len() as u32).to_le_bytes())?;
+    writer.write_all(s.as_bytes())
+}
+
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let mut u32_buf = [0u8; 4];
+    reader.read_exact(&mut u32_buf)?;
+    let len = u32::from_le_bytes(u32_buf) as usize;
+    if len > MAX_STRING_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("string of {len} bytes exceeds the limit of {MAX_STRING_LEN}"),
+        ));
+    }
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::counting::CountingGame;
+
+    fn read(bytes: &[u8]) -> io::Result<ArtifactHeader> {
+        let mut reader = bytes;
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        assert_eq!(magic, MAGIC);
+        ArtifactHeader::read_after_magic(&mut reader)
+    }
+
+    #[test]
+    fn header_round_trips() {
+        let header = ArtifactHeader::for_game::<CountingGame>(3);
+        let mut bytes = Vec::new();
+        header.write_to(&mut bytes).unwrap();
+        assert_eq!(read(&bytes).unwrap(), header);
+    }
+
+    #[test]
+    fn overlong_strings_are_not_written() {
+        let mut header = ArtifactHeader::for_game::<CountingGame>(1);
+        header.game = "x".repeat(MAX_STRING_LEN);
+        let mut bytes = Vec::new();
+        header.write_to(&mut bytes).unwrap();
+        assert_eq!(read(&bytes).unwrap(), header);
+
+        header.game.push('x');
+        let err = header.write_to(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -1,4 +1,5 @@
 use super::{GwGameResult, GwState, GwTeam};
+use std::borrow::Cow;
 use std::fmt::Debug;
 
 pub trait Game: Sized + Debug + 'static {
@@ -8,5 +9,17 @@ pub trait Game: Sized + Debug + 'static {
     type GameResult: GwGameResult<Self::Team>;
     type EvalType;
 
+    /// An upper bound on the number of legal actions in any state, if known.
+    /// Searches use it to size their action buffers up front.
+    const MAX_ACTIONS: Option<usize> = None;
+
+    /// A stable name identifying the game, e.g. in the headers of saved
+    /// artifacts. Defaults to the type name, which may change between
+    /// compiler or crate versions, so games whose artifacts are kept should
+    /// override it.
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed(std::any::type_name::<Self>())
+    }
+
     fn initial_state() -> Self::State;
 }
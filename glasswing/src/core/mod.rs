@@ -1,9 +1,12 @@
 use std::fmt::Debug;
+use std::time::Duration;
+pub mod clock;
 pub mod game;
 pub mod game_result;
 pub mod state;
 pub mod team;
 
+pub use clock::*;
 pub use game::*;
 pub use game_result::*;
 pub use state::*;
@@ -17,4 +20,12 @@ where
 {
     #[error("No available actions for state {0:?}")]
     NoAvailableActions(G::State),
+    #[error("Suspicious move generation at ply {ply}: generated at least {count} actions")]
+    SuspiciousMoveGeneration { ply: usize, count: usize },
+    #[error("Duplicate actions generated at ply {ply}")]
+    DuplicateActions { ply: usize },
+    #[error("Agent exceeded its time limit of {0:?}")]
+    TimeLimitExceeded(Duration),
+    #[error("Applying the action at ply {ply} panicked: {message}")]
+    ActionPanicked { ply: usize, message: String },
 }
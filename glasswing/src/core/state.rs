@@ -6,6 +6,14 @@ pub trait GwState<G: Game<State = Self>>: Sized + Clone + fmt::Debug + Send + Sy
 
     fn actions(&self) -> Self::ActionIter;
 
+    /// Writes all legal actions into `buf`, replacing its previous contents.
+    /// Searches use this to reuse buffers instead of allocating at every node.
+    #[inline]
+    fn actions_into(&self, buf: &mut Vec<G::Action>) {
+        buf.clear();
+        buf.extend(self.actions());
+    }
+
     #[inline]
     fn substates(&self) -> SubStateIter<G> {
         SubStateIter::new(self.clone())
@@ -18,6 +26,14 @@ pub trait GwState<G: Game<State = Self>>: Sized + Clone + fmt::Debug + Send + Sy
 
     fn team_to_move(&self) -> G::Team;
 
+    /// A cheap, static priority of `action` for move ordering. Searches order
+    /// actions by the evaluator and try those with higher keys first among
+    /// equally evaluated ones. All actions are equal by default.
+    #[inline]
+    fn static_order_key(&self, _action: &G::Action) -> i32 {
+        0
+    }
+
     #[must_use]
     fn apply_action(&self, action: &G::Action) -> Self;
 
@@ -29,6 +45,40 @@ pub trait GwState<G: Game<State = Self>>: Sized + Clone + fmt::Debug + Send + Sy
     fn game_result(&self) -> Option<G::GameResult>;
 }
 
+/// States that can enumerate the states preceding them, as required for
+/// retrograde analysis.
+pub trait ReverseState<G: Game<State = Self>>: GwState<G> {
+    /// Returns every non-terminal state from which a single legal action
+    /// leads to this state.
+    fn predecessors(&self) -> Vec<Self>;
+}
+
+/// States of games where play can continue without making progress, such
+/// as the fifty-move rule in chess. Used to adjudicate draws, see
+/// [Pit::with_draw_after_no_progress](crate::train::Pit::with_draw_after_no_progress).
+pub trait ProgressState<G: Game<State = Self>>: GwState<G> {
+    /// Whether playing `action` in this state, leading to `next`, makes
+    /// progress and resets the no-progress counter.
+    fn is_progress(&self, action: &G::Action, next: &Self) -> bool;
+}
+
+/// States that can be described by their difference to the previous state,
+/// for sending updates more compactly than the full state.
+pub trait DiffableState<G: Game<State = Self>>: GwState<G> {
+    type Delta: Clone + fmt::Debug;
+
+    /// The difference between `prev` and `self`, where `self` results from
+    /// a single action in `prev`.
+    ///
+    /// # Panics
+    /// Implementations panic if `self` does not follow from `prev` by a
+    /// single action, rather than returning a meaningless delta.
+    fn diff(&self, prev: &Self) -> Self::Delta;
+
+    /// Reconstructs the following state from a delta produced by [DiffableState::diff].
+    fn apply_delta(&self, delta: &Self::Delta) -> Self;
+}
+
 pub struct SubStateIter<G: Game> {
     actions: <<<G as Game>::State as GwState<G>>::ActionIter as IntoIterator>::IntoIter,
     state: G::State,
@@ -55,3 +105,52 @@ impl<G: Game> Iterator for SubStateIter<G> {
             .map(|action| self.state.apply_action(action))
     }
 }
+
+/// States of games whose move generation is naturally pseudo-legal, i.e.
+/// generates some actions that turn out to be illegal on closer inspection,
+/// such as moving a pinned piece in chess.
+///
+/// Such games can implement [GwState::actions] by returning
+/// [PseudoLegalState::legal_actions], which filters lazily, so that the
+/// legality check is only paid for actions that are actually consumed.
+pub trait PseudoLegalState<G: Game<State = Self>>: GwState<G> {
+    type PseudoActionIter: IntoIterator<Item = G::Action>;
+
+    /// All legal actions, and possibly some illegal ones.
+    fn pseudo_actions(&self) -> Self::PseudoActionIter;
+
+    /// Whether an action returned by [PseudoLegalState::pseudo_actions] is legal.
+    fn is_really_legal(&self, action: &G::Action) -> bool;
+
+    #[inline]
+    fn legal_actions(&self) -> LegalIter<G> {
+        LegalIter {
+            actions: self.pseudo_actions().into_iter(),
+            state: self.clone(),
+        }
+    }
+}
+
+/// Filters the pseudo-legal actions of a state down to the legal ones.
+pub struct LegalIter<G: Game>
+where
+    G::State: PseudoLegalState<G>,
+{
+    actions: <<G::State as PseudoLegalState<G>>::PseudoActionIter as IntoIterator>::IntoIter,
+    state: G::State,
+}
+
+impl<G: Game> Iterator for LegalIter<G>
+where
+    G::State: PseudoLegalState<G>,
+{
+    type Item = G::Action;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = &self.state;
+        self.actions
+            .by_ref()
+            .find(|action| state.is_really_legal(action))
+    }
+}
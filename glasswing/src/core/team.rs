@@ -3,9 +3,11 @@ use std::fmt;
 pub trait GwTeam: Sized + Clone + Eq + PartialEq + fmt::Debug {
     fn opponent(&self) -> Self;
 
+    /// Returns the team to move `n` plies after `self`, assuming the teams
+    /// alternate. `nth(0)` is `self`, and only the parity of `n` is used.
     #[inline]
     fn nth(&self, n: usize) -> Self {
-        if n % 2 == 0 {
+        if n.is_multiple_of(2) {
             self.clone()
         } else {
             self.opponent()
@@ -1,6 +1,13 @@
 #![allow(dead_code)]
 
 pub mod agents;
+pub mod analysis;
+pub mod artifact;
 pub mod core;
 pub mod perft;
+pub mod ranking;
+pub mod tablebase;
+pub mod testing;
 pub mod train;
+pub mod tuning;
+pub mod variants;
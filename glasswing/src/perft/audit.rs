@@ -0,0 +1,80 @@
+use crate::core::{Game, GwState};
+use cachewing::traits::{AlwaysReplacePolicy, TranspositionHash, TranspositionTable};
+
+/// A second hash of a state, independent of its [TranspositionHash] but
+/// computed from the same information, so that states the primary hash
+/// deliberately treats as equal also agree here. Used by
+/// [perft_with_cache_audited] to detect collisions of the primary hash.
+pub trait AuditHash {
+    fn audit_hash(&self) -> u64;
+}
+
+/// Cache statistics of [perft_with_cache_audited].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Hits whose entry belonged to a different state or depth, which were
+    /// recomputed instead of trusted.
+    pub collisions: u64,
+}
+
+/// Like [perft_with_cache](crate::perft::perft_with_cache), but stores a
+/// second, independent hash of the state and the depth with every count and
+/// verifies it on every hit. Useful to check the quality of a game's
+/// [TranspositionHash]: any collision it detects would have corrupted the
+/// count of the unaudited version.
+///
+/// The second hash is the state's [AuditHash], combined with the depth.
+pub fn perft_with_cache_audited<G, T>(
+    state: &G::State,
+    depth: u32,
+    table: &mut T,
+) -> (u64, CacheStats)
+where
+    G: Game,
+    G::State: TranspositionHash + AuditHash,
+    T: TranspositionTable<G::State, (u64, u64)> + AlwaysReplacePolicy,
+{
+    let mut stats = CacheStats::default();
+    let count = audited::<G, T>(state, depth, table, &mut stats);
+    (count, stats)
+}
+
+fn audited<G, T>(state: &G::State, depth: u32, table: &mut T, stats: &mut CacheStats) -> u64
+where
+    G: Game,
+    G::State: TranspositionHash + AuditHash,
+    T: TranspositionTable<G::State, (u64, u64)> + AlwaysReplacePolicy,
+{
+    if state.is_terminal() {
+        return 1;
+    } else if depth == 1 {
+        return state.count_actions() as u64;
+    }
+
+    let check = check_hash(state, depth);
+    match table.get(state) {
+        Some(&(count, stored)) if stored == check => {
+            stats.hits += 1;
+            return count;
+        }
+        Some(_) => stats.collisions += 1,
+        None => stats.misses += 1,
+    }
+
+    let count = state
+        .substates()
+        .map(|new_state| audited::<G, T>(&new_state, depth - 1, table, stats))
+        .sum::<u64>();
+
+    table.insert(state.clone(), (count, check));
+    count
+}
+
+#[inline]
+fn check_hash<S: AuditHash>(state: &S, depth: u32) -> u64 {
+    // mix the depth in with an odd multiplier so that equal states at
+    // different depths do not verify against each other
+    state.audit_hash() ^ (depth as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15)
+}
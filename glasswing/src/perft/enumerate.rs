@@ -0,0 +1,81 @@
+use crate::core::{Game, GwState};
+use cachewing::TranspositionHash;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum EnumerationError {
+    #[error("State enumeration exceeded the limit of {limit} nodes")]
+    NodeLimitExceeded { limit: usize },
+}
+
+/// Enumerates all nodes of the game tree below `state` in breadth-first
+/// order, up to and including `depth` plies. Transpositions are visited once
+/// per path. Fails if more than `max_nodes` states would be returned.
+pub fn enumerate_states<G: Game>(
+    state: &G::State,
+    depth: u32,
+    max_nodes: usize,
+) -> Result<Vec<G::State>, EnumerationError> {
+    if max_nodes == 0 {
+        return Err(EnumerationError::NodeLimitExceeded { limit: max_nodes });
+    }
+
+    let mut states = vec![state.clone()];
+    let mut level_start = 0;
+    for _ in 0..depth {
+        let level_end = states.len();
+        for i in level_start..level_end {
+            if states[i].is_terminal() {
+                continue;
+            }
+            for new_state in states[i].substates() {
+                if states.len() == max_nodes {
+                    return Err(EnumerationError::NodeLimitExceeded { limit: max_nodes });
+                }
+                states.push(new_state);
+            }
+        }
+        level_start = level_end;
+    }
+    Ok(states)
+}
+
+/// Like [enumerate_states], but transpositions are merged by their hash.
+/// Each unique state is returned once, together with the minimal number of
+/// plies needed to reach it. Fails if there are more than `max_nodes` unique
+/// states.
+pub fn enumerate_unique_states<G>(
+    state: &G::State,
+    depth: u32,
+    max_nodes: usize,
+) -> Result<Vec<(G::State, u32)>, EnumerationError>
+where
+    G: Game,
+    G::State: TranspositionHash,
+{
+    if max_nodes == 0 {
+        return Err(EnumerationError::NodeLimitExceeded { limit: max_nodes });
+    }
+
+    let mut seen = HashSet::new();
+    seen.insert(state.hash());
+    let mut states = vec![(state.clone(), 0)];
+
+    let mut i = 0;
+    while i < states.len() {
+        let (ref current, current_depth) = states[i];
+        if current_depth < depth && !current.is_terminal() {
+            let successors = current.substates().collect::<Vec<_>>();
+            for new_state in successors {
+                if seen.insert(new_state.hash()) {
+                    if states.len() == max_nodes {
+                        return Err(EnumerationError::NodeLimitExceeded { limit: max_nodes });
+                    }
+                    states.push((new_state, current_depth + 1));
+                }
+            }
+        }
+        i += 1;
+    }
+    Ok(states)
+}
@@ -1,3 +1,10 @@
+pub mod audit;
+pub mod enumerate;
+pub mod timed;
+pub use audit::*;
+pub use enumerate::*;
+pub use timed::*;
+
 use crate::core::{Game, GwState};
 use cachewing::traits::{AlwaysReplacePolicy, TranspositionHash, TranspositionTable};
 
@@ -9,6 +16,73 @@ pub fn perft<G: Game>(state: &G::State, depth: u32) -> u64 {
     perft_recursive::<G>(state, depth)
 }
 
+/// Splits the perft count of the given state by its actions. Each action is
+/// paired with the number of leaf nodes at `depth` below it.
+pub fn divide<G: Game>(state: &G::State, depth: u32) -> Vec<(G::Action, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    state
+        .actions()
+        .into_iter()
+        .map(|action| {
+            let count = perft::<G>(&state.apply_action(&action), depth - 1);
+            (action, count)
+        })
+        .collect()
+}
+
+/// Like [perft], but asserts at every node that the state generates at most
+/// `max_actions` actions, no action twice, and as many as it
+/// [counts](GwState::count_actions). Intended for debugging move
+/// generators, as it is considerably slower.
+///
+/// # Panics
+/// Panics with the offending state if an assertion fails.
+pub fn perft_checked<G>(state: &G::State, depth: u32, max_actions: usize) -> u64
+where
+    G: Game,
+    G::Action: PartialEq,
+{
+    if depth == 0 {
+        return 1;
+    }
+
+    let actions = state
+        .actions()
+        .into_iter()
+        .take(max_actions + 1)
+        .collect::<Vec<G::Action>>();
+    assert!(
+        actions.len() <= max_actions,
+        "state generated more than {} actions: {:?}",
+        max_actions,
+        state
+    );
+    assert_eq!(
+        state.count_actions(),
+        actions.len(),
+        "count_actions disagrees with actions: {:?}",
+        state
+    );
+    for (i, action) in actions.iter().enumerate() {
+        assert!(
+            !actions[i + 1..].contains(action),
+            "state generated {:?} twice: {:?}",
+            action,
+            state
+        );
+    }
+
+    if state.is_terminal() {
+        return 1;
+    }
+    actions
+        .iter()
+        .map(|action| perft_checked::<G>(&state.apply_action(action), depth - 1, max_actions))
+        .sum()
+}
+
 #[inline]
 fn perft_recursive<G: Game>(state: &G::State, depth: u32) -> u64 {
     if state.is_terminal() {
@@ -17,13 +91,10 @@ fn perft_recursive<G: Game>(state: &G::State, depth: u32) -> u64 {
         return state.count_actions() as u64;
     }
 
-    let count = state
+    state
         .substates()
-        .into_iter()
         .map(|new_state| perft_recursive::<G>(&new_state, depth - 1))
-        .sum::<u64>();
-
-    count
+        .sum::<u64>()
 }
 
 pub fn perft_with_cache<G, T>(state: &G::State, depth: u32, table: &mut T) -> u64
@@ -44,10 +115,37 @@ where
 
     let count = state
         .substates()
-        .into_iter()
         .map(|new_state| perft_with_cache::<G, T>(&new_state, depth - 1, table))
         .sum::<u64>();
 
     table.insert(state.clone(), count);
-    return count;
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::broken::{BrokenGame, BrokenState};
+    use crate::testing::counting::CountingGame;
+
+    #[test]
+    fn checked_perft_agrees_with_perft() {
+        let state = CountingGame::initial_state();
+        assert_eq!(
+            perft_checked::<CountingGame>(&state, 6, 3),
+            perft::<CountingGame>(&state, 6)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "state generated 1 twice")]
+    fn checked_perft_catches_duplicate_actions() {
+        perft_checked::<BrokenGame>(&BrokenGame::initial_state(), 2, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "state generated more than 10 actions")]
+    fn checked_perft_catches_unbounded_generators() {
+        perft_checked::<BrokenGame>(&BrokenState { unbounded: true }, 2, 10);
+    }
 }
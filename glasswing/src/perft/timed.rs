@@ -0,0 +1,174 @@
+use crate::core::{Clock, Game, GwState, SystemClock};
+use std::time::Duration;
+
+/// Number of nodes between two readings of the clock in [perft_timed].
+const CHECK_INTERVAL: u64 = 4096;
+
+/// The results of [perft_timed].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerftReport {
+    /// Exact perft counts of the completed depths, starting at depth 1.
+    pub counts: Vec<u64>,
+    /// The depth that was interrupted by the budget, with the number of
+    /// leaves counted so far, a lower bound of its perft count.
+    pub partial: Option<(u32, u64)>,
+    /// Nodes visited over all depths, including interior nodes.
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl PerftReport {
+    /// The deepest completed depth, 0 if none was completed.
+    pub fn completed_depth(&self) -> u32 {
+        self.counts.len() as u32
+    }
+
+    /// Nodes visited per second.
+    pub fn nps(&self) -> f64 {
+        self.nodes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+struct Budget<'a, C> {
+    clock: &'a C,
+    start: Duration,
+    limit: Duration,
+    nodes: u64,
+    exhausted: bool,
+}
+
+impl<C: Clock> Budget<'_, C> {
+    #[inline]
+    fn visit(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(CHECK_INTERVAL)
+            && self.clock.elapsed_since(self.start) >= self.limit
+        {
+            self.exhausted = true;
+        }
+        !self.exhausted
+    }
+}
+
+/// Runs perft at depths 1 to `max_depth` in turn until `budget` runs out.
+/// The clock is only read every few thousand nodes, so the budget may be
+/// overrun slightly.
+pub fn perft_timed<G: Game>(state: &G::State, max_depth: u32, budget: Duration) -> PerftReport {
+    perft_timed_with_clock::<G, _>(state, max_depth, budget, &SystemClock::new())
+}
+
+/// Like [perft_timed], measuring the budget with the given clock.
+pub fn perft_timed_with_clock<G: Game, C: Clock>(
+    state: &G::State,
+    max_depth: u32,
+    budget: Duration,
+    clock: &C,
+) -> PerftReport {
+    let mut budget = Budget {
+        clock,
+        start: clock.now(),
+        limit: budget,
+        nodes: 0,
+        exhausted: false,
+    };
+    let mut counts = Vec::new();
+    let mut partial = None;
+    for depth in 1..=max_depth {
+        let mut leaves = 0;
+        count_leaves::<G, C>(state, depth, &mut budget, &mut leaves);
+        if budget.exhausted {
+            partial = Some((depth, leaves));
+            break;
+        }
+        counts.push(leaves);
+    }
+    PerftReport {
+        counts,
+        partial,
+        nodes: budget.nodes,
+        elapsed: clock.elapsed_since(budget.start),
+    }
+}
+
+/// Adds the leaves below `state` to `leaves`, stopping once the budget is
+/// exhausted.
+fn count_leaves<G: Game, C: Clock>(
+    state: &G::State,
+    depth: u32,
+    budget: &mut Budget<C>,
+    leaves: &mut u64,
+) {
+    if !budget.visit() {
+        return;
+    }
+    if state.is_terminal() {
+        *leaves += 1;
+    } else if depth == 1 {
+        *leaves += state.count_actions() as u64;
+    } else {
+        for new_state in state.substates() {
+            count_leaves::<G, C>(&new_state, depth - 1, budget, leaves);
+            if budget.exhausted {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perft::perft;
+    use crate::testing::counting::CountingGame;
+    use std::cell::Cell;
+
+    /// A clock that advances by a millisecond every time it is read.
+    struct TickingClock(Cell<Duration>);
+
+    impl Clock for TickingClock {
+        fn now(&self) -> Duration {
+            let now = self.0.get();
+            self.0.set(now + Duration::from_millis(1));
+            now
+        }
+    }
+
+    #[test]
+    fn budget_is_checked_every_interval() {
+        let state = CountingGame::initial_state();
+        let clock = TickingClock(Cell::new(Duration::ZERO));
+        let report =
+            perft_timed_with_clock::<CountingGame, _>(&state, 30, Duration::from_millis(2), &clock);
+
+        // the clock is read at the start and then every CHECK_INTERVAL nodes,
+        // so the budget runs out at the second check
+        assert_eq!(report.nodes, 2 * CHECK_INTERVAL);
+        let completed = report.completed_depth();
+        for (depth, &count) in (1..).zip(&report.counts) {
+            assert_eq!(count, perft::<CountingGame>(&state, depth));
+        }
+        let (depth, leaves) = report.partial.unwrap();
+        assert_eq!(depth, completed + 1);
+        assert!(leaves < perft::<CountingGame>(&state, depth));
+        assert_eq!(
+            report,
+            perft_timed_with_clock::<CountingGame, _>(
+                &state,
+                30,
+                Duration::from_millis(2),
+                &TickingClock(Cell::new(Duration::ZERO)),
+            )
+        );
+    }
+
+    #[test]
+    fn unlimited_budget_completes_every_depth() {
+        let state = CountingGame::initial_state();
+        let clock = crate::core::ManualClock::new();
+        let report =
+            perft_timed_with_clock::<CountingGame, _>(&state, 6, Duration::from_secs(1), &clock);
+        assert_eq!(report.partial, None);
+        assert_eq!(report.counts.len(), 6);
+        assert_eq!(report.elapsed, Duration::ZERO);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::ranking::PlayerId;
+
+/// Pairwise results of a pool of players, as input to [fit].
+/// Draws are counted as half a win for each side.
+#[derive(Debug, Clone)]
+pub struct PairwiseResults {
+    players: usize,
+    // scores[i * players + j] is the score of player i against player j
+    scores: Vec<f64>,
+}
+
+impl PairwiseResults {
+    pub fn new(players: usize) -> Self {
+        PairwiseResults {
+            players,
+            scores: vec![0.0; players * players],
+        }
+    }
+
+    pub fn players(&self) -> usize {
+        self.players
+    }
+
+    pub fn add_win(&mut self, winner: PlayerId, loser: PlayerId) {
+        self.scores[winner * self.players + loser] += 1.0;
+    }
+
+    pub fn add_draw(&mut self, a: PlayerId, b: PlayerId) {
+        self.scores[a * self.players + b] += 0.5;
+        self.scores[b * self.players + a] += 0.5;
+    }
+
+    /// The score of player `a` against player `b`.
+    #[inline]
+    pub fn score(&self, a: PlayerId, b: PlayerId) -> f64 {
+        self.scores[a * self.players + b]
+    }
+
+    /// The number of games played between `a` and `b`.
+    #[inline]
+    pub fn games(&self, a: PlayerId, b: PlayerId) -> f64 {
+        self.score(a, b) + self.score(b, a)
+    }
+
+    /// Splits the players into groups that are connected by played games.
+    /// Strengths are only comparable within such a group.
+    pub fn components(&self) -> Vec<Vec<PlayerId>> {
+        let mut component = vec![usize::MAX; self.players];
+        let mut components = Vec::new();
+
+        for start in 0..self.players {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            let id = components.len();
+            let mut members = vec![start];
+            component[start] = id;
+
+            let mut i = 0;
+            while i < members.len() {
+                let a = members[i];
+                for (b, assigned) in component.iter_mut().enumerate() {
+                    if *assigned == usize::MAX && self.games(a, b) > 0.0 {
+                        *assigned = id;
+                        members.push(b);
+                    }
+                }
+                i += 1;
+            }
+            members.sort_unstable();
+            components.push(members);
+        }
+        components
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FitConfig {
+    /// Iteration stops once no strength changes by more than this amount.
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for FitConfig {
+    fn default() -> Self {
+        FitConfig {
+            tolerance: 1e-9,
+            max_iterations: 10_000,
+        }
+    }
+}
+
+/// The smallest strength used by [Strengths::elo]. Players below it are
+/// considered to have never scored.
+pub const MIN_STRENGTH: f64 = 1e-9;
+
+/// Fitted strengths of one connected group of players.
+#[derive(Debug, Clone)]
+pub struct Strengths {
+    /// Strengths normalised to sum to one, in descending order.
+    pub players: Vec<(PlayerId, f64)>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+impl Strengths {
+    /// Converts the strengths to an Elo-like scale, centered such that the
+    /// geometric mean of the strengths corresponds to 0.
+    ///
+    /// Strengths are floored at [MIN_STRENGTH] first, so players that never
+    /// scored get a very low but finite rating rather than an infinite one.
+    pub fn elo(&self) -> Vec<(PlayerId, f64)> {
+        let log = |strength: f64| strength.max(MIN_STRENGTH).log10();
+        let mean_log = self
+            .players
+            .iter()
+            .map(|&(_, strength)| log(strength))
+            .sum::<f64>()
+            / self.players.len() as f64;
+
+        self.players
+            .iter()
+            .map(|&(player, strength)| (player, 400.0 * (log(strength) - mean_log)))
+            .collect()
+    }
+
+    /// The players that never scored against their group, whose fitted
+    /// strength is not meaningful, see [fit_with].
+    pub fn never_scored(&self) -> Vec<PlayerId> {
+        self.players
+            .iter()
+            .filter(|&&(_, strength)| strength < MIN_STRENGTH)
+            .map(|&(player, _)| player)
+            .collect()
+    }
+}
+
+/// Fits a Bradley-Terry model to the results with the default [FitConfig].
+pub fn fit(results: &PairwiseResults) -> Vec<Strengths> {
+    fit_with(results, &FitConfig::default())
+}
+
+/// Fits a Bradley-Terry model to the results using the iterative MM algorithm.
+/// Every connected group of players is fitted separately, since the relative
+/// strength of players that are not connected by games is undefined.
+///
+/// Players that never scored within their group converge towards zero
+/// strength, as their maximum likelihood estimate does not exist.
+pub fn fit_with(results: &PairwiseResults, config: &FitConfig) -> Vec<Strengths> {
+    results
+        .components()
+        .into_iter()
+        .map(|members| fit_component(results, &members, config))
+        .collect()
+}
+
+fn fit_component(results: &PairwiseResults, members: &[PlayerId], config: &FitConfig) -> Strengths {
+    let n = members.len();
+    let mut strengths = vec![1.0 / n as f64; n];
+    let total_scores = members
+        .iter()
+        .map(|&a| members.iter().map(|&b| results.score(a, b)).sum::<f64>())
+        .collect::<Vec<f64>>();
+
+    let mut iterations = 0;
+    let mut converged = n == 1;
+
+    while !converged && iterations < config.max_iterations {
+        let mut next = vec![0.0; n];
+        for i in 0..n {
+            // pairs that never played are skipped, as two players that never
+            // scored would otherwise add 0 / 0
+            let denominator = (0..n)
+                .filter(|&j| j != i && results.games(members[i], members[j]) > 0.0)
+                .map(|j| results.games(members[i], members[j]) / (strengths[i] + strengths[j]))
+                .sum::<f64>();
+            next[i] = total_scores[i] / denominator;
+        }
+
+        let sum = next.iter().sum::<f64>();
+        next.iter_mut().for_each(|strength| *strength /= sum);
+
+        converged = next
+            .iter()
+            .zip(strengths.iter())
+            .all(|(new, old)| (new - old).abs() <= config.tolerance);
+        strengths = next;
+        iterations += 1;
+    }
+
+    let mut players = members
+        .iter()
+        .copied()
+        .zip(strengths)
+        .collect::<Vec<(PlayerId, f64)>>();
+    players.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    Strengths {
+        players,
+        iterations,
+        converged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn recovers_strengths_of_the_model() {
+        let truth = [8.0, 4.0, 2.0, 1.0];
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut results = PairwiseResults::new(truth.len());
+        for a in 0..truth.len() {
+            for b in a + 1..truth.len() {
+                for _ in 0..400 {
+                    if rng.gen_bool(truth[a] / (truth[a] + truth[b])) {
+                        results.add_win(a, b);
+                    } else {
+                        results.add_win(b, a);
+                    }
+                }
+            }
+        }
+
+        let fitted = fit(&results);
+        assert_eq!(fitted.len(), 1);
+        assert!(fitted[0].converged);
+        let order: Vec<_> = fitted[0]
+            .players
+            .iter()
+            .map(|&(player, _)| player)
+            .collect();
+        assert_eq!(order, [0, 1, 2, 3]);
+        for &(player, strength) in &fitted[0].players {
+            let expected = truth[player] / 15.0;
+            assert!((strength - expected).abs() < 0.2 * expected, "{:?}", fitted);
+        }
+    }
+
+    #[test]
+    fn players_that_never_played_each_other() {
+        // 1 and 2 only lost to 0 and never met
+        let mut results = PairwiseResults::new(3);
+        results.add_win(0, 1);
+        results.add_win(0, 2);
+
+        let fitted = fit(&results);
+        assert_eq!(fitted.len(), 1);
+        let strengths = &fitted[0];
+        assert!(strengths.converged);
+        assert!(strengths
+            .players
+            .iter()
+            .all(|(_, strength)| !strength.is_nan()));
+        assert_eq!(strengths.players[0], (0, 1.0));
+        let mut never_scored = strengths.never_scored();
+        never_scored.sort();
+        assert_eq!(never_scored, [1, 2]);
+
+        let elo = strengths.elo();
+        assert!(elo.iter().all(|(_, rating)| rating.is_finite()));
+        assert!(elo[0].1 > elo[1].1);
+    }
+
+    #[test]
+    fn disconnected_players_are_fitted_separately() {
+        let mut results = PairwiseResults::new(4);
+        results.add_win(0, 1);
+        results.add_draw(0, 1);
+        results.add_draw(2, 3);
+        assert_eq!(results.components(), [vec![0, 1], vec![2, 3]]);
+        let fitted = fit(&results);
+        assert_eq!(fitted.len(), 2);
+        assert!((fitted[1].players[0].1 - 0.5).abs() < 1e-9);
+    }
+}
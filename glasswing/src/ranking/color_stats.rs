@@ -0,0 +1,149 @@
+use crate::core::{Game, GwGameResult, GwTeam};
+use crate::ranking::wilson_interval;
+use crate::train::MatchOutcome;
+use std::fmt;
+
+/// Wins, draws and losses of one side over a number of games.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColorRecord {
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+}
+
+impl ColorRecord {
+    pub fn games(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games().max(1) as f64
+    }
+
+    /// Confidence interval of the win rate, see [wilson_interval].
+    pub fn win_rate_interval(&self, confidence: f64) -> (f64, f64) {
+        wilson_interval(self.wins, self.games(), confidence)
+    }
+
+    fn add(&mut self, won: Option<bool>) {
+        match won {
+            Some(true) => self.wins += 1,
+            Some(false) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+/// Results split by team, over all agents and for each agent of a
+/// [MatchSeries](crate::train::MatchSeries), to quantify the advantage of
+/// moving first. Games without a result count as draws.
+#[derive(Debug, Clone)]
+pub struct ColorStats<G: Game> {
+    teams: Vec<(G::Team, ColorRecord)>,
+    // indexed by agent, 0 for agent A and 1 for agent B
+    agents: [Vec<(G::Team, ColorRecord)>; 2],
+}
+
+impl<G: Game> ColorStats<G> {
+    pub fn new() -> Self {
+        ColorStats {
+            teams: Vec::new(),
+            agents: [Vec::new(), Vec::new()],
+        }
+    }
+
+    pub fn from_outcomes<'a>(outcomes: impl IntoIterator<Item = &'a MatchOutcome<G>>) -> Self {
+        let mut stats = Self::new();
+        for outcome in outcomes {
+            stats.add(outcome);
+        }
+        stats
+    }
+
+    pub fn add(&mut self, outcome: &MatchOutcome<G>) {
+        let winner = outcome.result.as_ref().and_then(|result| result.winner());
+        let first = outcome.first.clone();
+        let second = first.opponent();
+        let (team_a, team_b) = if outcome.swapped {
+            (second.clone(), first.clone())
+        } else {
+            (first.clone(), second.clone())
+        };
+
+        for team in [first, second] {
+            let won = winner.as_ref().map(|winner| *winner == team);
+            record_mut(&mut self.teams, &team).add(won);
+        }
+        for (agent, team) in [team_a, team_b].into_iter().enumerate() {
+            let won = winner.as_ref().map(|winner| *winner == team);
+            record_mut(&mut self.agents[agent], &team).add(won);
+        }
+    }
+
+    /// The results of the given team, over all agents.
+    pub fn team(&self, team: &G::Team) -> ColorRecord {
+        record(&self.teams, team)
+    }
+
+    /// The results of an agent, 0 for agent A and 1 for agent B, when
+    /// playing the given team.
+    pub fn agent(&self, agent: usize, team: &G::Team) -> ColorRecord {
+        record(&self.agents[agent], team)
+    }
+}
+
+impl<G: Game> Default for ColorStats<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn record<T: PartialEq>(records: &[(T, ColorRecord)], team: &T) -> ColorRecord {
+    records
+        .iter()
+        .find(|(t, _)| t == team)
+        .map_or_else(ColorRecord::default, |(_, record)| *record)
+}
+
+fn record_mut<'a, T: Clone + PartialEq>(
+    records: &'a mut Vec<(T, ColorRecord)>,
+    team: &T,
+) -> &'a mut ColorRecord {
+    let index = match records.iter().position(|(t, _)| t == team) {
+        Some(index) => index,
+        None => {
+            records.push((team.clone(), ColorRecord::default()));
+            records.len() - 1
+        }
+    };
+    &mut records[index].1
+}
+
+impl<G: Game> fmt::Display for ColorStats<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut line = |name: String, record: &ColorRecord| {
+            let (lower, upper) = record.win_rate_interval(0.95);
+            writeln!(
+                f,
+                "{}: +{} ={} -{}, win rate {:.1}% [{:.1}%, {:.1}%]",
+                name,
+                record.wins,
+                record.draws,
+                record.losses,
+                100.0 * record.win_rate(),
+                100.0 * lower,
+                100.0 * upper
+            )
+        };
+        for (team, record) in &self.teams {
+            line(format!("{:?}", team), record)?;
+        }
+        for (agent, records) in self.agents.iter().enumerate() {
+            for (team, record) in records {
+                let name = if agent == 0 { "A" } else { "B" };
+                line(format!("Agent {} as {:?}", name, team), record)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+pub mod bradley_terry;
+pub mod color_stats;
+pub mod stats;
+
+pub use color_stats::{ColorRecord, ColorStats};
+pub use stats::*;
+
+/// Index of a player within a pool of players.
+pub type PlayerId = usize;
@@ -0,0 +1,220 @@
+/// An Elo difference estimate together with its confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Estimates the Elo difference from a match result using the logistic model,
+/// with a 95% confidence interval. See [elo_diff_with_confidence].
+pub fn elo_diff(wins: u64, draws: u64, losses: u64) -> EloEstimate {
+    elo_diff_with_confidence(wins, draws, losses, 0.95)
+}
+
+/// Estimates the Elo difference from a match result using the logistic model.
+/// The interval is obtained from a normal approximation of the mean score at
+/// the given `confidence` level, which must lie strictly between 0 and 1.
+///
+/// A score of 0% or 100% yields an infinite Elo difference, and an empty
+/// match yields `NaN`. The bounds of the score interval are clamped to
+/// `[0, 1]`, so lopsided results give infinite rather than `NaN` bounds.
+pub fn elo_diff_with_confidence(
+    wins: u64,
+    draws: u64,
+    losses: u64,
+    confidence: f64,
+) -> EloEstimate {
+    assert!(
+        confidence > 0.0 && confidence < 1.0,
+        "confidence must be in (0, 1)"
+    );
+
+    let games = (wins + draws + losses) as f64;
+    let (w, d, l) = (wins as f64, draws as f64, losses as f64);
+    let score = (w + 0.5 * d) / games;
+
+    // variance of a single game's score around the mean score
+    let variance =
+        (w * (1.0 - score).powi(2) + d * (0.5 - score).powi(2) + l * (0.0 - score).powi(2)) / games;
+    let std_error = (variance / games).sqrt();
+    let z = normal_quantile(0.5 + confidence / 2.0);
+
+    EloEstimate {
+        elo: score_to_elo(score),
+        lower: score_to_elo((score - z * std_error).clamp(0.0, 1.0)),
+        upper: score_to_elo((score + z * std_error).clamp(0.0, 1.0)),
+    }
+}
+
+/// Likelihood of superiority: the probability that the player with the given
+/// number of `wins` is actually the stronger one. Draws do not contribute.
+/// Returns 0.5 if there were no decisive games.
+pub fn los(wins: u64, losses: u64) -> f64 {
+    if wins + losses == 0 {
+        return 0.5;
+    }
+    let (w, l) = (wins as f64, losses as f64);
+    0.5 * (1.0 + erf((w - l) / (2.0 * (w + l)).sqrt()))
+}
+
+/// Wilson score interval of a binomial proportion, e.g. a win rate, at the
+/// given `confidence` level. Returns `(0, 1)` if there were no trials.
+pub fn wilson_interval(successes: u64, trials: u64, confidence: f64) -> (f64, f64) {
+    assert!(
+        confidence > 0.0 && confidence < 1.0,
+        "confidence must be in (0, 1)"
+    );
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z = normal_quantile(0.5 + confidence / 2.0);
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+/// Converts an expected score in `[0, 1]` into an Elo difference.
+#[inline]
+pub fn score_to_elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Converts an Elo difference into an expected score in `[0, 1]`.
+#[inline]
+pub fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Error function, using the approximation 7.1.26 from Abramowitz and Stegun
+/// (maximum absolute error 1.5e-7).
+fn erf(x: f64) -> f64 {
+    const A1: f64 = 0.254829592;
+    const A2: f64 = -0.284496736;
+    const A3: f64 = 1.421413741;
+    const A4: f64 = -1.453152027;
+    const A5: f64 = 1.061405429;
+    const P: f64 = 0.3275911;
+
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1.0 / (1.0 + P * x);
+    let y = 1.0 - ((((A5 * t + A4) * t + A3) * t + A2) * t + A1) * t * (-x * x).exp();
+    sign * y
+}
+
+/// Quantile function of the standard normal distribution, using Acklam's
+/// rational approximation (relative error below 1.2e-9).
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.38357751867269e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if p < P_LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -normal_quantile(1.0 - p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn elo_of_reference_match() {
+        // 60 wins, 20 draws and 20 losses score 70%, about +147 Elo
+        let estimate = elo_diff(60, 20, 20);
+        assert_close(estimate.elo, 147.19, 0.01);
+        assert_close(estimate.lower, 86.23, 0.01);
+        assert_close(estimate.upper, 218.25, 0.01);
+
+        let narrow = elo_diff_with_confidence(60, 20, 20, 0.90);
+        assert_close(narrow.lower, 95.60, 0.01);
+        assert_close(narrow.upper, 205.81, 0.01);
+    }
+
+    #[test]
+    fn even_and_lopsided_matches() {
+        let even = elo_diff(30, 40, 30);
+        assert_close(even.elo, 0.0, 1e-9);
+        assert_close(even.lower, -even.upper, 1e-9);
+
+        let perfect = elo_diff(10, 0, 0);
+        assert_eq!(perfect.elo, f64::INFINITY);
+        assert!(!perfect.lower.is_nan() && !perfect.upper.is_nan());
+        assert!(elo_diff(0, 0, 0).elo.is_nan());
+    }
+
+    #[test]
+    fn likelihood_of_superiority() {
+        assert_close(los(55, 45), 0.8413, 1e-4);
+        assert_close(los(60, 20), 0.999996, 1e-6);
+        assert_close(los(45, 55), 1.0 - los(55, 45), 1e-9);
+        assert_eq!(los(0, 0), 0.5);
+    }
+
+    #[test]
+    fn elo_and_score_are_inverse() {
+        for elo in [-400.0, -100.0, 0.0, 50.0, 800.0] {
+            assert_close(score_to_elo(elo_to_score(elo)), elo, 1e-9);
+        }
+        assert_close(elo_to_score(400.0), 10.0 / 11.0, 1e-12);
+    }
+
+    #[test]
+    fn wilson_interval_contains_rate() {
+        let (lower, upper) = wilson_interval(70, 100, 0.95);
+        assert_close(lower, 0.6041, 1e-4);
+        assert_close(upper, 0.7810, 1e-4);
+        assert_eq!(wilson_interval(0, 0, 0.95), (0.0, 1.0));
+    }
+}
@@ -0,0 +1,420 @@
+use crate::agents::scores::{loss_in, score_from_table, win_in, WIN_SCORE};
+use crate::agents::{Agent, Evaluator, PrepareInfo, SearchError};
+use crate::artifact::{ArtifactError, ArtifactHeader, MAGIC};
+use crate::core::{Game, GwGameResult, GwState, ReverseState};
+use anyhow::Error;
+use cachewing::TranspositionHash;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Version of the binary format written by [Tablebase::write_to].
+const FORMAT_VERSION: u32 = 1;
+
+/// Size in bytes of an entry written by [Tablebase::write_to].
+const ENTRY_SIZE: u64 = 8 + 1 + 4;
+
+/// At most this many entries are allocated up front when reading, so that a
+/// corrupt length cannot exhaust memory before the entries run out.
+const MAX_PREALLOCATED: usize = 1 << 20;
+
+/// Score of a won position with a distance of zero, as used by [TablebaseEvaluator].
+pub const TB_WIN_SCORE: i32 = WIN_SCORE;
+
+/// The exact value of a position, relative to the team to move. Distances
+/// are counted in plies until the end of the game under optimal play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TbValue {
+    Win(u32),
+    Loss(u32),
+    Draw,
+}
+
+impl TbValue {
+    /// Converts the value into a score, preferring faster wins and slower
+    /// losses. Unlike the decisive scores of [scores](crate::agents::scores),
+    /// the distance is counted from this position rather than from the start
+    /// of the game, so only scores of positions at the same ply compare.
+    pub fn score(&self) -> i32 {
+        match *self {
+            TbValue::Win(distance) => win_in(distance),
+            TbValue::Loss(distance) => loss_in(distance),
+            TbValue::Draw => 0,
+        }
+    }
+
+    /// Converts the value of a position `ply` plies into the game into a
+    /// score counted from the start of the game, as evaluators do.
+    pub fn score_at(&self, ply: u32) -> i32 {
+        score_from_table(self.score(), ply)
+    }
+}
+
+/// Exact values of positions, keyed by their [TranspositionHash].
+pub struct Tablebase<G: Game> {
+    entries: HashMap<u64, TbValue>,
+    _game: PhantomData<G>,
+}
+
+impl<G> Tablebase<G>
+where
+    G: Game,
+    G::State: TranspositionHash,
+{
+    pub fn probe(&self, state: &G::State) -> Option<TbValue> {
+        self.entries.get(&state.hash()).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ArtifactError> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the tablebase in a little-endian binary format: an
+    /// [ArtifactHeader], the number of entries, followed by (hash, tag,
+    /// distance) for every entry.
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        ArtifactHeader::for_game::<G>(FORMAT_VERSION).write_to(writer)?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        for (&hash, value) in self.entries.iter() {
+            let (tag, distance) = match *value {
+                TbValue::Win(distance) => (0u8, distance),
+                TbValue::Loss(distance) => (1u8, distance),
+                TbValue::Draw => (2u8, 0),
+            };
+            writer.write_all(&hash.to_le_bytes())?;
+            writer.write_all(&[tag])?;
+            writer.write_all(&distance.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a tablebase written by [Tablebase::write_to]. Tablebases written
+    /// before the header was introduced are still accepted, without any checks.
+    pub fn read_from(reader: &mut impl Read) -> Result<Self, ArtifactError> {
+        let mut u64_buf = [0u8; 8];
+        let mut u32_buf = [0u8; 4];
+        let mut tag = [0u8; 1];
+
+        reader.read_exact(&mut u64_buf)?;
+        if u64_buf == MAGIC {
+            ArtifactHeader::read_after_magic(reader)?.validate::<G>(FORMAT_VERSION)?;
+            reader.read_exact(&mut u64_buf)?;
+        }
+        let len = u64::from_le_bytes(u64_buf);
+        let len = match usize::try_from(len) {
+            Ok(len) if len as u64 <= u64::MAX / ENTRY_SIZE => len,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid tablebase length {}", len),
+                )
+                .into())
+            }
+        };
+
+        let mut entries = HashMap::with_capacity(len.min(MAX_PREALLOCATED));
+        for _ in 0..len {
+            reader.read_exact(&mut u64_buf)?;
+            reader.read_exact(&mut tag)?;
+            reader.read_exact(&mut u32_buf)?;
+            let distance = u32::from_le_bytes(u32_buf);
+            let value = match tag[0] {
+                0 => TbValue::Win(distance),
+                1 => TbValue::Loss(distance),
+                2 => TbValue::Draw,
+                tag => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid tablebase entry tag {}", tag),
+                    )
+                    .into())
+                }
+            };
+            entries.insert(u64::from_le_bytes(u64_buf), value);
+        }
+
+        Ok(Tablebase {
+            entries,
+            _game: PhantomData,
+        })
+    }
+}
+
+/// Builds a tablebase by retrograde analysis.
+///
+/// All positions reachable from the initial state are enumerated, up to
+/// `max_positions` distinct positions. Starting from the terminal positions,
+/// values are then propagated backwards through [ReverseState::predecessors].
+/// Positions whose successors were not all enumerated are left out of the
+/// tablebase, unless one of their successors proves them won.
+///
+/// The analysis assumes that the teams alternate, as a position with a lost
+/// successor is scored as won for the team to move.
+pub fn build<G>(max_positions: usize) -> Tablebase<G>
+where
+    G: Game,
+    G::State: ReverseState<G> + TranspositionHash,
+{
+    // Step 1: enumerate positions and count their distinct successors
+    let mut index = HashMap::new();
+    let mut positions = vec![G::initial_state()];
+    index.insert(positions[0].hash(), 0);
+    let mut remaining = Vec::new();
+    let mut complete = Vec::new();
+
+    let mut i = 0;
+    while i < positions.len() {
+        let mut successors = positions[i]
+            .substates()
+            .map(|s| s.hash())
+            .collect::<Vec<u64>>();
+        successors.sort_unstable();
+        successors.dedup();
+        remaining.push(successors.len());
+
+        let mut is_complete = true;
+        for new_state in positions[i].substates() {
+            match index.entry(new_state.hash()) {
+                Entry::Occupied(_) => {}
+                Entry::Vacant(entry) if positions.len() < max_positions => {
+                    entry.insert(positions.len());
+                    positions.push(new_state);
+                }
+                Entry::Vacant(_) => is_complete = false,
+            }
+        }
+        complete.push(is_complete);
+        i += 1;
+    }
+
+    // Step 2: seed the queue with the terminal positions
+    let mut values: Vec<Option<TbValue>> = vec![None; positions.len()];
+    let mut has_draw = vec![false; positions.len()];
+    let mut longest_loss = vec![0u32; positions.len()];
+    let mut queue = VecDeque::new();
+
+    for (i, state) in positions.iter().enumerate() {
+        if let Some(result) = state.game_result() {
+            values[i] = Some(match result.winner() {
+                None => TbValue::Draw,
+                Some(winner) if winner == state.team_to_move() => TbValue::Win(0),
+                Some(_) => TbValue::Loss(0),
+            });
+            queue.push_back(i);
+        }
+    }
+
+    // Step 3: propagate values backwards
+    while let Some(child) = queue.pop_front() {
+        let value = values[child].unwrap();
+        for predecessor in positions[child].predecessors() {
+            let Some(&parent) = index.get(&predecessor.hash()) else {
+                continue;
+            };
+            if values[parent].is_some() {
+                continue;
+            }
+
+            match value {
+                TbValue::Loss(distance) => {
+                    values[parent] = Some(TbValue::Win(distance + 1));
+                    queue.push_back(parent);
+                    continue;
+                }
+                TbValue::Win(distance) => {
+                    longest_loss[parent] = longest_loss[parent].max(distance + 1);
+                }
+                TbValue::Draw => has_draw[parent] = true,
+            }
+
+            remaining[parent] -= 1;
+            if remaining[parent] == 0 && complete[parent] {
+                values[parent] = Some(if has_draw[parent] {
+                    TbValue::Draw
+                } else {
+                    TbValue::Loss(longest_loss[parent])
+                });
+                queue.push_back(parent);
+            }
+        }
+    }
+
+    // Step 4: find the positions from which a position with successors
+    // missing from the enumeration can be reached. Their values depend on
+    // positions that were never analysed.
+    let mut open = complete.iter().map(|&c| !c).collect::<Vec<bool>>();
+    let mut queue = (0..positions.len())
+        .filter(|&i| open[i])
+        .collect::<VecDeque<usize>>();
+    while let Some(child) = queue.pop_front() {
+        for predecessor in positions[child].predecessors() {
+            if let Some(&parent) = index.get(&predecessor.hash()) {
+                if !open[parent] {
+                    open[parent] = true;
+                    queue.push_back(parent);
+                }
+            }
+        }
+    }
+
+    // Step 5: the remaining unresolved positions only occur in games with
+    // cycles, where neither side can force a result.
+    let entries = positions
+        .iter()
+        .zip(values)
+        .zip(open)
+        .filter_map(|((state, value), open)| match value {
+            Some(value) => Some((state.hash(), value)),
+            None if !open => Some((state.hash(), TbValue::Draw)),
+            None => None,
+        })
+        .collect();
+
+    Tablebase {
+        entries,
+        _game: PhantomData,
+    }
+}
+
+/// An evaluator that returns exact scores for positions in the tablebase,
+/// and falls back to another evaluator otherwise.
+///
+/// Decisive scores are counted from the start of the game, like those of
+/// the bundled heuristics, so `ply` must return the number of plies played
+/// before the given state.
+pub struct TablebaseEvaluator<G: Game, E> {
+    tablebase: Arc<Tablebase<G>>,
+    fallback: E,
+    ply: fn(&G::State) -> u32,
+}
+
+impl<G: Game, E> TablebaseEvaluator<G, E> {
+    pub fn new(tablebase: Arc<Tablebase<G>>, fallback: E, ply: fn(&G::State) -> u32) -> Self {
+        TablebaseEvaluator {
+            tablebase,
+            fallback,
+            ply,
+        }
+    }
+}
+
+impl<G, E> Evaluator<G> for TablebaseEvaluator<G, E>
+where
+    G: Game,
+    G::State: TranspositionHash,
+    G::EvalType: From<i32>,
+    E: Evaluator<G>,
+{
+    fn evaluate_for(&mut self, state: &G::State, team: &G::Team) -> G::EvalType {
+        match self.tablebase.probe(state) {
+            Some(value) if state.team_to_move() == *team => {
+                value.score_at((self.ply)(state)).into()
+            }
+            Some(value) => (-value.score_at((self.ply)(state))).into(),
+            None => self.fallback.evaluate_for(state, team),
+        }
+    }
+
+    fn begin_move(&mut self) {
+        self.fallback.begin_move();
+    }
+
+    fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+        self.fallback.set_time_budget(soft, hard);
+    }
+
+    fn set_node_budget(&mut self, nodes: u64) {
+        self.fallback.set_node_budget(nodes);
+    }
+
+    fn end_move(&mut self) {
+        self.fallback.end_move();
+    }
+
+    fn take_error(&mut self) -> Option<SearchError> {
+        self.fallback.take_error()
+    }
+}
+
+/// An agent that plays perfectly in positions contained in the tablebase,
+/// and delegates to another agent otherwise.
+pub struct TablebaseAgent<G: Game, A> {
+    tablebase: Arc<Tablebase<G>>,
+    fallback: A,
+}
+
+impl<G: Game, A> TablebaseAgent<G, A> {
+    pub fn new(tablebase: Arc<Tablebase<G>>, fallback: A) -> Self {
+        TablebaseAgent {
+            tablebase,
+            fallback,
+        }
+    }
+}
+
+impl<G, A> Agent<G> for TablebaseAgent<G, A>
+where
+    G: Game,
+    G::State: TranspositionHash,
+    A: Agent<G>,
+{
+    fn select_action(&mut self, state: &G::State) -> Result<G::Action, Error> {
+        if self.tablebase.probe(state).is_some() {
+            // The values of the successors are relative to the opponent,
+            // therefore the best action minimises them.
+            let best = state
+                .actions()
+                .into_iter()
+                .map(|action| {
+                    let value = self.tablebase.probe(&state.apply_action(&action));
+                    (action, value)
+                })
+                .filter_map(|(action, value)| value.map(|value| (action, value.score())))
+                .min_by_key(|(_, score)| *score);
+
+            if let Some((action, _)) = best {
+                return Ok(action);
+            }
+        }
+        self.fallback.select_action(state)
+    }
+
+    fn prepare(&mut self, initial_state: &G::State, info: &PrepareInfo<G>) {
+        self.fallback.prepare(initial_state, info);
+    }
+
+    fn observe(&mut self, prev: &G::State, action: &G::Action, next: &G::State) {
+        self.fallback.observe(prev, action, next);
+    }
+
+    fn accept_swap(&mut self, state: &G::State) -> bool {
+        self.fallback.accept_swap(state)
+    }
+
+    fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+        self.fallback.set_time_budget(soft, hard);
+    }
+
+    fn set_node_budget(&mut self, nodes: u64) {
+        self.fallback.set_node_budget(nodes);
+    }
+}
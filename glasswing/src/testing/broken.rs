@@ -0,0 +1,52 @@
+//! A game with a broken move generator, for testing the diagnostics that
+//! are meant to catch one.
+
+use crate::core::{Game, GameResult, GwState, Team};
+
+/// A game that never ends. Its states generate the same action twice, or
+/// an endless stream of actions if `unbounded` is set, and applying action
+/// 2 panics.
+#[derive(Debug)]
+pub struct BrokenGame;
+
+#[derive(Debug, Clone)]
+pub struct BrokenState {
+    pub unbounded: bool,
+}
+
+impl Game for BrokenGame {
+    type State = BrokenState;
+    type Action = u32;
+    type Team = Team;
+    type GameResult = GameResult<Team>;
+    type EvalType = i32;
+
+    fn initial_state() -> BrokenState {
+        BrokenState { unbounded: false }
+    }
+}
+
+impl GwState<BrokenGame> for BrokenState {
+    type ActionIter = Box<dyn Iterator<Item = u32>>;
+
+    fn actions(&self) -> Self::ActionIter {
+        if self.unbounded {
+            Box::new(0..)
+        } else {
+            Box::new([1, 2, 1].into_iter())
+        }
+    }
+
+    fn team_to_move(&self) -> Team {
+        Team::One
+    }
+
+    fn apply_action(&self, action: &u32) -> Self {
+        assert_ne!(*action, 2, "cannot apply action 2");
+        self.clone()
+    }
+
+    fn game_result(&self) -> Option<GameResult<Team>> {
+        None
+    }
+}
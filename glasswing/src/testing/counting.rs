@@ -0,0 +1,114 @@
+//! A small game for the tests of this crate, which cannot use the games in
+//! `glasswing_games` as those implement the traits of a separate build.
+
+use crate::agents::Evaluator;
+use crate::core::{Game, GameResult, GwState, ProgressState, Team};
+use std::fmt;
+
+/// The count that wins the game.
+pub const TARGET: u32 = 21;
+
+/// Two teams take turns adding 1, 2 or 3 to a counter starting at 0. The
+/// team that reaches [TARGET] wins. The team to move loses under perfect
+/// play whenever the remaining count is a multiple of 4.
+#[derive(Debug)]
+pub struct CountingGame;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountingState {
+    pub count: u32,
+    pub team: Team,
+}
+
+impl Game for CountingGame {
+    type State = CountingState;
+    type Action = u32;
+    type Team = Team;
+    type GameResult = GameResult<Team>;
+    type EvalType = i32;
+
+    const MAX_ACTIONS: Option<usize> = Some(3);
+
+    fn initial_state() -> CountingState {
+        CountingState {
+            count: 0,
+            team: Team::One,
+        }
+    }
+}
+
+impl CountingState {
+    pub fn new(count: u32, team: Team) -> Self {
+        CountingState { count, team }
+    }
+
+    /// Whether the team to move loses under perfect play.
+    pub fn is_lost(&self) -> bool {
+        (TARGET - self.count).is_multiple_of(4)
+    }
+}
+
+impl fmt::Display for CountingState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?} to move)", self.count, self.team)
+    }
+}
+
+impl GwState<CountingGame> for CountingState {
+    type ActionIter = Vec<u32>;
+
+    fn actions(&self) -> Vec<u32> {
+        (1..=3).filter(|n| self.count + n <= TARGET).collect()
+    }
+
+    fn team_to_move(&self) -> Team {
+        self.team
+    }
+
+    fn apply_action(&self, action: &u32) -> Self {
+        CountingState {
+            count: self.count + action,
+            team: self.team.opponent(),
+        }
+    }
+
+    fn game_result(&self) -> Option<GameResult<Team>> {
+        (self.count == TARGET).then(|| GameResult::Win(self.team.opponent()))
+    }
+}
+
+/// Only adding 3 counts as progress, so that tests can choose when it is made.
+impl ProgressState<CountingGame> for CountingState {
+    fn is_progress(&self, action: &u32, _next: &Self) -> bool {
+        *action == 3
+    }
+}
+
+impl cachewing::TranspositionHash for CountingState {
+    fn hash(&self) -> u64 {
+        1 + ((self.count as u64) << 1 | (self.team == Team::Two) as u64)
+    }
+}
+
+/// Scores won positions 100 and lost ones -100, and every other position
+/// by the perfect play rule if `perfect` is set, or 0 otherwise.
+pub struct CountingEvaluator {
+    pub perfect: bool,
+}
+
+impl Evaluator<CountingGame> for CountingEvaluator {
+    fn evaluate_for(&mut self, state: &CountingState, team: &Team) -> i32 {
+        let score = match state.game_result() {
+            Some(GameResult::Win(winner)) if winner == *team => return 100,
+            Some(_) => return -100,
+            None if self.perfect && state.is_lost() => -1,
+            None if self.perfect => 1,
+            None => 0,
+        };
+        if *team == state.team {
+            score
+        } else {
+            -score
+        }
+    }
+}
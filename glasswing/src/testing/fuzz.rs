@@ -0,0 +1,106 @@
+use crate::core::{Game, GwState};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// An invariant of [GwState] broken by a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FuzzViolation {
+    /// `is_terminal()` disagrees with `game_result().is_some()`.
+    TerminalMismatch { is_terminal: bool },
+    /// `count_actions()` differs from the number of actions generated.
+    CountMismatch { count: usize, generated: usize },
+    /// `actions_into()` generates different actions than `actions()`.
+    ActionsIntoMismatch,
+    /// A terminal state generates actions.
+    TerminalHasActions { generated: usize },
+    /// A non-terminal state generates no actions.
+    NoActions,
+    /// An action is generated more than once.
+    DuplicateAction,
+}
+
+/// A broken invariant, together with the actions leading from the initial
+/// state to the offending state.
+#[derive(Debug, Clone)]
+pub struct FuzzFailure<G: Game> {
+    pub actions: Vec<G::Action>,
+    pub violation: FuzzViolation,
+}
+
+/// Plays `iterations` random games of at most `max_plies` plies, checking
+/// the invariants of [GwState] in every state visited. Returns the first
+/// violation found, with the actions the failing game played to reach it.
+/// The line is not minimised, so shorter ones may reach a violation too.
+pub fn fuzz_game<G, R>(
+    rng: &mut R,
+    max_plies: usize,
+    iterations: usize,
+) -> Result<(), FuzzFailure<G>>
+where
+    G: Game,
+    G::Action: PartialEq,
+    R: Rng,
+{
+    let mut buf = Vec::new();
+    for _ in 0..iterations {
+        let mut state = G::initial_state();
+        let mut line = Vec::new();
+        for _ in 0..=max_plies {
+            let actions = state.actions().into_iter().collect::<Vec<G::Action>>();
+            if let Some(violation) = check_state::<G>(&state, &actions, &mut buf) {
+                return Err(FuzzFailure {
+                    actions: line,
+                    violation,
+                });
+            }
+            match actions.choose(rng) {
+                Some(action) if !state.is_terminal() => {
+                    state = state.apply_action(action);
+                    line.push(action.clone());
+                }
+                _ => break,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_state<G>(
+    state: &G::State,
+    actions: &[G::Action],
+    buf: &mut Vec<G::Action>,
+) -> Option<FuzzViolation>
+where
+    G: Game,
+    G::Action: PartialEq,
+{
+    let is_terminal = state.is_terminal();
+    if is_terminal != state.game_result().is_some() {
+        return Some(FuzzViolation::TerminalMismatch { is_terminal });
+    }
+    if is_terminal && !actions.is_empty() {
+        return Some(FuzzViolation::TerminalHasActions {
+            generated: actions.len(),
+        });
+    }
+    if !is_terminal && actions.is_empty() {
+        return Some(FuzzViolation::NoActions);
+    }
+    let count = state.count_actions();
+    if count != actions.len() {
+        return Some(FuzzViolation::CountMismatch {
+            count,
+            generated: actions.len(),
+        });
+    }
+    state.actions_into(buf);
+    if buf.as_slice() != actions {
+        return Some(FuzzViolation::ActionsIntoMismatch);
+    }
+    for (i, action) in actions.iter().enumerate() {
+        if actions[i + 1..].contains(action) {
+            return Some(FuzzViolation::DuplicateAction);
+        }
+    }
+    None
+}
@@ -0,0 +1,11 @@
+//! Utilities for testing implementations of [Game](crate::core::Game).
+
+#[cfg(test)]
+pub(crate) mod broken;
+#[cfg(test)]
+pub(crate) mod counting;
+pub mod fuzz;
+pub mod symmetry;
+
+pub use fuzz::*;
+pub use symmetry::*;
@@ -0,0 +1,55 @@
+use crate::agents::Evaluator;
+use crate::core::{Game, GwState, GwTeam};
+
+/// Maps a state to an equivalent one, such as its mirror image.
+pub type Transform<G> = fn(&<G as Game>::State) -> <G as Game>::State;
+
+/// A state whose evaluation changes under a symmetry of the board.
+#[derive(Debug, Clone)]
+pub struct SymmetryViolation<G: Game> {
+    pub state: G::State,
+    /// Index of the offending transform.
+    pub transform: usize,
+    pub team: G::Team,
+    pub original: f64,
+    pub transformed: f64,
+}
+
+/// Checks that `evaluator` scores every position like its images under each
+/// of `transforms`, for both teams, up to `epsilon`. Returns every violation
+/// found.
+///
+/// Checking the generators of a symmetry group suffices for the
+/// whole group.
+pub fn check_symmetry<'a, G, E>(
+    evaluator: &mut E,
+    transforms: &[Transform<G>],
+    positions: impl IntoIterator<Item = &'a G::State>,
+    epsilon: f64,
+) -> Vec<SymmetryViolation<G>>
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G>,
+{
+    let mut violations = Vec::new();
+    for state in positions {
+        let to_move = state.team_to_move();
+        for team in [to_move.clone(), to_move.opponent()] {
+            let original = evaluator.evaluate_for(state, &team).into();
+            for (i, transform) in transforms.iter().enumerate() {
+                let transformed = evaluator.evaluate_for(&transform(state), &team).into();
+                if (original - transformed).abs() > epsilon {
+                    violations.push(SymmetryViolation {
+                        state: state.clone(),
+                        transform: i,
+                        team: team.clone(),
+                        original,
+                        transformed,
+                    });
+                }
+            }
+        }
+    }
+    violations
+}
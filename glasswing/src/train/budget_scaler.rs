@@ -0,0 +1,192 @@
+use crate::agents::{LengthEstimate, TimeManager};
+use std::time::Duration;
+
+/// Gives each agent a fixed amount of time for the whole game, like a chess
+/// clock, and scales the budget of every move to the estimated number of
+/// moves left, see [Pit::with_budget_scaler](crate::train::Pit::with_budget_scaler).
+///
+/// The estimate starts from a guessed game length and is updated with the
+/// length of every game played with the scaler, so reusing the scaler for
+/// the games of a series keeps an average of their lengths.
+#[derive(Debug, Clone)]
+pub struct BudgetScaler {
+    time: Duration,
+    manager: TimeManager,
+}
+
+impl BudgetScaler {
+    /// Gives each agent `time` per game, expecting games of `plies` plies
+    /// until games are observed.
+    pub fn new(time: Duration, plies: usize) -> Self {
+        BudgetScaler {
+            time,
+            manager: TimeManager::new().with_length_estimate(LengthEstimate::new(plies)),
+        }
+    }
+
+    /// Replaces the time manager, e.g. to use a different length estimate
+    /// for the game. Without a length estimate, the manager's fixed fraction
+    /// of the remaining time is used.
+    pub fn with_manager(mut self, manager: TimeManager) -> Self {
+        self.manager = manager;
+        self
+    }
+
+    /// The time each agent has for the whole game.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    pub fn manager(&self) -> &TimeManager {
+        &self.manager
+    }
+
+    /// The soft and hard budget of a move after `ply` plies, for an agent
+    /// that has used `used` of its time. The hard budget never exceeds the
+    /// time left on the agent's clock.
+    pub fn budget(&self, used: Duration, ply: usize) -> (Duration, Duration) {
+        self.manager.budget_at(self.time.saturating_sub(used), ply)
+    }
+
+    /// Updates the length estimate with the length of a finished game.
+    pub fn observe_game(&mut self, plies: usize) {
+        if let Some(estimate) = self.manager.length_estimate_mut() {
+            estimate.observe_game(plies);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::Agent;
+    use crate::core::{Game, GameResult, ManualClock, MatchError, Team};
+    use crate::testing::counting::{CountingGame, CountingState, TARGET};
+    use crate::train::Pit;
+    use anyhow::Error;
+
+    enum Spend {
+        Soft,
+        Hard,
+        Fixed(Duration),
+    }
+
+    /// Always adds 1, thinking for as long as it is told to.
+    struct Spender {
+        clock: ManualClock,
+        spend: Spend,
+        budget: (Duration, Duration),
+    }
+
+    impl Spender {
+        fn new(clock: &ManualClock, spend: Spend) -> Self {
+            Spender {
+                clock: clock.clone(),
+                spend,
+                budget: (Duration::ZERO, Duration::ZERO),
+            }
+        }
+    }
+
+    impl Agent<CountingGame> for Spender {
+        fn select_action(&mut self, _: &CountingState) -> Result<u32, Error> {
+            self.clock.advance(match self.spend {
+                Spend::Soft => self.budget.0,
+                Spend::Hard => self.budget.1,
+                Spend::Fixed(time) => time,
+            });
+            Ok(1)
+        }
+
+        fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+            self.budget = (soft, hard);
+        }
+    }
+
+    fn assert_close(actual: Duration, expected: Duration) {
+        assert!(
+            actual.abs_diff(expected) < Duration::from_micros(1),
+            "{:?} is not {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn budgets_follow_the_moves_left() {
+        let clock = ManualClock::new();
+        let scaler = BudgetScaler::new(Duration::from_secs(10), 20);
+        let mut pit = Pit::new(
+            Spender::new(&clock, Spend::Soft),
+            Spender::new(&clock, Spend::Soft),
+            CountingGame::initial_state(),
+        )
+        .with_clock(clock.clone())
+        .with_budget_scaler(scaler.clone());
+
+        let turns: Vec<_> = (&mut pit).collect();
+        assert_eq!(turns.len(), TARGET as usize);
+        assert_eq!(pit.game_result(), Some(GameResult::Win(Team::One)));
+
+        let mut used = [Duration::ZERO; 2];
+        for turn in &turns {
+            let (soft, hard) = turn.budget.unwrap();
+            assert_eq!((soft, hard), scaler.budget(used[turn.agent], turn.ply));
+            // the remaining time divided by the moves left, with at least
+            // 10 plies left
+            let remaining = scaler.time() - used[turn.agent];
+            let moves_left = ((20 - turn.ply as i32).max(10) as f64 / 2.0).ceil();
+            assert_close(soft, remaining.div_f64(moves_left));
+            assert!(hard <= remaining.mul_f64(0.5));
+            assert_eq!(turn.elapsed, soft);
+            used[turn.agent] += turn.elapsed;
+        }
+        // an even split until the minimum of plies left is reached
+        assert_close(turns[0].budget.unwrap().0, Duration::from_secs(1));
+        assert_close(turns[10].budget.unwrap().0, Duration::from_secs(1));
+        assert_close(turns[12].budget.unwrap().0, Duration::from_millis(800));
+        assert_eq!(pit.agentA_time(), used[0]);
+        assert!(used.iter().all(|&used| used < scaler.time()));
+
+        let estimate = pit.budget_scaler().unwrap().manager().length_estimate();
+        assert_eq!(estimate.unwrap().expected_length(), TARGET as f64);
+    }
+
+    #[test]
+    fn agents_using_the_hard_budget_keep_time() {
+        let clock = ManualClock::new();
+        let scaler = BudgetScaler::new(Duration::from_secs(10), 20);
+        let mut pit = Pit::new(
+            Spender::new(&clock, Spend::Hard),
+            Spender::new(&clock, Spend::Hard),
+            CountingGame::initial_state(),
+        )
+        .with_clock(clock)
+        .with_budget_scaler(scaler);
+        for turn in &mut pit {
+            assert_eq!(turn.elapsed, turn.budget.unwrap().1);
+        }
+        assert!(pit.agentA_time() < Duration::from_secs(10));
+        assert!(pit.agentB_time() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn exceeding_the_clock_fails() {
+        let clock = ManualClock::new();
+        let mut pit = Pit::new(
+            Spender::new(&clock, Spend::Fixed(Duration::from_secs(6))),
+            Spender::new(&clock, Spend::Soft),
+            CountingGame::initial_state(),
+        )
+        .with_clock(clock)
+        .with_budget_scaler(BudgetScaler::new(Duration::from_secs(10), 20));
+
+        assert!(pit.step().unwrap().is_some());
+        assert!(pit.step().unwrap().is_some());
+        let err = pit.step().unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<MatchError<CountingGame>>(),
+            Some(MatchError::TimeLimitExceeded(time)) if *time == Duration::from_secs(10)
+        ));
+    }
+}
@@ -1,3 +1,25 @@
+pub mod budget_scaler;
+pub mod odds;
+pub mod openings;
+pub mod outcome;
+#[cfg(feature = "threads")]
+pub mod parallel;
 pub mod pit;
+pub mod series;
+pub mod session;
+pub mod snapshot;
+pub mod termination;
+pub mod turn_record;
 
+pub use budget_scaler::*;
+pub use odds::*;
+pub use openings::*;
+pub use outcome::*;
+#[cfg(feature = "threads")]
+pub use parallel::*;
 pub use pit::*;
+pub use series::*;
+pub use session::*;
+pub use snapshot::*;
+pub use termination::*;
+pub use turn_record::*;
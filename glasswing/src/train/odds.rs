@@ -0,0 +1,118 @@
+use crate::agents::Agent;
+use crate::core::Game;
+use crate::train::SeriesResult;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+/// Budgets for the agents of a [MatchSeries](crate::train::MatchSeries),
+/// see [MatchSeries::with_odds](crate::train::MatchSeries::with_odds). `a`
+/// is given to the agent from `factory_a` and `b` to the one from
+/// `factory_b`, regardless of which moves first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Odds {
+    /// Time per move, passed as both the soft and the hard limit of
+    /// [Agent::set_time_budget].
+    Time { a: Duration, b: Duration },
+    /// Nodes per move, passed to [Agent::set_node_budget].
+    Nodes { a: u64, b: u64 },
+}
+
+impl Odds {
+    pub(crate) fn apply<G: Game>(&self, agent_a: &mut impl Agent<G>, agent_b: &mut impl Agent<G>) {
+        match *self {
+            Odds::Time { a, b } => {
+                agent_a.set_time_budget(a, a);
+                agent_b.set_time_budget(b, b);
+            }
+            Odds::Nodes { a, b } => {
+                agent_a.set_node_budget(a);
+                agent_b.set_node_budget(b);
+            }
+        }
+    }
+}
+
+/// The result of [find_equalizing_budget].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OddsSearch {
+    /// The budget whose series scored closest to the target.
+    pub budget: u64,
+    pub score: f64,
+    /// Whether that score is within the tolerance of the target.
+    pub converged: bool,
+    /// Every budget tried, with the score of its series, in order.
+    pub trials: Vec<(u64, f64)>,
+}
+
+/// Finds the budget at which a candidate agent scores `target_score`
+/// against a reference, e.g. the number of nodes a shallow search needs to
+/// match a deeper one.
+///
+/// `play` plays a series with the candidate as agent A, given the budget, for
+/// instance a [MatchSeries](crate::train::MatchSeries) with
+/// [odds](crate::train::MatchSeries::with_odds) built from the budget.
+/// Budgets are bisected geometrically within `range`, assuming the score
+/// grows with the budget, until a series scores within `tolerance` of the
+/// target or `max_series` series have been played.
+pub fn find_equalizing_budget<G: Game>(
+    mut play: impl FnMut(u64) -> SeriesResult<G>,
+    range: RangeInclusive<u64>,
+    target_score: f64,
+    tolerance: f64,
+    max_series: usize,
+) -> OddsSearch {
+    let (mut low, mut high) = ((*range.start()).max(1), *range.end());
+    let mut trials = Vec::new();
+    while trials.len() < max_series && low <= high {
+        let budget = ((low as f64 * high as f64).sqrt() as u64).clamp(low, high);
+        let score = play(budget).total().score();
+        trials.push((budget, score));
+        if (score - target_score).abs() <= tolerance {
+            break;
+        } else if score < target_score {
+            match budget.checked_add(1) {
+                Some(budget) => low = budget,
+                None => break,
+            }
+        } else {
+            high = budget - 1;
+        }
+    }
+
+    let (budget, score) = trials
+        .iter()
+        .copied()
+        .min_by(|a, b| {
+            (a.1 - target_score)
+                .abs()
+                .total_cmp(&(b.1 - target_score).abs())
+        })
+        .unwrap_or((low, f64::NAN));
+    OddsSearch {
+        budget,
+        score,
+        converged: (score - target_score).abs() <= tolerance,
+        trials,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::counting::CountingGame;
+
+    #[test]
+    fn search_stops_at_the_largest_budget() {
+        // a candidate that never scores keeps raising the lower bound
+        let search = find_equalizing_budget::<CountingGame>(
+            |_| SeriesResult::new(Vec::new()),
+            u64::MAX - 2..=u64::MAX,
+            0.5,
+            0.01,
+            10,
+        );
+        assert!(!search.converged);
+        assert!(search.trials.len() < 10);
+        assert_eq!(search.trials.last().unwrap().0, u64::MAX);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::core::{Game, GwState};
+use anyhow::{anyhow, Error};
+
+/// A set of starting positions. Playing every position with both color
+/// assignments gives both agents the same variety of games.
+#[derive(Debug, Clone)]
+pub struct OpeningSuite<G: Game> {
+    positions: Vec<G::State>,
+}
+
+impl<G: Game> OpeningSuite<G> {
+    pub fn new(positions: Vec<G::State>) -> Self {
+        OpeningSuite { positions }
+    }
+
+    /// Builds the suite by playing each line of actions from the initial state.
+    pub fn from_lines<L>(lines: impl IntoIterator<Item = L>) -> Result<Self, Error>
+    where
+        L: IntoIterator<Item = G::Action>,
+        G::Action: PartialEq,
+    {
+        let mut positions = Vec::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            let mut state = G::initial_state();
+            for (ply, action) in line.into_iter().enumerate() {
+                if !state.actions().into_iter().any(|legal| legal == action) {
+                    return Err(anyhow!(
+                        "illegal action {:?} at ply {} of opening {}",
+                        action,
+                        ply,
+                        i
+                    ));
+                }
+                state = state.apply_action(&action);
+            }
+            positions.push(state);
+        }
+        Ok(OpeningSuite { positions })
+    }
+
+    pub fn positions(&self) -> &[G::State] {
+        &self.positions
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// The starting position of the given game and its index in the suite.
+    /// Consecutive pairs of games share an opening, so with alternating
+    /// colors every opening is played once with each color assignment.
+    pub fn opening_for(&self, game: usize) -> (usize, &G::State) {
+        let index = (game / 2) % self.positions.len();
+        (index, &self.positions[index])
+    }
+}
@@ -0,0 +1,187 @@
+use crate::agents::Agent;
+use crate::core::{Game, GwGameResult, GwState};
+use crate::train::{Odds, OpeningSuite, Pit, Termination};
+use std::thread;
+
+#[derive(Debug, Clone)]
+pub struct MatchConfig {
+    /// Number of worker threads, if the `threads` feature is enabled.
+    /// Defaults to the available parallelism.
+    pub threads: usize,
+    /// Games still running after this many plies are stopped without a result.
+    pub max_plies: Option<usize>,
+    /// Seed from which the per-game seeds passed to the agent factories are
+    /// derived. Each agent gets its own seed, which does not depend on its color.
+    pub base_seed: u64,
+    /// Whether to keep the actions played in each game.
+    pub record_actions: bool,
+    /// Maximum number of finished games buffered between the workers and the collector.
+    pub channel_bound: usize,
+    /// Budgets given to the agents after they are built, see [Odds].
+    pub odds: Option<Odds>,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        MatchConfig {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            max_plies: None,
+            base_seed: 0,
+            record_actions: false,
+            channel_bound: 64,
+            odds: None,
+        }
+    }
+}
+
+/// The outcome of a single game played by a [MatchSeries](crate::train::MatchSeries).
+#[derive(Debug, Clone)]
+pub struct MatchOutcome<G: Game> {
+    /// Index of the game, outcomes are returned in this order.
+    pub game: usize,
+    /// The seed passed to `factory_a` for this game.
+    pub seed_a: u64,
+    /// The seed passed to `factory_b` for this game.
+    pub seed_b: u64,
+    /// Whether the agent from `factory_b` made the first move.
+    pub swapped: bool,
+    /// Index of the starting position, if the game used an [OpeningSuite].
+    pub opening: Option<usize>,
+    /// The team that moved first.
+    pub first: G::Team,
+    /// `None` if the game did not reach a terminal state.
+    pub result: Option<G::GameResult>,
+    /// Why the game stopped.
+    pub termination: Termination,
+    pub plies: usize,
+    /// The actions played, if recording was enabled.
+    pub actions: Option<Vec<G::Action>>,
+}
+
+impl<G: Game> MatchOutcome<G> {
+    /// The score of the agent built by `factory_a`: 1 for a win, 0 for a
+    /// loss and 0.5 for a draw or a game that did not reach a terminal state.
+    pub fn score_a(&self) -> f64 {
+        match self.result.as_ref().and_then(|result| result.winner()) {
+            None => 0.5,
+            Some(winner) => {
+                if (winner == self.first) != self.swapped {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Plays game number `game` of a series between fresh agents from the two
+/// factories, each built from its own seed. The agent from `factory_a`
+/// moves first in even games, the one from `factory_b` in odd games.
+pub(crate) fn play_series_game<G, A, B, FA, FB>(
+    game: usize,
+    factory_a: &FA,
+    factory_b: &FB,
+    config: &MatchConfig,
+    openings: Option<&OpeningSuite<G>>,
+) -> MatchOutcome<G>
+where
+    G: Game,
+    A: Agent<G>,
+    B: Agent<G>,
+    FA: Fn(u64) -> A,
+    FB: Fn(u64) -> B,
+{
+    let seeds = agent_seeds(config.base_seed, game);
+    let (mut agent_a, mut agent_b) = (factory_a(seeds.0), factory_b(seeds.1));
+    if let Some(odds) = config.odds {
+        odds.apply(&mut agent_a, &mut agent_b);
+    }
+    if game.is_multiple_of(2) {
+        play_game(game, seeds, false, agent_a, agent_b, config, openings)
+    } else {
+        play_game(game, seeds, true, agent_b, agent_a, config, openings)
+    }
+}
+
+/// Plays game number `game`, where the agent moving first is built by
+/// `factory_b` if `swapped` is set.
+fn play_game<G, A, B>(
+    game: usize,
+    (seed_a, seed_b): (u64, u64),
+    swapped: bool,
+    first: A,
+    second: B,
+    config: &MatchConfig,
+    openings: Option<&OpeningSuite<G>>,
+) -> MatchOutcome<G>
+where
+    G: Game,
+    A: Agent<G>,
+    B: Agent<G>,
+{
+    let (opening, start) = match openings {
+        Some(openings) => {
+            let (index, state) = openings.opening_for(game);
+            (Some(index), state.clone())
+        }
+        None => (None, G::initial_state()),
+    };
+    let first_team = start.team_to_move();
+    let mut pit = Pit::<G, A, B>::new(first, second, start);
+    let max_plies = config.max_plies.unwrap_or(usize::MAX);
+
+    let mut plies = 0;
+    let mut actions = config.record_actions.then(Vec::new);
+    let termination = loop {
+        if plies == max_plies && !pit.state().is_terminal() {
+            break Termination::MaxPlies;
+        }
+        match pit.step() {
+            Ok(Some(turn)) => {
+                plies += 1;
+                if let Some(actions) = actions.as_mut() {
+                    actions.push(turn.action);
+                }
+            }
+            Ok(None) => match pit.adjudication() {
+                Some(reason) => break Termination::Adjudicated(reason),
+                None => break Termination::Natural,
+            },
+            Err(err) => {
+                log::warn!("Game {} aborted: {:#}", game, err);
+                break Termination::Error(format!("{:#}", err));
+            }
+        }
+    };
+
+    MatchOutcome {
+        game,
+        seed_a,
+        seed_b,
+        swapped,
+        opening,
+        first: first_team,
+        result: pit.game_result(),
+        termination,
+        plies,
+        actions,
+    }
+}
+
+/// Derives the seeds of the agents from `factory_a` and `factory_b` in a game.
+/// Both agents draw from independent streams, even if built by the same factory.
+pub(crate) fn agent_seeds(base_seed: u64, game: usize) -> (u64, u64) {
+    (
+        game_seed(base_seed, 2 * game),
+        game_seed(base_seed, 2 * game + 1),
+    )
+}
+
+/// Derives a seed from the base seed and an index (SplitMix64).
+fn game_seed(base_seed: u64, game: usize) -> u64 {
+    let mut z = base_seed.wrapping_add((game as u64 + 1).wrapping_mul(0x9E3779B97F4A7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
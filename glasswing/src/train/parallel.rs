@@ -0,0 +1,74 @@
+use crate::agents::Agent;
+use crate::core::Game;
+use crate::train::outcome::play_series_game;
+use crate::train::{MatchConfig, MatchOutcome, OpeningSuite};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Plays `n_games` independent games on a pool of threads. Each game uses
+/// fresh agents built by the factories from seeds derived from
+/// `config.base_seed`, the game index and the factory, so results are
+/// reproducible regardless of scheduling. The factories alternate colors: in even games
+/// the agent from `factory_a` moves first, in odd games the one from `factory_b`.
+pub fn run_matches_parallel<G, A, B, FA, FB>(
+    factory_a: FA,
+    factory_b: FB,
+    n_games: usize,
+    config: &MatchConfig,
+) -> Vec<MatchOutcome<G>>
+where
+    G: Game,
+    G::Action: Send,
+    G::Team: Send,
+    G::GameResult: Send,
+    A: Agent<G>,
+    B: Agent<G>,
+    FA: Fn(u64) -> A + Sync,
+    FB: Fn(u64) -> B + Sync,
+{
+    run_parallel(factory_a, factory_b, n_games, config, None)
+}
+
+pub(crate) fn run_parallel<G, A, B, FA, FB>(
+    factory_a: FA,
+    factory_b: FB,
+    n_games: usize,
+    config: &MatchConfig,
+    openings: Option<&OpeningSuite<G>>,
+) -> Vec<MatchOutcome<G>>
+where
+    G: Game,
+    G::Action: Send,
+    G::Team: Send,
+    G::GameResult: Send,
+    A: Agent<G>,
+    B: Agent<G>,
+    FA: Fn(u64) -> A + Sync,
+    FB: Fn(u64) -> B + Sync,
+{
+    let next_game = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::sync_channel(config.channel_bound.max(1));
+
+    let mut outcomes = thread::scope(|scope| {
+        for _ in 0..config.threads.max(1) {
+            let sender = sender.clone();
+            let (next_game, factory_a, factory_b) = (&next_game, &factory_a, &factory_b);
+            scope.spawn(move || loop {
+                let game = next_game.fetch_add(1, Ordering::Relaxed);
+                if game >= n_games {
+                    break;
+                }
+                let outcome = play_series_game(game, factory_a, factory_b, config, openings);
+                if sender.send(outcome).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        receiver.iter().collect::<Vec<MatchOutcome<G>>>()
+    });
+
+    outcomes.sort_by_key(|outcome| outcome.game);
+    outcomes
+}
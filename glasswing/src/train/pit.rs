@@ -1,8 +1,18 @@
-use crate::agents::Agent;
-use crate::core::{Game, GwState};
+use crate::agents::{Agent, PrepareInfo};
+use crate::core::{
+    Clock, Game, GameResult, GwGameResult, GwState, GwTeam, MatchError, ProgressState, SystemClock,
+};
+use crate::tablebase::{Tablebase, TbValue};
+use crate::train::{Adjudication, BudgetScaler, TurnRecord};
+use anyhow::Error;
+use cachewing::TranspositionHash;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[allow(non_snake_case)]
-pub struct Pit<G, A, B>
+pub struct Pit<G, A, B, C = SystemClock>
 where
     G: Game,
 {
@@ -10,6 +20,51 @@ where
     agentB: B,
     turn: usize,
     state: G::State,
+    clock: C,
+    agentA_time: Duration,
+    agentB_time: Duration,
+    time_charge: TimeCharge,
+    budget_scaler: Option<BudgetScaler>,
+    max_actions: Option<usize>,
+    duplicate_check: Option<fn(&G::State) -> bool>,
+    prepared: bool,
+    // the team agent A plays, known once the agents are prepared
+    first_team: Option<G::Team>,
+    harden: bool,
+    swap_rule: bool,
+    // whether agent B accepted the swap, once it has been offered
+    swapped: Option<bool>,
+    no_progress: Option<NoProgressRule<G>>,
+    tablebase: Option<TablebaseRule<G>>,
+    adjudicators: Vec<Adjudicator<G>>,
+    adjudicated: Option<(G::GameResult, Adjudication)>,
+}
+
+/// A custom stopping rule, see [Pit::with_adjudicator].
+type Adjudicator<G> =
+    Box<dyn FnMut(&<G as Game>::State, usize) -> Option<(<G as Game>::GameResult, String)> + Send>;
+
+/// Which duration is added to an agent's total time after each of its turns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeCharge {
+    /// The time measured by the pit around [Agent::select_action].
+    #[default]
+    Measured,
+    /// The time reported by the agent through [Agent::reported_time], or the
+    /// measured time if the agent does not report one.
+    Reported,
+}
+
+struct TablebaseRule<G: Game> {
+    tablebase: Arc<Tablebase<G>>,
+    probe: fn(&Tablebase<G>, &G::State) -> Option<G::GameResult>,
+}
+
+struct NoProgressRule<G: Game> {
+    limit: usize,
+    plies: usize,
+    is_progress: fn(&G::State, &G::Action, &G::State) -> bool,
+    draw: G::GameResult,
 }
 
 impl<G, A, B> Pit<G, A, B>
@@ -25,22 +80,326 @@ where
             agentB,
             turn: 0,
             state: initial,
+            clock: SystemClock::new(),
+            agentA_time: Duration::ZERO,
+            agentB_time: Duration::ZERO,
+            time_charge: TimeCharge::Measured,
+            budget_scaler: None,
+            max_actions: None,
+            duplicate_check: None,
+            prepared: false,
+            first_team: None,
+            harden: false,
+            swap_rule: false,
+            swapped: None,
+            no_progress: None,
+            tablebase: None,
+            adjudicators: Vec::new(),
+            adjudicated: None,
+        }
+    }
+}
+
+impl<G, A, B, C> Pit<G, A, B, C>
+where
+    G: Game,
+    A: Agent<G>,
+    B: Agent<G>,
+    C: Clock,
+{
+    /// Replaces the clock used to measure the agents' thinking time.
+    pub fn with_clock<D: Clock>(self, clock: D) -> Pit<G, A, B, D> {
+        Pit {
+            agentA: self.agentA,
+            agentB: self.agentB,
+            turn: self.turn,
+            state: self.state,
+            clock,
+            agentA_time: self.agentA_time,
+            agentB_time: self.agentB_time,
+            time_charge: self.time_charge,
+            budget_scaler: self.budget_scaler,
+            max_actions: self.max_actions,
+            duplicate_check: self.duplicate_check,
+            prepared: self.prepared,
+            first_team: self.first_team,
+            harden: self.harden,
+            swap_rule: self.swap_rule,
+            swapped: self.swapped,
+            no_progress: self.no_progress,
+            tablebase: self.tablebase,
+            adjudicators: self.adjudicators,
+            adjudicated: self.adjudicated,
         }
     }
 
+    /// Chooses which duration is charged to the agents' total time. Both are
+    /// recorded in every [TurnRecord] regardless.
+    pub fn with_time_charge(mut self, charge: TimeCharge) -> Self {
+        self.time_charge = charge;
+        self
+    }
+
+    /// Plays under a chess clock: before every turn, the agent to move is
+    /// told its [time budget](Agent::set_time_budget) for the move, scaled to
+    /// the time left on its clock and the estimated length of the game. The
+    /// budget is recorded in the [TurnRecord]. An agent whose charged time
+    /// exceeds the clock fails with [MatchError::TimeLimitExceeded].
+    ///
+    /// The scaler's length estimate is updated when the game ends. To keep
+    /// the estimate across a series, pass [Pit::budget_scaler] on to the
+    /// next game.
+    pub fn with_budget_scaler(mut self, scaler: BudgetScaler) -> Self {
+        self.budget_scaler = Some(scaler);
+        self
+    }
+
+    /// Fails with [MatchError::ActionPanicked] instead of panicking if the
+    /// game panics while applying an agent's action, e.g. because the agent
+    /// returned a stale action.
+    pub fn with_hardening(mut self) -> Self {
+        self.harden = true;
+        self
+    }
+
+    /// Plays with the pie rule: after the first ply, agent B is offered to
+    /// [swap](Agent::accept_swap) sides. If it accepts, agent B plays the team
+    /// that made the first move for the rest of the game and agent A plays
+    /// the other one.
+    pub fn with_swap_rule(mut self) -> Self {
+        self.swap_rule = true;
+        self
+    }
+
+    /// Adds a custom stopping rule, called with the state and the number of
+    /// plies played after every action that does not end the game. Returning
+    /// a result and a reason ends the game as
+    /// [Adjudication::Custom]. Rules are tried after the built-in ones, in
+    /// the order they were added, and the first to return a result wins.
+    pub fn with_adjudicator(
+        mut self,
+        adjudicator: impl FnMut(&G::State, usize) -> Option<(G::GameResult, String)> + Send + 'static,
+    ) -> Self {
+        self.adjudicators.push(Box::new(adjudicator));
+        self
+    }
+
+    /// Diagnostic option for buggy move generators: before every turn, fail
+    /// with [MatchError::SuspiciousMoveGeneration] if the state generates more
+    /// than `n` actions. At most `n + 1` actions are generated for the check,
+    /// so unbounded generators are caught as well.
+    pub fn with_max_actions_per_state(mut self, n: usize) -> Self {
+        self.max_actions = Some(n);
+        self
+    }
+
+    /// Advances the game by one turn. Returns `Ok(None)` once the game is over.
+    ///
+    /// Before the first turn, both agents are given the chance to
+    /// [prepare](Agent::prepare), which is not counted towards their time.
+    pub fn step(&mut self) -> Result<Option<TurnRecord<G>>, Error> {
+        if self.state.is_terminal() || self.adjudicated.is_some() {
+            return Ok(None);
+        }
+        if !self.prepared {
+            self.prepare();
+        }
+        self.check_actions()?;
+        if self.swap_rule && self.turn == 1 && self.swapped.is_none() {
+            let swapped = self.agentB.accept_swap(&self.state);
+            log::debug!("{} swap", if swapped { "Accepted" } else { "Declined" });
+            self.swapped = Some(swapped);
+        }
+
+        // agents are chosen by team rather than by ply, since in some games a
+        // team may move several times in a row
+        let plays_first = Some(self.state.team_to_move()) == self.first_team;
+        let agent_idx = (!plays_first as usize + self.is_swapped() as usize) % 2;
+        let (agent, time): (&mut dyn Agent<G>, _) = if agent_idx == 0 {
+            (&mut self.agentA, &mut self.agentA_time)
+        } else {
+            (&mut self.agentB, &mut self.agentB_time)
+        };
+        let budget = self
+            .budget_scaler
+            .as_ref()
+            .map(|scaler| scaler.budget(*time, self.turn));
+        if let Some((soft, hard)) = budget {
+            agent.set_time_budget(soft, hard);
+        }
+        let start = self.clock.now();
+        let action = agent.select_action(&self.state)?;
+        let elapsed = self.clock.elapsed_since(start);
+        let reported = agent.reported_time();
+        *time += match self.time_charge {
+            TimeCharge::Measured => elapsed,
+            TimeCharge::Reported => reported.unwrap_or(elapsed),
+        };
+        if let Some(scaler) = &self.budget_scaler {
+            if *time > scaler.time() {
+                return Err(MatchError::<G>::TimeLimitExceeded(scaler.time()).into());
+            }
+        }
+        log::debug!("Turn {}: {} selected {:?}", self.turn, agent.name(), action);
+
+        let pre = self.state.clone();
+        self.state = if self.harden {
+            panic::catch_unwind(AssertUnwindSafe(|| pre.apply_action(&action))).map_err(
+                |payload| MatchError::<G>::ActionPanicked {
+                    ply: self.turn,
+                    message: panic_message(payload.as_ref()),
+                },
+            )?
+        } else {
+            pre.apply_action(&action)
+        };
+        self.agentA.observe(&pre, &action, &self.state);
+        self.agentB.observe(&pre, &action, &self.state);
+        if let Some(rule) = self.no_progress.as_mut() {
+            if (rule.is_progress)(&pre, &action, &self.state) {
+                rule.plies = 0;
+            } else {
+                rule.plies += 1;
+                if rule.plies >= rule.limit && !self.state.is_terminal() {
+                    self.adjudicated = Some((rule.draw.clone(), Adjudication::NoProgress));
+                }
+            }
+        }
+        if let Some(rule) = self.tablebase.as_ref() {
+            if self.adjudicated.is_none() && !self.state.is_terminal() {
+                if let Some(result) = (rule.probe)(&rule.tablebase, &self.state) {
+                    self.adjudicated = Some((result, Adjudication::Tablebase));
+                }
+            }
+        }
+        for adjudicator in &mut self.adjudicators {
+            if self.adjudicated.is_some() || self.state.is_terminal() {
+                break;
+            }
+            if let Some((result, reason)) = adjudicator(&self.state, self.turn + 1) {
+                self.adjudicated = Some((result, Adjudication::Custom(reason)));
+            }
+        }
+        let record = TurnRecord {
+            ply: self.turn,
+            team: pre.team_to_move(),
+            agent: agent_idx,
+            action,
+            pre,
+            post: self.state.clone(),
+            elapsed,
+            reported,
+            budget,
+        };
+        self.turn += 1;
+        if let Some(scaler) = self.budget_scaler.as_mut() {
+            if self.state.is_terminal() || self.adjudicated.is_some() {
+                scaler.observe_game(self.turn);
+            }
+        }
+
+        Ok(Some(record))
+    }
+
+    fn prepare(&mut self) {
+        let team = self.state.team_to_move();
+        let opponent = team.opponent();
+        self.agentA.prepare(&self.state, &PrepareInfo { team });
+        self.agentB
+            .prepare(&self.state, &PrepareInfo { team: opponent });
+        self.first_team = Some(self.state.team_to_move());
+        self.prepared = true;
+    }
+
+    fn check_actions(&self) -> Result<(), Error> {
+        if let Some(max) = self.max_actions {
+            let count = self.state.actions().into_iter().take(max + 1).count();
+            if count > max {
+                return Err(MatchError::<G>::SuspiciousMoveGeneration {
+                    ply: self.turn,
+                    count,
+                }
+                .into());
+            }
+        }
+        if let Some(has_duplicates) = self.duplicate_check {
+            if has_duplicates(&self.state) {
+                return Err(MatchError::<G>::DuplicateActions { ply: self.turn }.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Plays the game until it ends.
+    ///
+    /// # Panics
+    /// Panics if an agent fails to select an action, see [Pit::try_playout].
     pub fn playout(&mut self) -> Option<G::GameResult> {
         for _ in &mut *self {}
-        self.state.game_result()
+        self.game_result()
     }
 
+    /// Plays the game until it ends or an error occurs.
+    pub fn try_playout(&mut self) -> Result<Option<G::GameResult>, Error> {
+        while self.step()?.is_some() {}
+        Ok(self.game_result())
+    }
+
+    /// The result of the game, either from its final state or adjudicated.
     pub fn game_result(&self) -> Option<G::GameResult> {
-        self.state.game_result()
+        self.adjudicated
+            .as_ref()
+            .map(|(result, _)| result.clone())
+            .or_else(|| self.state.game_result())
+    }
+
+    /// Whether the game was ended by adjudication rather than reaching a
+    /// terminal state.
+    pub fn is_adjudicated(&self) -> bool {
+        self.adjudicated.is_some()
+    }
+
+    /// Why the game was adjudicated, if it was.
+    pub fn adjudication(&self) -> Option<Adjudication> {
+        self.adjudicated.as_ref().map(|(_, reason)| reason.clone())
+    }
+
+    /// Whether agent B accepted the swap offered by [Pit::with_swap_rule].
+    pub fn is_swapped(&self) -> bool {
+        self.swapped == Some(true)
+    }
+
+    /// The team agent A plays once any swap is taken into account, or `None`
+    /// before the first turn.
+    #[allow(non_snake_case)]
+    pub fn agentA_team(&self) -> Option<G::Team> {
+        let team = self.first_team.clone()?;
+        Some(if self.is_swapped() {
+            team.opponent()
+        } else {
+            team
+        })
+    }
+
+    /// The agent that won the game, 0 for agent A and 1 for agent B as in
+    /// [TurnRecord::agent]. `None` if the game is not over or drawn.
+    pub fn winning_agent(&self) -> Option<usize> {
+        let winner = self.game_result()?.winner()?;
+        Some((Some(winner) != self.agentA_team()) as usize)
     }
 
     pub fn state(&self) -> &G::State {
         &self.state
     }
 
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn budget_scaler(&self) -> Option<&BudgetScaler> {
+        self.budget_scaler.as_ref()
+    }
+
     #[allow(non_snake_case)]
     pub fn agentA(&self) -> &A {
         &self.agentA
@@ -51,6 +410,12 @@ where
         &mut self.agentA
     }
 
+    /// Total time charged to agent A so far, see [Pit::with_time_charge].
+    #[allow(non_snake_case)]
+    pub fn agentA_time(&self) -> Duration {
+        self.agentA_time
+    }
+
     #[allow(non_snake_case)]
     pub fn agentB(&self) -> &B {
         &self.agentB
@@ -60,33 +425,441 @@ where
     pub fn agentB_mut(&mut self) -> &mut B {
         &mut self.agentB
     }
+
+    /// Total time charged to agent B so far, see [Pit::with_time_charge].
+    #[allow(non_snake_case)]
+    pub fn agentB_time(&self) -> Duration {
+        self.agentB_time
+    }
+}
+
+impl<G, A, B, C> Pit<G, A, B, C>
+where
+    G: Game,
+    G::Action: PartialEq,
+    A: Agent<G>,
+    B: Agent<G>,
+    C: Clock,
+{
+    /// Diagnostic option for buggy move generators: before every turn, fail
+    /// with [MatchError::DuplicateActions] if the state generates the same
+    /// action twice. This is quadratic in the number of actions.
+    pub fn with_duplicate_action_check(mut self) -> Self {
+        self.duplicate_check = Some(has_duplicate_actions::<G>);
+        self
+    }
+}
+
+impl<G, A, B, C> Pit<G, A, B, C>
+where
+    G: Game<GameResult = GameResult<<G as Game>::Team>>,
+    G::State: ProgressState<G>,
+    A: Agent<G>,
+    B: Agent<G>,
+    C: Clock,
+{
+    /// Adjudicates the game as a draw once `n` consecutive plies have been
+    /// played without [progress](ProgressState::is_progress).
+    pub fn with_draw_after_no_progress(mut self, n: usize) -> Self {
+        self.no_progress = Some(NoProgressRule {
+            limit: n,
+            plies: 0,
+            is_progress: <G::State as ProgressState<G>>::is_progress,
+            draw: GameResult::Draw,
+        });
+        self
+    }
+}
+
+impl<G, A, B, C> Pit<G, A, B, C>
+where
+    G: Game<GameResult = GameResult<<G as Game>::Team>>,
+    G::State: TranspositionHash,
+    A: Agent<G>,
+    B: Agent<G>,
+    C: Clock,
+{
+    /// Adjudicates the game with the proven result as soon as a position
+    /// after an action is found in the tablebase.
+    pub fn with_tablebase_adjudication(mut self, tablebase: Arc<Tablebase<G>>) -> Self {
+        self.tablebase = Some(TablebaseRule {
+            tablebase,
+            probe: probe_result::<G>,
+        });
+        self
+    }
+}
+
+fn probe_result<G>(tablebase: &Tablebase<G>, state: &G::State) -> Option<GameResult<G::Team>>
+where
+    G: Game<GameResult = GameResult<<G as Game>::Team>>,
+    G::State: TranspositionHash,
+{
+    let team = state.team_to_move();
+    tablebase.probe(state).map(|value| match value {
+        TbValue::Win(_) => GameResult::Win(team),
+        TbValue::Loss(_) => GameResult::Win(team.opponent()),
+        TbValue::Draw => GameResult::Draw,
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+fn has_duplicate_actions<G>(state: &G::State) -> bool
+where
+    G: Game,
+    G::Action: PartialEq,
+{
+    let actions = state.actions().into_iter().collect::<Vec<G::Action>>();
+    actions
+        .iter()
+        .enumerate()
+        .any(|(i, action)| actions[i + 1..].contains(action))
 }
 
-impl<G, A, B> Iterator for Pit<G, A, B>
+impl<G, A, B, C> Iterator for Pit<G, A, B, C>
 where
     G: Game,
     A: Agent<G>,
     B: Agent<G>,
+    C: Clock,
 {
-    /// (previous state, action, post state)
-    type Item = (G::State, G::Action, G::State);
+    type Item = TurnRecord<G>;
 
+    /// # Panics
+    /// Panics if an agent fails to select an action, see [Pit::step].
     fn next(&mut self) -> Option<Self::Item> {
-        if self.state.is_terminal() {
-            return None;
+        self.step().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::functional_agent::FunctionalAgent;
+    use crate::agents::{MaximisingAgent, SimpleAgent};
+    use crate::core::{ManualClock, Team};
+    use crate::testing::broken::{BrokenGame, BrokenState};
+    use crate::testing::counting::{CountingEvaluator, CountingGame, CountingState};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn broken_pit(
+        state: BrokenState,
+    ) -> Pit<BrokenGame, SimpleAgent<BrokenGame>, SimpleAgent<BrokenGame>> {
+        Pit::new(SimpleAgent::new(), SimpleAgent::new(), state)
+    }
+
+    /// A pit from `count` in which both agents play the next of `actions`.
+    fn scripted_pit(
+        count: u32,
+        actions: Vec<u32>,
+    ) -> Pit<CountingGame, impl Agent<CountingGame>, impl Agent<CountingGame>> {
+        let script = Rc::new(RefCell::new(actions.into_iter()));
+        let agent = |script: Rc<RefCell<std::vec::IntoIter<u32>>>| {
+            FunctionalAgent::new(move |_: &CountingState| {
+                Ok(script.borrow_mut().next().expect("script exhausted"))
+            })
+        };
+        Pit::new(
+            agent(script.clone()),
+            agent(script),
+            CountingState::new(count, Team::One),
+        )
+    }
+
+    #[test]
+    fn no_progress_draw_fires_after_exactly_n_plies() {
+        let mut pit = scripted_pit(0, vec![1; 8]).with_draw_after_no_progress(4);
+        for ply in 0..3 {
+            pit.step().unwrap().unwrap();
+            assert!(!pit.is_adjudicated(), "ply {}", ply);
         }
+        pit.step().unwrap().unwrap();
+        assert_eq!(pit.adjudication(), Some(Adjudication::NoProgress));
+        assert_eq!(pit.game_result(), Some(GameResult::Draw));
+        assert!(pit.step().unwrap().is_none());
+        assert_eq!(pit.state().count, 4);
+    }
 
-        let agent: &mut dyn Agent<G> = if self.turn % 2 == 0 {
-            &mut self.agentA
-        } else {
-            &mut self.agentB
+    #[test]
+    fn progress_resets_the_no_progress_count() {
+        let mut pit = scripted_pit(0, vec![1, 1, 1, 3, 1, 1, 1, 3, 1, 1, 1, 1])
+            .with_draw_after_no_progress(4);
+        let plies = pit.by_ref().count();
+        assert_eq!(plies, 12);
+        assert_eq!(pit.adjudication(), Some(Adjudication::NoProgress));
+        assert_eq!(pit.state().count, 16);
+    }
+
+    #[test]
+    fn no_progress_draw_does_not_override_a_result() {
+        // the fourth ply without progress reaches the target
+        let mut pit = scripted_pit(17, vec![1; 4]).with_draw_after_no_progress(4);
+        assert_eq!(pit.playout(), Some(GameResult::Win(Team::Two)));
+        assert!(!pit.is_adjudicated());
+    }
+
+    /// Always takes over the first mover's team when offered.
+    struct Swapper<A>(A);
+
+    impl<A: Agent<CountingGame>> Agent<CountingGame> for Swapper<A> {
+        fn select_action(&mut self, state: &CountingState) -> Result<u32, Error> {
+            self.0.select_action(state)
+        }
+
+        fn accept_swap(&mut self, _state: &CountingState) -> bool {
+            true
+        }
+    }
+
+    fn perfect() -> MaximisingAgent<CountingGame, CountingEvaluator> {
+        MaximisingAgent::new(CountingEvaluator { perfect: true })
+    }
+
+    #[test]
+    fn swapping_agent_is_credited_with_the_first_team() {
+        // the first mover wins with perfect play, by leaving 20 after its
+        // first move
+        let mut pit =
+            Pit::new(perfect(), Swapper(perfect()), CountingGame::initial_state()).with_swap_rule();
+        assert_eq!(pit.agentA_team(), None);
+        let records = pit.by_ref().collect::<Vec<_>>();
+
+        assert!(pit.is_swapped());
+        assert_eq!(pit.agentA_team(), Some(Team::Two));
+        assert_eq!(pit.game_result(), Some(GameResult::Win(Team::One)));
+        assert_eq!(pit.winning_agent(), Some(1));
+        assert_eq!(records[0].agent, 0);
+        for record in &records[1..] {
+            let expected = if record.team == Team::One { 1 } else { 0 };
+            assert_eq!(record.agent, expected, "ply {}", record.ply);
+        }
+    }
+
+    #[test]
+    fn declined_swap_keeps_the_teams() {
+        let mut pit =
+            Pit::new(perfect(), perfect(), CountingGame::initial_state()).with_swap_rule();
+        let records = pit.by_ref().collect::<Vec<_>>();
+
+        assert!(!pit.is_swapped());
+        assert_eq!(pit.agentA_team(), Some(Team::One));
+        assert_eq!(pit.winning_agent(), Some(0));
+        for record in &records {
+            assert_eq!(record.agent, (record.team == Team::Two) as usize);
+        }
+    }
+
+    /// A pit in which agent A plays 1 until its `n`-th turn, where it plays 2.
+    fn panicking_pit(n: usize) -> Pit<BrokenGame, impl Agent<BrokenGame>, SimpleAgent<BrokenGame>> {
+        let mut turns = 0;
+        let agent = FunctionalAgent::new(move |_: &BrokenState| {
+            turns += 1;
+            Ok(if turns == n { 2 } else { 1 })
+        });
+        Pit::new(agent, SimpleAgent::new(), BrokenGame::initial_state())
+    }
+
+    #[test]
+    fn hardened_pit_reports_panicking_actions() {
+        let mut pit = panicking_pit(3).with_hardening();
+        pit.step().unwrap().unwrap();
+        pit.step().unwrap().unwrap();
+        let err = pit.step().unwrap_err();
+        match err.downcast_ref::<MatchError<BrokenGame>>() {
+            Some(MatchError::ActionPanicked { ply, message }) => {
+                assert_eq!(*ply, 2);
+                assert!(message.contains("cannot apply action 2"), "{}", message);
+            }
+            _ => panic!("unexpected error {:#}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot apply action 2")]
+    fn unhardened_pit_propagates_panics() {
+        let mut pit = panicking_pit(1);
+        let _ = pit.step();
+    }
+
+    /// Plays the first action and logs every call it receives.
+    #[derive(Default)]
+    struct Observer {
+        log: Vec<String>,
+    }
+
+    impl Agent<CountingGame> for Observer {
+        fn select_action(&mut self, state: &CountingState) -> Result<u32, Error> {
+            SimpleAgent::<CountingGame>::new().select_action(state)
+        }
+
+        fn prepare(&mut self, initial: &CountingState, info: &PrepareInfo<CountingGame>) {
+            self.log
+                .push(format!("prepare {} as {:?}", initial.count, info.team));
+        }
+
+        fn observe(&mut self, prev: &CountingState, action: &u32, next: &CountingState) {
+            self.log
+                .push(format!("{} + {} = {}", prev.count, action, next.count));
+        }
+    }
+
+    #[test]
+    fn every_ply_is_observed_once_in_order() {
+        let mut pit = Pit::new(
+            Observer::default(),
+            Observer::default(),
+            CountingState::new(10, Team::Two),
+        );
+        let records = pit.by_ref().collect::<Vec<_>>();
+        assert_eq!(records.len(), 11);
+        assert!(records
+            .iter()
+            .enumerate()
+            .all(|(i, record)| record.ply == i));
+
+        let plies = records
+            .iter()
+            .map(|record| {
+                format!(
+                    "{} + {} = {}",
+                    record.pre.count, record.action, record.post.count
+                )
+            })
+            .collect::<Vec<String>>();
+        for (agent, team) in [
+            (&pit.agentA().log, Team::Two),
+            (&pit.agentB().log, Team::One),
+        ] {
+            assert_eq!(agent[0], format!("prepare 10 as {:?}", team));
+            assert_eq!(agent[1..], plies[..]);
+        }
+    }
+
+    /// Plays the first action, advancing a manual clock while it prepares
+    /// and thinks.
+    struct Timed {
+        clock: ManualClock,
+        prepare: Duration,
+        think: Duration,
+        report: Option<Duration>,
+    }
+
+    impl Timed {
+        fn new(clock: &ManualClock, think: u64) -> Self {
+            Timed {
+                clock: clock.clone(),
+                prepare: Duration::ZERO,
+                think: Duration::from_secs(think),
+                report: None,
+            }
+        }
+    }
+
+    impl Agent<CountingGame> for Timed {
+        fn select_action(&mut self, state: &CountingState) -> Result<u32, Error> {
+            self.clock.advance(self.think);
+            SimpleAgent::<CountingGame>::new().select_action(state)
+        }
+
+        fn prepare(&mut self, _initial: &CountingState, _info: &PrepareInfo<CountingGame>) {
+            self.clock.advance(self.prepare);
+        }
+
+        fn reported_time(&self) -> Option<Duration> {
+            self.report
+        }
+    }
+
+    #[test]
+    fn preparation_is_not_charged_to_the_first_move() {
+        let clock = ManualClock::new();
+        let mut slow = Timed::new(&clock, 1);
+        slow.prepare = Duration::from_secs(60);
+        let mut pit = Pit::new(slow, Timed::new(&clock, 2), CountingGame::initial_state())
+            .with_clock(clock.clone());
+
+        let first = pit.step().unwrap().unwrap();
+        assert_eq!(first.elapsed, Duration::from_secs(1));
+        assert_eq!(pit.agentA_time(), Duration::from_secs(1));
+        assert_eq!(clock.now(), Duration::from_secs(61));
+
+        let second = pit.step().unwrap().unwrap();
+        assert_eq!(second.elapsed, Duration::from_secs(2));
+        assert_eq!(pit.agentB_time(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn time_charge_selects_the_accumulated_time() {
+        let play = |charge: TimeCharge| {
+            let clock = ManualClock::new();
+            let mut reporting = Timed::new(&clock, 3);
+            reporting.report = Some(Duration::from_secs(1));
+            let mut pit = Pit::new(
+                reporting,
+                Timed::new(&clock, 2),
+                CountingGame::initial_state(),
+            )
+            .with_clock(clock)
+            .with_time_charge(charge);
+            let records = pit.by_ref().collect::<Vec<_>>();
+            for record in &records {
+                let (elapsed, reported) = if record.agent == 0 {
+                    (3, Some(Duration::from_secs(1)))
+                } else {
+                    (2, None)
+                };
+                assert_eq!(record.elapsed, Duration::from_secs(elapsed));
+                assert_eq!(record.reported, reported);
+            }
+            let turns = records.iter().filter(|record| record.agent == 0).count() as u32;
+            (turns, pit.agentA_time(), pit.agentB_time())
         };
-        let action = agent.select_action(&self.state).unwrap();
 
-        let pre = self.state.clone();
-        self.state = self.state.apply_action(&action);
-        self.turn += 1;
+        let (turns, measured_a, measured_b) = play(TimeCharge::Measured);
+        assert_eq!(measured_a, Duration::from_secs(3) * turns);
+        let (_, reported_a, reported_b) = play(TimeCharge::Reported);
+        assert_eq!(reported_a, Duration::from_secs(1) * turns);
+        // agents that report nothing are charged the measured time
+        assert_eq!(reported_b, measured_b);
+    }
 
-        Some((pre, action, self.state.clone()))
+    #[test]
+    fn unbounded_move_generation_is_reported() {
+        let mut pit = broken_pit(BrokenState { unbounded: true }).with_max_actions_per_state(16);
+        let err = pit.step().unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<MatchError<BrokenGame>>(),
+            Some(MatchError::SuspiciousMoveGeneration { ply: 0, count: 17 })
+        ));
+    }
+
+    #[test]
+    fn duplicate_actions_are_reported() {
+        let mut pit = broken_pit(BrokenGame::initial_state())
+            .with_max_actions_per_state(16)
+            .with_duplicate_action_check();
+        let err = pit.step().unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<MatchError<BrokenGame>>(),
+            Some(MatchError::DuplicateActions { ply: 0 })
+        ));
+    }
+
+    #[test]
+    fn unchecked_broken_games_are_played() {
+        let mut pit = broken_pit(BrokenGame::initial_state());
+        for ply in 0..3 {
+            assert_eq!(pit.step().unwrap().unwrap().ply, ply);
+        }
     }
 }
@@ -0,0 +1,189 @@
+use crate::agents::Agent;
+use crate::core::{Game, GwGameResult};
+use crate::train::outcome::play_series_game;
+use crate::train::{MatchConfig, MatchOutcome, Odds, OpeningSuite, Termination};
+use std::marker::PhantomData;
+
+/// Plays a series of games between agents built by two factories, with
+/// colors alternating between games. With the `threads` feature, games can
+/// also be played in parallel, see `MatchSeries::run_parallel`.
+pub struct MatchSeries<G: Game, FA, FB> {
+    factory_a: FA,
+    factory_b: FB,
+    games: usize,
+    config: MatchConfig,
+    openings: Option<OpeningSuite<G>>,
+    _game: PhantomData<G>,
+}
+
+impl<G, A, B, FA, FB> MatchSeries<G, FA, FB>
+where
+    G: Game,
+    A: Agent<G>,
+    B: Agent<G>,
+    FA: Fn(u64) -> A,
+    FB: Fn(u64) -> B,
+{
+    pub fn new(factory_a: FA, factory_b: FB, games: usize) -> Self {
+        MatchSeries {
+            factory_a,
+            factory_b,
+            games,
+            config: MatchConfig::default(),
+            openings: None,
+            _game: PhantomData,
+        }
+    }
+
+    pub fn with_config(mut self, config: MatchConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_seed(mut self, base_seed: u64) -> Self {
+        self.config.base_seed = base_seed;
+        self
+    }
+
+    /// Gives the agents from `factory_a` and `factory_b` different budgets,
+    /// e.g. to measure how much time or how many nodes a weaker agent needs
+    /// to match a stronger one, see [find_equalizing_budget](crate::train::find_equalizing_budget).
+    pub fn with_odds(mut self, odds: Odds) -> Self {
+        self.config.odds = Some(odds);
+        self
+    }
+
+    /// Starts the games from the positions of the suite instead of the
+    /// initial state, see [OpeningSuite::opening_for]. To play every opening
+    /// with both colors, the number of games should be a multiple of twice
+    /// the size of the suite.
+    ///
+    /// # Panics
+    /// Panics if the suite is empty.
+    pub fn with_openings(mut self, openings: OpeningSuite<G>) -> Self {
+        assert!(!openings.is_empty(), "opening suite must not be empty");
+        self.openings = Some(openings);
+        self
+    }
+
+    /// Plays all games one after another.
+    pub fn run(&self) -> SeriesResult<G> {
+        let outcomes = (0..self.games)
+            .map(|game| {
+                play_series_game(
+                    game,
+                    &self.factory_a,
+                    &self.factory_b,
+                    &self.config,
+                    self.openings.as_ref(),
+                )
+            })
+            .collect();
+        SeriesResult::new(outcomes)
+    }
+}
+
+#[cfg(feature = "threads")]
+impl<G, A, B, FA, FB> MatchSeries<G, FA, FB>
+where
+    G: Game,
+    G::Action: Send,
+    G::Team: Send,
+    G::GameResult: Send,
+    A: Agent<G>,
+    B: Agent<G>,
+    FA: Fn(u64) -> A + Sync,
+    FB: Fn(u64) -> B + Sync,
+{
+    /// Plays all games on a pool of threads, with the same results as
+    /// [MatchSeries::run].
+    pub fn run_parallel(&self) -> SeriesResult<G> {
+        SeriesResult::new(crate::train::parallel::run_parallel(
+            &self.factory_a,
+            &self.factory_b,
+            self.games,
+            &self.config,
+            self.openings.as_ref(),
+        ))
+    }
+}
+
+/// Wins, draws and losses from the point of view of one agent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Wdl {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl Wdl {
+    pub fn total(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// The average score, counting a draw as half a win.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.total().max(1) as f64
+    }
+}
+
+/// The aggregated results of a [MatchSeries], from the point of view of the
+/// agent built by `factory_a`. Games that did not finish are counted as draws.
+#[derive(Debug, Clone)]
+pub struct SeriesResult<G: Game> {
+    /// Results of the games in which agent A moved first.
+    pub as_first: Wdl,
+    /// Results of the games in which agent A moved second.
+    pub as_second: Wdl,
+    /// Number of games aborted by an error.
+    pub errors: usize,
+    /// Number of games stopped by the ply limit.
+    pub unfinished: usize,
+    pub outcomes: Vec<MatchOutcome<G>>,
+}
+
+impl<G: Game> SeriesResult<G> {
+    pub fn new(outcomes: Vec<MatchOutcome<G>>) -> Self {
+        let mut result = SeriesResult {
+            as_first: Wdl::default(),
+            as_second: Wdl::default(),
+            errors: 0,
+            unfinished: 0,
+            outcomes: Vec::new(),
+        };
+        for outcome in &outcomes {
+            let wdl = if outcome.swapped {
+                &mut result.as_second
+            } else {
+                &mut result.as_first
+            };
+            match outcome.result.as_ref().and_then(|result| result.winner()) {
+                None => wdl.draws += 1,
+                Some(winner) if (winner == outcome.first) != outcome.swapped => wdl.wins += 1,
+                Some(_) => wdl.losses += 1,
+            }
+            match outcome.termination {
+                Termination::Natural | Termination::Adjudicated(_) => {}
+                Termination::MaxPlies => result.unfinished += 1,
+                Termination::Error(_) => result.errors += 1,
+            }
+        }
+        result.outcomes = outcomes;
+        result
+    }
+
+    /// Combined results of agent A over both colors.
+    pub fn total(&self) -> Wdl {
+        Wdl {
+            wins: self.as_first.wins + self.as_second.wins,
+            draws: self.as_first.draws + self.as_second.draws,
+            losses: self.as_first.losses + self.as_second.losses,
+        }
+    }
+
+    /// Average number of plies per game.
+    pub fn average_plies(&self) -> f64 {
+        let plies = self.outcomes.iter().map(|o| o.plies).sum::<usize>();
+        plies as f64 / self.outcomes.len().max(1) as f64
+    }
+}
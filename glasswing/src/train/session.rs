@@ -0,0 +1,360 @@
+use crate::agents::{Agent, Evaluator, PrepareInfo};
+use crate::core::{Game, GwState, GwTeam};
+use anyhow::Error;
+use std::fmt::{Debug, Display};
+use std::fs;
+use std::io::{BufRead, Write};
+
+/// How an [InteractiveSession] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEnd<G: Game> {
+    /// The game reached a terminal state.
+    Finished(G::GameResult),
+    /// The human resigned.
+    Resigned,
+    /// The input ended before the game did.
+    Quit,
+}
+
+/// Plays a game between a human and an agent on a text stream, for
+/// debugging agents by hand.
+///
+/// Besides the index of an action, the human can enter `undo` to take back
+/// their last move and the agent's reply, `hint` to ask the
+/// [hint agent](InteractiveSession::with_hint_agent) for a move, `eval` to
+/// score the position with the
+/// [evaluator](InteractiveSession::with_evaluator), `save <path>` to write
+/// the actions played so far to a file, one per line, and `resign`.
+///
+/// The opponent is [prepared](Agent::prepare) before its first move and
+/// [observes](Agent::observe) every action, as in a
+/// [Pit](crate::train::Pit). After an undo it is prepared again and replays
+/// the remaining actions, so that it can resynchronise.
+pub struct InteractiveSession<G: Game, A, I, W> {
+    opponent: A,
+    human: G::Team,
+    states: Vec<G::State>,
+    // the action leading to each state but the first
+    actions: Vec<G::Action>,
+    prepared: bool,
+    hint: Option<Box<dyn Agent<G>>>,
+    evaluator: Option<Box<dyn Evaluator<G>>>,
+    input: I,
+    output: W,
+}
+
+impl<G, A, I, W> InteractiveSession<G, A, I, W>
+where
+    G: Game,
+    G::State: Display,
+    G::Action: Display,
+    G::EvalType: Debug,
+    A: Agent<G>,
+    I: BufRead,
+    W: Write,
+{
+    /// A session starting from `initial`, in which the human plays `human`.
+    pub fn new(opponent: A, human: G::Team, initial: G::State, input: I, output: W) -> Self {
+        InteractiveSession {
+            opponent,
+            human,
+            states: vec![initial],
+            actions: Vec::new(),
+            prepared: false,
+            hint: None,
+            evaluator: None,
+            input,
+            output,
+        }
+    }
+
+    pub fn with_hint_agent(mut self, agent: impl Agent<G> + 'static) -> Self {
+        self.hint = Some(Box::new(agent));
+        self
+    }
+
+    pub fn with_evaluator(mut self, evaluator: impl Evaluator<G> + 'static) -> Self {
+        self.evaluator = Some(Box::new(evaluator));
+        self
+    }
+
+    /// The current position.
+    pub fn state(&self) -> &G::State {
+        self.states.last().unwrap()
+    }
+
+    /// Every position of the game so far, starting with the initial one.
+    pub fn states(&self) -> &[G::State] {
+        &self.states
+    }
+
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    /// Plays until the game ends, the human resigns or the input ends.
+    pub fn run(&mut self) -> Result<SessionEnd<G>, Error> {
+        if !self.prepared {
+            self.prepare();
+        }
+        loop {
+            let state = self.state().clone();
+            if let Some(result) = state.game_result() {
+                writeln!(self.output, "{}", state)?;
+                return Ok(SessionEnd::Finished(result));
+            }
+            if state.team_to_move() != self.human {
+                let action = self.opponent.select_action(&state)?;
+                writeln!(self.output, "{} plays {}", self.opponent.name(), action)?;
+                self.play(&state, action);
+                continue;
+            }
+
+            let actions = state.actions().into_iter().collect::<Vec<_>>();
+            writeln!(self.output, "{}", state)?;
+            for (i, action) in actions.iter().enumerate() {
+                writeln!(self.output, "({}): {}", i, action)?;
+            }
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(SessionEnd::Quit);
+            }
+            match line.trim() {
+                "undo" => self.undo()?,
+                "hint" => match self.hint.as_mut() {
+                    Some(agent) => {
+                        let action = agent.select_action(&state)?;
+                        writeln!(self.output, "hint: {}", action)?;
+                    }
+                    None => writeln!(self.output, "No hint agent configured.")?,
+                },
+                "eval" => match self.evaluator.as_mut() {
+                    Some(evaluator) => {
+                        let eval = evaluator.evaluate_for(&state, &self.human);
+                        writeln!(self.output, "eval: {:?}", eval)?;
+                    }
+                    None => writeln!(self.output, "No evaluator configured.")?,
+                },
+                "resign" => return Ok(SessionEnd::Resigned),
+                input if input.split_whitespace().next() == Some("save") => {
+                    self.save(input["save".len()..].trim())?
+                }
+                input => match input.parse::<usize>().ok().and_then(|i| actions.get(i)) {
+                    Some(action) => self.play(&state, action.clone()),
+                    None if actions.is_empty() => writeln!(self.output, "Enter a command.")?,
+                    None => writeln!(
+                        self.output,
+                        "Enter a command or a valid index between 0 and {}.",
+                        actions.len() - 1
+                    )?,
+                },
+            }
+        }
+    }
+
+    /// Rewinds to the human's previous turn, taking back the agent's replies.
+    /// If the agent moved first, it may rewind to the initial position, where
+    /// the agent moves again.
+    fn undo(&mut self) -> Result<(), Error> {
+        let before = self.states.len();
+        while self.states.len() > 1 {
+            self.states.pop();
+            self.actions.pop();
+            if self.state().team_to_move() == self.human {
+                break;
+            }
+        }
+        writeln!(
+            self.output,
+            "Took back {} plies.",
+            before - self.states.len()
+        )?;
+        if self.states.len() < before {
+            self.replay();
+        }
+        Ok(())
+    }
+
+    fn save(&mut self, path: &str) -> Result<(), Error> {
+        if path.is_empty() {
+            writeln!(self.output, "Enter a path to save to.")?;
+            return Ok(());
+        }
+        let contents = self
+            .actions
+            .iter()
+            .map(|action| action.to_string())
+            .collect::<Vec<String>>()
+            .join("\n");
+        match fs::write(path, contents) {
+            Ok(()) => writeln!(
+                self.output,
+                "Saved {} plies to {}.",
+                self.actions.len(),
+                path
+            )?,
+            Err(err) => writeln!(self.output, "Could not save to {}: {}", path, err)?,
+        }
+        Ok(())
+    }
+
+    /// Applies `action` to `state`, the current position, and lets the
+    /// opponent observe it.
+    fn play(&mut self, state: &G::State, action: G::Action) {
+        let next = state.apply_action(&action);
+        self.opponent.observe(state, &action, &next);
+        self.states.push(next);
+        self.actions.push(action);
+    }
+
+    fn prepare(&mut self) {
+        let info = PrepareInfo {
+            team: self.human.opponent(),
+        };
+        self.opponent.prepare(&self.states[0], &info);
+        self.prepared = true;
+    }
+
+    /// Prepares the opponent again and lets it observe the actions that are
+    /// left, after some were taken back.
+    fn replay(&mut self) {
+        self.prepare();
+        for (i, action) in self.actions.iter().enumerate() {
+            self.opponent
+                .observe(&self.states[i], action, &self.states[i + 1]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{MaximisingAgent, NegaMax, SimpleAgent};
+    use crate::core::Team;
+    use crate::testing::counting::{CountingEvaluator, CountingGame, CountingState};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Plays the first action and records what it is told.
+    #[derive(Default)]
+    struct Recorder {
+        prepared: usize,
+        observed: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl Agent<CountingGame> for Recorder {
+        fn select_action(&mut self, state: &CountingState) -> Result<u32, Error> {
+            SimpleAgent::<CountingGame>::new().select_action(state)
+        }
+
+        fn prepare(&mut self, _initial: &CountingState, _info: &PrepareInfo<CountingGame>) {
+            self.prepared += 1;
+            self.observed.borrow_mut().clear();
+        }
+
+        fn observe(&mut self, _before: &CountingState, action: &u32, _after: &CountingState) {
+            self.observed.borrow_mut().push(*action);
+        }
+    }
+
+    fn session(
+        opponent: Recorder,
+        input: &str,
+    ) -> InteractiveSession<CountingGame, Recorder, &[u8], Vec<u8>> {
+        InteractiveSession::new(
+            opponent,
+            Team::One,
+            CountingState::new(0, Team::One),
+            input.as_bytes(),
+            Vec::new(),
+        )
+    }
+
+    fn counts(session: &InteractiveSession<CountingGame, Recorder, &[u8], Vec<u8>>) -> Vec<u32> {
+        session.states().iter().map(|state| state.count).collect()
+    }
+
+    #[test]
+    fn undo_takes_back_the_reply_too() {
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let opponent = Recorder {
+            prepared: 0,
+            observed: observed.clone(),
+        };
+        // plays 3 and 2, each answered by 1, then takes back 2 and its reply
+        let mut session = session(opponent, "2\n1\nundo\n");
+        assert!(matches!(session.run().unwrap(), SessionEnd::Quit));
+        assert_eq!(counts(&session), vec![0, 3, 4]);
+        assert_eq!(*observed.borrow(), vec![3, 1]);
+        assert_eq!(session.opponent.prepared, 2);
+
+        let output = String::from_utf8(session.into_output()).unwrap();
+        assert!(output.contains("Took back 2 plies."));
+    }
+
+    #[test]
+    fn undo_at_the_start_takes_back_nothing() {
+        let mut session = session(Recorder::default(), "undo\n0\nundo\nundo\n");
+        assert!(matches!(session.run().unwrap(), SessionEnd::Quit));
+        assert_eq!(counts(&session), vec![0]);
+        assert_eq!(session.opponent.prepared, 2);
+
+        let output = String::from_utf8(session.into_output()).unwrap();
+        let undos = output
+            .lines()
+            .filter(|line| line.starts_with("Took back"))
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            undos,
+            vec![
+                "Took back 0 plies.",
+                "Took back 2 plies.",
+                "Took back 0 plies."
+            ]
+        );
+    }
+
+    #[test]
+    fn hint_does_not_move() {
+        let hint = MaximisingAgent::new(NegaMax::new(4, CountingEvaluator { perfect: true }));
+        // leaves 21 - 1 = 20, a multiple of 4, to the opponent
+        let mut session = session(Recorder::default(), "hint\nhint\n").with_hint_agent(hint);
+        assert!(matches!(session.run().unwrap(), SessionEnd::Quit));
+        assert_eq!(counts(&session), vec![0]);
+
+        let output = String::from_utf8(session.into_output()).unwrap();
+        let hints = output
+            .lines()
+            .filter(|line| line.starts_with("hint"))
+            .collect::<Vec<&str>>();
+        assert_eq!(hints, vec!["hint: 1", "hint: 1"]);
+    }
+
+    #[test]
+    fn commands_without_a_helper_are_reported() {
+        let mut session = session(Recorder::default(), "hint\neval\nsave\n9\nresign\n");
+        assert!(matches!(session.run().unwrap(), SessionEnd::Resigned));
+        assert_eq!(counts(&session), vec![0]);
+
+        let output = String::from_utf8(session.into_output()).unwrap();
+        assert!(output.contains("No hint agent configured."));
+        assert!(output.contains("No evaluator configured."));
+        assert!(output.contains("Enter a path to save to."));
+        assert!(output.contains("Enter a command or a valid index between 0 and 2."));
+    }
+
+    #[test]
+    fn save_writes_the_actions_played() {
+        let path = std::env::temp_dir().join(format!("session-{}.txt", std::process::id()));
+        let input = format!("2\n0\nsave {}\n", path.display());
+        let mut session = session(Recorder::default(), &input);
+        assert!(matches!(session.run().unwrap(), SessionEnd::Quit));
+        assert_eq!(counts(&session), vec![0, 3, 4, 5, 6]);
+
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved, "3\n1\n1\n1");
+        let output = String::from_utf8(session.into_output()).unwrap();
+        assert!(output.contains("Saved 4 plies to"));
+    }
+}
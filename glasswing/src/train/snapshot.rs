@@ -0,0 +1,88 @@
+use crate::core::Game;
+use crate::train::TurnRecord;
+use std::time::Duration;
+
+/// A position recorded by a [SnapshotRecorder].
+#[derive(Debug, Clone)]
+pub struct Snapshot<G: Game> {
+    /// Number of plies played before this position.
+    pub ply: usize,
+    pub state: G::State,
+    /// Total time agent A and agent B had spent selecting actions.
+    pub times: [Duration; 2],
+}
+
+/// Keeps periodic snapshots of a game instead of its full history. Feed it
+/// every [TurnRecord] of a [Pit](crate::train::Pit), then call
+/// [SnapshotRecorder::finish] with the final position, which is always
+/// recorded.
+#[derive(Debug, Clone)]
+pub struct SnapshotRecorder<G: Game> {
+    interval: Option<usize>,
+    plies: Vec<usize>,
+    times: [Duration; 2],
+    snapshots: Vec<Snapshot<G>>,
+}
+
+impl<G: Game> SnapshotRecorder<G> {
+    /// A recorder that only keeps the final position.
+    pub fn new() -> Self {
+        SnapshotRecorder {
+            interval: None,
+            plies: Vec::new(),
+            times: [Duration::ZERO; 2],
+            snapshots: Vec::new(),
+        }
+    }
+
+    /// Records the position after every `n` plies.
+    pub fn with_interval(mut self, n: usize) -> Self {
+        self.interval = Some(n.max(1));
+        self
+    }
+
+    /// Records the positions after the given numbers of plies.
+    pub fn with_plies(mut self, plies: impl IntoIterator<Item = usize>) -> Self {
+        self.plies.extend(plies);
+        self
+    }
+
+    pub fn observe(&mut self, turn: &TurnRecord<G>) {
+        self.times[turn.agent] += turn.elapsed;
+        let ply = turn.ply + 1;
+        let periodic = self.interval.is_some_and(|n| ply.is_multiple_of(n));
+        if periodic || self.plies.contains(&ply) {
+            self.push(ply, &turn.post);
+        }
+    }
+
+    /// Records the final position after `plies` plies, unless it has just
+    /// been recorded.
+    pub fn finish(&mut self, state: &G::State, plies: usize) {
+        if self.snapshots.last().map(|snapshot| snapshot.ply) != Some(plies) {
+            self.push(plies, state);
+        }
+    }
+
+    fn push(&mut self, ply: usize, state: &G::State) {
+        self.snapshots.push(Snapshot {
+            ply,
+            state: state.clone(),
+            times: self.times,
+        });
+    }
+
+    pub fn snapshots(&self) -> &[Snapshot<G>] {
+        &self.snapshots
+    }
+
+    pub fn into_snapshots(self) -> Vec<Snapshot<G>> {
+        self.snapshots
+    }
+}
+
+impl<G: Game> Default for SnapshotRecorder<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
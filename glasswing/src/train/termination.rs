@@ -0,0 +1,97 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Why a game stopped.
+///
+/// The [Display](fmt::Display) and [FromStr] implementations use stable
+/// strings (`natural`, `adjudicated: <reason>`, `max_plies` and
+/// `error: <message>`), so that other tools can parse them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Termination {
+    /// The game reached a terminal state.
+    Natural,
+    /// The result was decided before the game reached a terminal state.
+    Adjudicated(Adjudication),
+    /// The game was stopped by a ply limit.
+    MaxPlies,
+    /// The game was aborted by an error, e.g. an agent failing to select an action.
+    Error(String),
+}
+
+/// Why a game was adjudicated, see [Pit::adjudication](crate::train::Pit::adjudication).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Adjudication {
+    /// Too many plies without progress, see
+    /// [Pit::with_draw_after_no_progress](crate::train::Pit::with_draw_after_no_progress).
+    NoProgress,
+    /// The position was found in a tablebase, see
+    /// [Pit::with_tablebase_adjudication](crate::train::Pit::with_tablebase_adjudication).
+    Tablebase,
+    /// A custom rule with the given reason, see
+    /// [Pit::with_adjudicator](crate::train::Pit::with_adjudicator).
+    Custom(String),
+}
+
+impl Termination {
+    /// Whether the game has a result, either because it reached a terminal
+    /// state or because it was adjudicated.
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Termination::Natural | Termination::Adjudicated(_))
+    }
+}
+
+impl fmt::Display for Adjudication {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Adjudication::NoProgress => write!(f, "no_progress"),
+            Adjudication::Tablebase => write!(f, "tablebase"),
+            Adjudication::Custom(reason) => write!(f, "custom: {}", reason),
+        }
+    }
+}
+
+impl FromStr for Adjudication {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "no_progress" => Ok(Adjudication::NoProgress),
+            "tablebase" => Ok(Adjudication::Tablebase),
+            _ => match s.strip_prefix("custom: ") {
+                Some(reason) => Ok(Adjudication::Custom(reason.to_string())),
+                None => Err(anyhow::anyhow!("invalid adjudication {:?}", s)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Termination::Natural => write!(f, "natural"),
+            Termination::Adjudicated(reason) => write!(f, "adjudicated: {}", reason),
+            Termination::MaxPlies => write!(f, "max_plies"),
+            Termination::Error(message) => write!(f, "error: {}", message),
+        }
+    }
+}
+
+impl FromStr for Termination {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "natural" => Ok(Termination::Natural),
+            "max_plies" => Ok(Termination::MaxPlies),
+            _ => {
+                if let Some(reason) = s.strip_prefix("adjudicated: ") {
+                    Ok(Termination::Adjudicated(reason.parse()?))
+                } else if let Some(message) = s.strip_prefix("error: ") {
+                    Ok(Termination::Error(message.to_string()))
+                } else {
+                    Err(anyhow::anyhow!("invalid termination {:?}", s))
+                }
+            }
+        }
+    }
+}
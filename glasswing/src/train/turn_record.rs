@@ -0,0 +1,29 @@
+use crate::core::Game;
+use std::time::Duration;
+
+/// A single turn played in a [Pit](crate::train::Pit).
+#[derive(Debug, Clone)]
+pub struct TurnRecord<G: Game> {
+    /// Number of plies played before this turn, starting at 0.
+    pub ply: usize,
+    /// The team that made the move.
+    pub team: G::Team,
+    /// Index of the agent that made the move, 0 for agent A and 1 for agent B.
+    pub agent: usize,
+    pub action: G::Action,
+    pub pre: G::State,
+    pub post: G::State,
+    /// Time the agent took to select the action, as measured by the pit.
+    pub elapsed: Duration,
+    /// Time the agent reported spending, see [Agent::reported_time](crate::agents::Agent::reported_time).
+    pub reported: Option<Duration>,
+    /// The soft and hard budget the agent was given for the move, see
+    /// [Pit::with_budget_scaler](crate::train::Pit::with_budget_scaler).
+    pub budget: Option<(Duration, Duration)>,
+}
+
+impl<G: Game> From<TurnRecord<G>> for (G::State, G::Action, G::State) {
+    fn from(turn: TurnRecord<G>) -> Self {
+        (turn.pre, turn.action, turn.post)
+    }
+}
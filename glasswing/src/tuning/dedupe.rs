@@ -0,0 +1,119 @@
+use crate::core::Game;
+use cachewing::traits::{AlwaysReplacePolicy, TranspositionHash, TranspositionTable};
+use std::marker::PhantomData;
+
+/// Caps how often the same position enters a dataset, across games, so that
+/// the openings of deterministic agents do not dominate it.
+///
+/// Positions are counted in a transposition table, which bounds the memory
+/// used. Counts lost to replacement or hash collisions make the cap
+/// approximate: a position may occasionally be kept more often, or dropped
+/// early, than `max_count` allows.
+pub struct PositionDeduper<G, T> {
+    table: T,
+    max_count: u32,
+    kept: u64,
+    dropped: u64,
+    _game: PhantomData<G>,
+}
+
+impl<G, T> PositionDeduper<G, T>
+where
+    G: Game,
+    G::State: TranspositionHash,
+    T: TranspositionTable<G::State, u32> + AlwaysReplacePolicy,
+{
+    /// Keeps at most `max_count` samples of each position, counting them in
+    /// `table`.
+    pub fn new(table: T, max_count: u32) -> Self {
+        PositionDeduper {
+            table,
+            max_count,
+            kept: 0,
+            dropped: 0,
+            _game: PhantomData,
+        }
+    }
+
+    /// Counts a sample of `state`, returning whether it should be kept.
+    pub fn admit(&mut self, state: &G::State) -> bool {
+        let count = self.table.get(state).copied().unwrap_or(0);
+        if count >= self.max_count {
+            self.dropped += 1;
+            return false;
+        }
+        self.table.insert(state.clone(), count + 1);
+        self.kept += 1;
+        true
+    }
+
+    /// Removes the samples of positions over the cap, e.g. from the output
+    /// of [label_positions](crate::tuning::texel::label_positions).
+    pub fn filter(&mut self, samples: Vec<(G::State, f64)>) -> Vec<(G::State, f64)> {
+        samples
+            .into_iter()
+            .filter(|(state, _)| self.admit(state))
+            .collect()
+    }
+
+    /// Number of samples kept so far.
+    pub fn kept(&self) -> u64 {
+        self.kept
+    }
+
+    /// Number of samples dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::GwState;
+    use crate::testing::counting::{CountingGame, CountingState, TARGET};
+    use crate::tuning::texel::label_positions;
+    use cachewing::QuadraticProbingTable64;
+
+    /// Plays a game in which both teams always add `step`, or whatever is
+    /// left to reach the target.
+    fn play(step: u32) -> Vec<(CountingState, f64)> {
+        let mut state = CountingGame::initial_state();
+        let mut states = vec![state.clone()];
+        while !state.is_terminal() {
+            state = state.apply_action(&step.min(TARGET - state.count));
+            states.push(state.clone());
+        }
+        let result = state.game_result().unwrap();
+        label_positions::<CountingGame>(&states, &result, 0)
+    }
+
+    fn deduper(
+        max_count: u32,
+    ) -> PositionDeduper<CountingGame, QuadraticProbingTable64<CountingState, u32>> {
+        PositionDeduper::new(QuadraticProbingTable64::new(1 << 8), max_count)
+    }
+
+    #[test]
+    fn cap_is_enforced_across_games() {
+        let mut deduper = deduper(2);
+        assert_eq!(play(1).len(), TARGET as usize);
+
+        let kept: Vec<_> = (0..5).map(|_| deduper.filter(play(1)).len()).collect();
+        assert_eq!(kept, [21, 21, 0, 0, 0]);
+        assert_eq!(deduper.kept(), 42);
+        assert_eq!(deduper.dropped(), 63);
+    }
+
+    #[test]
+    fn only_shared_positions_are_dropped() {
+        let mut deduper = deduper(1);
+        assert_eq!(deduper.filter(play(1)).len(), 21);
+        // adding 2 reaches every even count, but only the multiples of 4
+        // with the same team to move as when adding 1
+        let kept = deduper.filter(play(2));
+        let counts: Vec<_> = kept.iter().map(|(state, _)| state.count).collect();
+        assert_eq!(counts, [2, 6, 10, 14, 18]);
+        assert_eq!((deduper.kept(), deduper.dropped()), (26, 6));
+    }
+}
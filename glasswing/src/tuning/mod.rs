@@ -0,0 +1,24 @@
+pub mod dedupe;
+#[cfg(feature = "threads")]
+pub mod spsa;
+pub mod texel;
+
+pub use dedupe::PositionDeduper;
+#[cfg(feature = "threads")]
+pub use spsa::*;
+
+/// Evaluators (or agents) whose behaviour is controlled by a vector of
+/// real-valued parameters, such as the weights of a heuristic.
+pub trait Tunable {
+    fn params(&self) -> Vec<f64>;
+
+    /// Replaces the parameters. `params` has the same length as [Tunable::params].
+    fn set_params(&mut self, params: &[f64]);
+
+    /// Identifies the current parameters, so that values computed with older
+    /// parameters can be told apart. Implementations that cache results must
+    /// change it whenever the parameters change. 0 by default.
+    fn version(&self) -> u64 {
+        0
+    }
+}
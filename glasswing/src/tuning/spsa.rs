@@ -0,0 +1,291 @@
+use crate::agents::Agent;
+use crate::core::Game;
+use crate::train::{run_matches_parallel, MatchConfig};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct SpsaConfig {
+    /// Number of games played per iteration.
+    pub games_per_iteration: usize,
+    /// Step size numerator `a` of `a_k = a / (k + 1 + big_a)^alpha`.
+    pub a: f64,
+    /// Perturbation size numerator `c` of `c_k = c / (k + 1)^gamma`.
+    pub c: f64,
+    pub big_a: f64,
+    pub alpha: f64,
+    pub gamma: f64,
+    /// Seed for the perturbations and, through the match config, the games.
+    pub seed: u64,
+    pub match_config: MatchConfig,
+    /// If set, the best parameters so far, see [Spsa::best], are written to
+    /// this file after every iteration.
+    pub checkpoint: Option<PathBuf>,
+}
+
+impl Default for SpsaConfig {
+    fn default() -> Self {
+        SpsaConfig {
+            games_per_iteration: 64,
+            a: 1.0,
+            c: 1.0,
+            big_a: 10.0,
+            alpha: 0.602,
+            gamma: 0.101,
+            seed: 0,
+            match_config: MatchConfig::default(),
+            checkpoint: None,
+        }
+    }
+}
+
+/// Simultaneous perturbation stochastic approximation over self-play.
+///
+/// Every iteration perturbs all parameters at once in a random direction,
+/// plays a batch of games between the positively and the negatively
+/// perturbed agent, and moves the parameters along the resulting estimate of
+/// the win rate gradient.
+pub struct Spsa {
+    config: SpsaConfig,
+    params: Vec<f64>,
+    iteration: usize,
+    rng: StdRng,
+    best: Option<(Vec<f64>, f64)>,
+}
+
+impl Spsa {
+    pub fn new(initial: Vec<f64>, config: SpsaConfig) -> Self {
+        let rng = StdRng::seed_from_u64(config.seed);
+        Spsa {
+            config,
+            params: initial,
+            iteration: 0,
+            rng,
+            best: None,
+        }
+    }
+
+    pub fn params(&self) -> &[f64] {
+        &self.params
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// The perturbed parameters with the highest score in their batch so
+    /// far, together with that score.
+    pub fn best(&self) -> Option<(&[f64], f64)> {
+        self.best
+            .as_ref()
+            .map(|(params, score)| (params.as_slice(), *score))
+    }
+
+    /// Runs a single iteration and returns the score of the positively
+    /// perturbed agent against the negatively perturbed one.
+    ///
+    /// `make_agent` builds an agent from a parameter vector and a game seed.
+    pub fn step<G, A, F>(&mut self, make_agent: &F) -> io::Result<f64>
+    where
+        G: Game,
+        G::Action: Send,
+        G::Team: Send,
+        G::GameResult: Send,
+        A: Agent<G>,
+        F: Fn(&[f64], u64) -> A + Sync,
+    {
+        let k = self.iteration as f64;
+        let a_k = self.config.a / (k + 1.0 + self.config.big_a).powf(self.config.alpha);
+        let c_k = self.config.c / (k + 1.0).powf(self.config.gamma);
+
+        let delta = (0..self.params.len())
+            .map(|_| if self.rng.gen::<bool>() { 1.0 } else { -1.0 })
+            .collect::<Vec<f64>>();
+        let plus = perturb(&self.params, &delta, c_k);
+        let minus = perturb(&self.params, &delta, -c_k);
+
+        let match_config = MatchConfig {
+            base_seed: self.config.seed ^ (self.iteration as u64).wrapping_mul(0x9E3779B97F4A7C15),
+            ..self.config.match_config.clone()
+        };
+        let outcomes = run_matches_parallel::<G, _, _, _, _>(
+            |seed| make_agent(&plus, seed),
+            |seed| make_agent(&minus, seed),
+            self.config.games_per_iteration,
+            &match_config,
+        );
+        let score = outcomes
+            .iter()
+            .map(|outcome| outcome.score_a())
+            .sum::<f64>()
+            / outcomes.len().max(1) as f64;
+
+        // score - (1 - score) estimates the difference in win rate between plus and minus
+        let difference = 2.0 * score - 1.0;
+        for (param, delta) in self.params.iter_mut().zip(delta.iter()) {
+            *param += a_k * difference / (2.0 * c_k * delta);
+        }
+
+        let (best_candidate, best_score) = if score >= 0.5 {
+            (plus, score)
+        } else {
+            (minus, 1.0 - score)
+        };
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|(_, best)| best_score > *best)
+        {
+            self.best = Some((best_candidate, best_score));
+        }
+
+        self.iteration += 1;
+        if let (Some(path), Some((best, _))) = (&self.config.checkpoint, &self.best) {
+            save_params(path, best)?;
+        }
+        Ok(score)
+    }
+
+    /// Runs `iterations` iterations and returns the final parameters.
+    pub fn run<G, A, F>(&mut self, iterations: usize, make_agent: &F) -> io::Result<&[f64]>
+    where
+        G: Game,
+        G::Action: Send,
+        G::Team: Send,
+        G::GameResult: Send,
+        A: Agent<G>,
+        F: Fn(&[f64], u64) -> A + Sync,
+    {
+        for _ in 0..iterations {
+            self.step::<G, A, F>(make_agent)?;
+        }
+        Ok(&self.params)
+    }
+}
+
+fn perturb(params: &[f64], delta: &[f64], c: f64) -> Vec<f64> {
+    params
+        .iter()
+        .zip(delta.iter())
+        .map(|(param, delta)| param + c * delta)
+        .collect()
+}
+
+/// Writes the parameters to a file, one per line.
+pub fn save_params(path: impl AsRef<Path>, params: &[f64]) -> io::Result<()> {
+    let contents = params
+        .iter()
+        .map(|param| param.to_string())
+        .collect::<Vec<String>>()
+        .join("\n");
+    fs::write(path, contents)
+}
+
+/// Reads parameters written by [save_params].
+pub fn load_params(path: impl AsRef<Path>) -> io::Result<Vec<f64>> {
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.trim()
+                .parse::<f64>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{Evaluator, MaximisingAgent};
+    use crate::core::{GameResult, GwState, Team};
+    use crate::testing::counting::{CountingGame, CountingState};
+    use crate::train::MatchSeries;
+
+    /// Weighs the perfect play rule against random noise.
+    struct Weighted([f64; 2], StdRng);
+
+    impl Evaluator<CountingGame> for Weighted {
+        fn evaluate_for(&mut self, state: &CountingState, team: &Team) -> i32 {
+            let score = match state.game_result() {
+                Some(GameResult::Win(winner)) if winner == state.team => 1000,
+                Some(_) => -1000,
+                None => {
+                    let rule = if state.is_lost() { -1.0 } else { 1.0 };
+                    let noise = self.1.gen_range(-1.0..1.0);
+                    (100.0 * (self.0[0] * rule + self.0[1] * noise)).round() as i32
+                }
+            };
+            if *team == state.team {
+                score
+            } else {
+                -score
+            }
+        }
+    }
+
+    fn agent(params: &[f64], seed: u64) -> MaximisingAgent<CountingGame, Weighted> {
+        MaximisingAgent::new(Weighted(
+            [params[0], params[1]],
+            StdRng::seed_from_u64(seed),
+        ))
+    }
+
+    fn score_against_baseline(params: &[f64]) -> f64 {
+        let params = params.to_vec();
+        MatchSeries::new(
+            |seed| agent(&params, seed),
+            |seed| agent(&BASELINE, seed),
+            200,
+        )
+        .with_seed(99)
+        .run()
+        .total()
+        .score()
+    }
+
+    const BASELINE: [f64; 2] = [0.0, 1.0];
+
+    #[test]
+    fn tuning_beats_the_baseline() {
+        let checkpoint = std::env::temp_dir().join(format!("spsa-{}.txt", std::process::id()));
+        let config = SpsaConfig {
+            games_per_iteration: 32,
+            a: 0.5,
+            c: 0.3,
+            seed: 1,
+            checkpoint: Some(checkpoint.clone()),
+            ..SpsaConfig::default()
+        };
+        let mut spsa = Spsa::new(BASELINE.to_vec(), config.clone());
+        let tuned = spsa.run::<CountingGame, _, _>(20, &agent).unwrap().to_vec();
+
+        assert!(tuned[0] > BASELINE[0], "{:?}", tuned);
+        let baseline_score = score_against_baseline(&BASELINE);
+        let tuned_score = score_against_baseline(&tuned);
+        assert!(
+            tuned_score > baseline_score + 0.1,
+            "{} vs {}",
+            tuned_score,
+            baseline_score
+        );
+
+        // the best parameters are checkpointed
+        let (best, _) = spsa.best().unwrap();
+        assert_eq!(load_params(&checkpoint).unwrap(), best);
+        fs::remove_file(&checkpoint).unwrap();
+
+        // seeded batches make tuning deterministic
+        let mut again = Spsa::new(
+            BASELINE.to_vec(),
+            SpsaConfig {
+                checkpoint: None,
+                ..config
+            },
+        );
+        assert_eq!(again.run::<CountingGame, _, _>(20, &agent).unwrap(), tuned);
+    }
+}
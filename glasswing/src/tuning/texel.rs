@@ -0,0 +1,182 @@
+use crate::agents::Evaluator;
+use crate::core::{Game, GwGameResult, GwState};
+use crate::tuning::Tunable;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TexelConfig {
+    /// Scaling constant of the sigmoid `1 / (1 + 10^(-k * eval / 400))`.
+    pub k: f64,
+    /// Initial step by which parameters are changed.
+    pub step: f64,
+    /// The search ends once the step has been halved below this value.
+    pub min_step: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for TexelConfig {
+    fn default() -> Self {
+        TexelConfig {
+            k: 1.0,
+            step: 1.0,
+            min_step: 1e-3,
+            max_iterations: 1000,
+        }
+    }
+}
+
+/// Fits the parameters of an evaluator to game outcomes by minimising the
+/// mean squared error between the sigmoid of its evaluation and the outcome,
+/// using coordinate descent with a shrinking step.
+///
+/// Each position is labelled with the outcome for the team to move in it:
+/// 1 for a win, 0.5 for a draw and 0 for a loss (see [label_positions]).
+/// Returns the final error.
+pub fn fit<G, E>(positions: &[(G::State, f64)], evaluator: &mut E, config: &TexelConfig) -> f64
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G> + Tunable,
+{
+    let mut params = evaluator.params();
+    let mut best_error = error::<G, E>(positions, evaluator, config.k);
+    let mut step = config.step;
+
+    for _ in 0..config.max_iterations {
+        if step < config.min_step {
+            break;
+        }
+
+        let mut improved = false;
+        for i in 0..params.len() {
+            for direction in [1.0, -1.0] {
+                let original = params[i];
+                params[i] = original + direction * step;
+                evaluator.set_params(&params);
+
+                let new_error = error::<G, E>(positions, evaluator, config.k);
+                if new_error < best_error {
+                    best_error = new_error;
+                    improved = true;
+                    break;
+                }
+                params[i] = original;
+            }
+        }
+        evaluator.set_params(&params);
+
+        if !improved {
+            step /= 2.0;
+        }
+    }
+    best_error
+}
+
+/// Mean squared error between the predicted and the actual outcomes.
+pub fn error<G, E>(positions: &[(G::State, f64)], evaluator: &mut E, k: f64) -> f64
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G>,
+{
+    let sum = positions
+        .iter()
+        .map(|(state, outcome)| {
+            let eval: f64 = evaluator.evaluate(state).into();
+            (outcome - sigmoid(eval, k)).powi(2)
+        })
+        .sum::<f64>();
+    sum / positions.len().max(1) as f64
+}
+
+#[inline]
+fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+/// Labels the states of a finished game with its result for the team to move
+/// in each state, skipping the first `skip_plies` states and terminal states.
+pub fn label_positions<G: Game>(
+    states: &[G::State],
+    result: &G::GameResult,
+    skip_plies: usize,
+) -> Vec<(G::State, f64)> {
+    states
+        .iter()
+        .skip(skip_plies)
+        .filter(|state| !state.is_terminal())
+        .map(|state| {
+            let outcome = match result.winner() {
+                None => 0.5,
+                Some(winner) if winner == state.team_to_move() => 1.0,
+                Some(_) => 0.0,
+            };
+            (state.clone(), outcome)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Team;
+    use crate::testing::counting::{CountingGame, CountingState, TARGET};
+
+    /// A weighted sum of two features of the position: the count and
+    /// whether the team to move is lost under perfect play.
+    struct LinearEvaluator {
+        weights: [f64; 2],
+    }
+
+    impl LinearEvaluator {
+        fn raw(&self, state: &CountingState) -> f64 {
+            let lost = if state.is_lost() { -10.0 } else { 10.0 };
+            self.weights[0] * (state.count as f64 - 10.0) + self.weights[1] * lost
+        }
+    }
+
+    impl Evaluator<CountingGame> for LinearEvaluator {
+        fn evaluate_for(&mut self, state: &CountingState, team: &Team) -> i32 {
+            let eval = self.raw(state).round() as i32;
+            if *team == state.team {
+                eval
+            } else {
+                -eval
+            }
+        }
+    }
+
+    impl Tunable for LinearEvaluator {
+        fn params(&self) -> Vec<f64> {
+            self.weights.to_vec()
+        }
+
+        fn set_params(&mut self, params: &[f64]) {
+            self.weights.copy_from_slice(params);
+        }
+    }
+
+    #[test]
+    fn recovers_weights_of_a_linear_evaluator() {
+        let truth = LinearEvaluator {
+            weights: [6.0, 25.0],
+        };
+        let positions = (0..TARGET)
+            .flat_map(|count| [Team::One, Team::Two].map(|team| CountingState::new(count, team)))
+            .map(|state| {
+                let outcome = sigmoid(truth.raw(&state), 1.0);
+                (state, outcome)
+            })
+            .collect::<Vec<_>>();
+
+        let mut evaluator = LinearEvaluator {
+            weights: [0.0, 0.0],
+        };
+        let config = TexelConfig::default();
+        let before = error::<CountingGame, _>(&positions, &mut evaluator, config.k);
+        let after = fit::<CountingGame, _>(&positions, &mut evaluator, &config);
+        assert!(after < before / 100.0, "error {} after fitting", after);
+        for (fitted, expected) in evaluator.weights.iter().zip(truth.weights) {
+            assert!((fitted - expected).abs() < 0.1, "{:?}", evaluator.weights);
+        }
+    }
+}
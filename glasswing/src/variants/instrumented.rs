@@ -0,0 +1,245 @@
+use crate::agents::{Evaluator, SearchError};
+use crate::core::{Game, GwState};
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A game that plays like `G` but counts the calls made to its states, to
+/// measure how searches and matches use a game without changing its code.
+///
+/// Every state derived from the same initial state shares one set of
+/// [counters](InstrumentedState::counters).
+pub struct Instrumented<G> {
+    _marker: PhantomData<G>,
+}
+
+impl<G> fmt::Debug for Instrumented<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Instrumented")
+    }
+}
+
+impl<G: Game> Game for Instrumented<G> {
+    type State = InstrumentedState<G>;
+    type Action = G::Action;
+    type Team = G::Team;
+    type GameResult = G::GameResult;
+    type EvalType = G::EvalType;
+
+    const MAX_ACTIONS: Option<usize> = G::MAX_ACTIONS;
+
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("Instrumented<{}>", G::name()))
+    }
+
+    fn initial_state() -> Self::State {
+        InstrumentedState::new(G::initial_state())
+    }
+}
+
+/// Calls counted by an [Instrumented] game.
+#[derive(Debug, Default)]
+pub struct CallCounters {
+    action_calls: AtomicU64,
+    actions_generated: AtomicU64,
+    apply_calls: AtomicU64,
+    terminal_checks: AtomicU64,
+}
+
+impl CallCounters {
+    pub fn report(&self) -> CallReport {
+        CallReport {
+            action_calls: self.action_calls.load(Ordering::Relaxed),
+            actions_generated: self.actions_generated.load(Ordering::Relaxed),
+            apply_calls: self.apply_calls.load(Ordering::Relaxed),
+            terminal_checks: self.terminal_checks.load(Ordering::Relaxed),
+        }
+    }
+
+    #[inline]
+    fn count_actions(&self, generated: usize) {
+        self.action_calls.fetch_add(1, Ordering::Relaxed);
+        self.actions_generated
+            .fetch_add(generated as u64, Ordering::Relaxed);
+    }
+}
+
+/// A snapshot of [CallCounters].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallReport {
+    /// Calls to [GwState::actions] and [GwState::actions_into].
+    pub action_calls: u64,
+    /// Actions returned by those calls.
+    pub actions_generated: u64,
+    /// Calls to [GwState::apply_action].
+    pub apply_calls: u64,
+    /// Calls to [GwState::is_terminal].
+    pub terminal_checks: u64,
+}
+
+impl CallReport {
+    /// Average number of actions per generation.
+    pub fn average_branching(&self) -> f64 {
+        self.actions_generated as f64 / self.action_calls.max(1) as f64
+    }
+}
+
+/// A state of the wrapped game, with the counters it reports to.
+pub struct InstrumentedState<G: Game> {
+    pub state: G::State,
+    counters: Arc<CallCounters>,
+}
+
+impl<G: Game> InstrumentedState<G> {
+    /// Wraps a state with fresh counters.
+    pub fn new(state: G::State) -> Self {
+        InstrumentedState {
+            state,
+            counters: Arc::new(CallCounters::default()),
+        }
+    }
+
+    pub fn counters(&self) -> &Arc<CallCounters> {
+        &self.counters
+    }
+}
+
+impl<G: Game> Clone for InstrumentedState<G> {
+    fn clone(&self) -> Self {
+        InstrumentedState {
+            state: self.state.clone(),
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+impl<G: Game> fmt::Debug for InstrumentedState<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("InstrumentedState")
+            .field(&self.state)
+            .finish()
+    }
+}
+
+impl<G: Game> fmt::Display for InstrumentedState<G>
+where
+    G::State: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.state.fmt(f)
+    }
+}
+
+impl<G: Game> GwState<Instrumented<G>> for InstrumentedState<G> {
+    type ActionIter = Vec<G::Action>;
+
+    fn actions(&self) -> Self::ActionIter {
+        let actions = self.state.actions().into_iter().collect::<Vec<_>>();
+        self.counters.count_actions(actions.len());
+        actions
+    }
+
+    #[inline]
+    fn actions_into(&self, buf: &mut Vec<G::Action>) {
+        self.state.actions_into(buf);
+        self.counters.count_actions(buf.len());
+    }
+
+    #[inline]
+    fn count_actions(&self) -> usize {
+        self.state.count_actions()
+    }
+
+    #[inline]
+    fn team_to_move(&self) -> G::Team {
+        self.state.team_to_move()
+    }
+
+    #[inline]
+    fn static_order_key(&self, action: &G::Action) -> i32 {
+        self.state.static_order_key(action)
+    }
+
+    #[inline]
+    fn apply_action(&self, action: &G::Action) -> Self {
+        self.counters.apply_calls.fetch_add(1, Ordering::Relaxed);
+        InstrumentedState {
+            state: self.state.apply_action(action),
+            counters: self.counters.clone(),
+        }
+    }
+
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        self.counters
+            .terminal_checks
+            .fetch_add(1, Ordering::Relaxed);
+        self.state.is_terminal()
+    }
+
+    #[inline]
+    fn game_result(&self) -> Option<G::GameResult> {
+        self.state.game_result()
+    }
+}
+
+impl<G: Game> cachewing::TranspositionHash for InstrumentedState<G>
+where
+    G::State: cachewing::TranspositionHash,
+{
+    #[inline]
+    fn hash(&self) -> u64 {
+        self.state.hash()
+    }
+}
+
+impl<G: Game> crate::perft::AuditHash for InstrumentedState<G>
+where
+    G::State: crate::perft::AuditHash,
+{
+    #[inline]
+    fn audit_hash(&self) -> u64 {
+        self.state.audit_hash()
+    }
+}
+
+/// Adapts an evaluator of a game to its [Instrumented] variant.
+pub struct InstrumentedEvaluator<E>(pub E);
+
+impl<G, E> Evaluator<Instrumented<G>> for InstrumentedEvaluator<E>
+where
+    G: Game,
+    E: Evaluator<G>,
+{
+    #[inline]
+    fn evaluate_for(&mut self, state: &InstrumentedState<G>, team: &G::Team) -> G::EvalType {
+        self.0.evaluate_for(&state.state, team)
+    }
+
+    fn begin_move(&mut self) {
+        self.0.begin_move();
+    }
+
+    fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+        self.0.set_time_budget(soft, hard);
+    }
+
+    fn set_node_budget(&mut self, nodes: u64) {
+        self.0.set_node_budget(nodes);
+    }
+
+    fn end_move(&mut self) {
+        self.0.end_move();
+    }
+
+    fn take_error(&mut self) -> Option<SearchError> {
+        self.0.take_error()
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.0.name()
+    }
+}
@@ -0,0 +1,176 @@
+use crate::agents::scores::is_decisive;
+use crate::agents::{Evaluator, SearchError};
+use crate::core::{Game, GameResult, GwState, GwTeam};
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// The misère variant of a game: play is unchanged, but whoever would have
+/// won loses instead. Draws stay draws.
+pub struct Misere<G> {
+    _marker: PhantomData<G>,
+}
+
+impl<G> fmt::Debug for Misere<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Misere")
+    }
+}
+
+impl<G> Game for Misere<G>
+where
+    G: Game<GameResult = GameResult<<G as Game>::Team>>,
+{
+    type State = MisereState<G>;
+    type Action = G::Action;
+    type Team = G::Team;
+    type GameResult = GameResult<G::Team>;
+    type EvalType = G::EvalType;
+
+    const MAX_ACTIONS: Option<usize> = G::MAX_ACTIONS;
+
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("Misere<{}>", G::name()))
+    }
+
+    fn initial_state() -> Self::State {
+        MisereState(G::initial_state())
+    }
+}
+
+/// A state of the wrapped game.
+pub struct MisereState<G: Game>(pub G::State);
+
+impl<G: Game> Clone for MisereState<G> {
+    fn clone(&self) -> Self {
+        MisereState(self.0.clone())
+    }
+}
+
+impl<G: Game> fmt::Debug for MisereState<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MisereState").field(&self.0).finish()
+    }
+}
+
+impl<G: Game> fmt::Display for MisereState<G>
+where
+    G::State: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<G> GwState<Misere<G>> for MisereState<G>
+where
+    G: Game<GameResult = GameResult<<G as Game>::Team>>,
+{
+    type ActionIter = <G::State as GwState<G>>::ActionIter;
+
+    #[inline]
+    fn actions(&self) -> Self::ActionIter {
+        self.0.actions()
+    }
+
+    #[inline]
+    fn actions_into(&self, buf: &mut Vec<G::Action>) {
+        self.0.actions_into(buf)
+    }
+
+    #[inline]
+    fn count_actions(&self) -> usize {
+        self.0.count_actions()
+    }
+
+    #[inline]
+    fn team_to_move(&self) -> G::Team {
+        self.0.team_to_move()
+    }
+
+    #[inline]
+    fn static_order_key(&self, action: &G::Action) -> i32 {
+        self.0.static_order_key(action)
+    }
+
+    #[inline]
+    fn apply_action(&self, action: &G::Action) -> Self {
+        MisereState(self.0.apply_action(action))
+    }
+
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        self.0.is_terminal()
+    }
+
+    fn game_result(&self) -> Option<GameResult<G::Team>> {
+        self.0.game_result().map(|result| match result {
+            GameResult::Win(team) => GameResult::Win(team.opponent()),
+            GameResult::Draw => GameResult::Draw,
+        })
+    }
+}
+
+impl<G: Game> cachewing::TranspositionHash for MisereState<G>
+where
+    G::State: cachewing::TranspositionHash,
+{
+    #[inline]
+    fn hash(&self) -> u64 {
+        self.0.hash()
+    }
+}
+
+impl<G: Game> crate::perft::AuditHash for MisereState<G>
+where
+    G::State: crate::perft::AuditHash,
+{
+    #[inline]
+    fn audit_hash(&self) -> u64 {
+        self.0.audit_hash()
+    }
+}
+
+/// Adapts an evaluator of a game to its misère variant by negating its
+/// decisive scores. Other scores are passed through unchanged.
+pub struct MisereEvaluator<E>(pub E);
+
+impl<G, E> Evaluator<Misere<G>> for MisereEvaluator<E>
+where
+    G: Game<GameResult = GameResult<<G as Game>::Team>, EvalType = i32>,
+    E: Evaluator<G>,
+{
+    fn evaluate_for(&mut self, state: &MisereState<G>, team: &G::Team) -> i32 {
+        let eval = self.0.evaluate_for(&state.0, team);
+        if is_decisive(eval) {
+            -eval
+        } else {
+            eval
+        }
+    }
+
+    fn begin_move(&mut self) {
+        self.0.begin_move();
+    }
+
+    fn set_time_budget(&mut self, soft: Duration, hard: Duration) {
+        self.0.set_time_budget(soft, hard);
+    }
+
+    fn set_node_budget(&mut self, nodes: u64) {
+        self.0.set_node_budget(nodes);
+    }
+
+    fn end_move(&mut self) {
+        self.0.end_move();
+    }
+
+    fn take_error(&mut self) -> Option<SearchError> {
+        self.0.take_error()
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Misere({})", self.0.name()))
+    }
+}
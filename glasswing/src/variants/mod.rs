@@ -0,0 +1,5 @@
+pub mod instrumented;
+pub mod misere;
+
+pub use instrumented::*;
+pub use misere::*;
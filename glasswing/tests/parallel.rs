@@ -0,0 +1,73 @@
+#![cfg(feature = "threads")]
+
+use glasswing::agents::RandomAgent;
+use glasswing::train::{
+    run_matches_parallel, MatchConfig, MatchOutcome, MatchSeries, SeriesResult,
+};
+use glasswing_games::tictactoe::TicTacToe;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn random_games(threads: usize, base_seed: u64) -> Vec<MatchOutcome<TicTacToe>> {
+    let config = MatchConfig {
+        threads,
+        base_seed,
+        record_actions: true,
+        ..MatchConfig::default()
+    };
+    let random = |seed| RandomAgent::<TicTacToe, _>::new(StdRng::seed_from_u64(seed));
+    run_matches_parallel(random, random, 1000, &config)
+}
+
+#[test]
+fn random_tictactoe_results_are_deterministic() {
+    let first = SeriesResult::new(random_games(4, 7));
+    let second = SeriesResult::new(random_games(3, 7));
+    assert_eq!(first.total().total(), 1000);
+    assert_eq!(first.as_first, second.as_first);
+    assert_eq!(first.as_second, second.as_second);
+    assert_eq!(first.errors + first.unfinished, 0);
+    for (a, b) in first.outcomes.iter().zip(&second.outcomes) {
+        assert_eq!(a.game, b.game);
+        assert_eq!(a.actions, b.actions);
+    }
+
+    let other = SeriesResult::new(random_games(4, 8));
+    assert_ne!(
+        first
+            .outcomes
+            .iter()
+            .map(|o| &o.actions)
+            .collect::<Vec<_>>(),
+        other
+            .outcomes
+            .iter()
+            .map(|o| &o.actions)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn agents_draw_from_independent_seeds() {
+    let outcomes = random_games(2, 0);
+    for outcome in &outcomes {
+        assert_ne!(outcome.seed_a, outcome.seed_b);
+        assert_eq!(outcome.swapped, !outcome.game.is_multiple_of(2));
+    }
+    // every game builds its agents from fresh seeds
+    let seeds = outcomes
+        .iter()
+        .map(|o| o.seed_a)
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(seeds.len(), outcomes.len());
+}
+
+#[test]
+fn parallel_series_matches_sequential_series() {
+    let random = |seed| RandomAgent::<TicTacToe, _>::new(StdRng::seed_from_u64(seed));
+    let series = MatchSeries::new(random, random, 200).with_seed(3);
+    let sequential = series.run();
+    let parallel = series.run_parallel();
+    assert_eq!(sequential.as_first, parallel.as_first);
+    assert_eq!(sequential.as_second, parallel.as_second);
+}
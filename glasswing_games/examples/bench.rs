@@ -0,0 +1,47 @@
+use cachewing::{QuadraticProbingTable64, TranspositionTable};
+use glasswing_games::bench;
+use std::time::Instant;
+
+fn main() {
+    let time = Instant::now();
+    let evaluations = bench::connect4_negamax_evaluations(8);
+    println!(
+        "Connect4 NegaMax(8): {:?} evaluations in {:?}",
+        evaluations,
+        time.elapsed()
+    );
+
+    let time = Instant::now();
+    let evaluations = bench::tictactoe_negamax_evaluations(9);
+    println!(
+        "TicTacToe NegaMax(9): {:?} evaluations in {:?}",
+        evaluations,
+        time.elapsed()
+    );
+
+    let keys = bench::connect4_keys(7);
+    let mut table = QuadraticProbingTable64::new(1 << 20);
+
+    let time = Instant::now();
+    for (i, key) in keys.iter().enumerate() {
+        table.insert(key.clone(), i as u64);
+    }
+    let elapsed = time.elapsed();
+    println!(
+        "Table insert: {} keys in {:?} ({:.1} ns/op)",
+        keys.len(),
+        elapsed,
+        elapsed.as_nanos() as f64 / keys.len() as f64
+    );
+
+    let time = Instant::now();
+    let hits = keys.iter().filter(|key| table.get(key).is_some()).count();
+    let elapsed = time.elapsed();
+    println!(
+        "Table get: {} hits of {} in {:?} ({:.1} ns/op)",
+        hits,
+        keys.len(),
+        elapsed,
+        elapsed.as_nanos() as f64 / keys.len() as f64
+    );
+}
@@ -0,0 +1,54 @@
+use glasswing::core::{Clock, Game, SystemClock};
+use glasswing::perft::{divide, perft};
+use glasswing_games::connect4::Connect4;
+use glasswing_games::nxn_tictactoe::NTicTacToe;
+use glasswing_games::tictactoe::TicTacToe;
+use std::fmt::Debug;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        println!("Usage: {} <game> <depth>", args[0]);
+        println!("Games: tictactoe, connect4, nttt3, nttt4");
+        return;
+    }
+
+    let depth = args[2].parse::<u32>().expect("Depth must be a number");
+
+    match args[1].as_str() {
+        "tictactoe" => run::<TicTacToe>(depth),
+        "connect4" => run::<Connect4>(depth),
+        "nttt3" => run::<NTicTacToe<3>>(depth),
+        "nttt4" => run::<NTicTacToe<4>>(depth),
+        game => println!("Unknown game: {}", game),
+    }
+}
+
+fn run<G: Game>(depth: u32)
+where
+    G::Action: Debug,
+{
+    let state = G::initial_state();
+    let clock = SystemClock::new();
+
+    for d in 1..=depth {
+        let start = clock.now();
+        let count = perft::<G>(&state, d);
+        let elapsed = clock.elapsed_since(start);
+        println!(
+            "Perft({}) = {} in {:?} ({:.0} nodes/s)",
+            d,
+            count,
+            elapsed,
+            count as f64 / elapsed.as_secs_f64()
+        );
+    }
+
+    println!("Divide({}):", depth);
+    let mut total = 0;
+    for (action, count) in divide::<G>(&state, depth) {
+        println!("{:?}: {}", action, count);
+        total += count;
+    }
+    println!("Total: {}", total);
+}
@@ -0,0 +1,96 @@
+//! Fixed positions and workloads for benchmarking the search agents and the
+//! transposition tables. The node counts are deterministic, so they can also
+//! be used to detect changes in search behaviour.
+
+use crate::connect4::{C4Heuristic, C4State, Connect4};
+use crate::tictactoe::{TTTHeuristic, TTTState, TicTacToe};
+use glasswing::agents::{Evaluator, NegaMax};
+use glasswing::core::{Game, GwState};
+
+/// Connect4 positions as move strings, from the opening to the late middlegame.
+pub const CONNECT4_POSITIONS: [&str; 4] = ["", "3344", "316212535010", "31621253501026316212"];
+
+/// TicTacToe positions as move strings.
+pub const TICTACTOE_POSITIONS: [&str; 3] = ["", "40", "4082"];
+
+pub fn connect4_positions() -> Vec<C4State> {
+    CONNECT4_POSITIONS
+        .iter()
+        .map(|moves| C4State::from_moves(moves))
+        .collect()
+}
+
+pub fn tictactoe_positions() -> Vec<TTTState> {
+    TICTACTOE_POSITIONS
+        .iter()
+        .map(|moves| TTTState::from_moves(moves))
+        .collect()
+}
+
+/// Wraps an evaluator and counts how often it is called.
+pub struct CountingEvaluator<E> {
+    inner: E,
+    calls: u64,
+}
+
+impl<E> CountingEvaluator<E> {
+    pub fn new(inner: E) -> Self {
+        CountingEvaluator { inner, calls: 0 }
+    }
+
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+}
+
+impl<G: Game, E: Evaluator<G>> Evaluator<G> for CountingEvaluator<E> {
+    #[inline]
+    fn evaluate_for(&mut self, state: &G::State, team: &G::Team) -> G::EvalType {
+        self.calls += 1;
+        self.inner.evaluate_for(state, team)
+    }
+}
+
+/// Number of evaluator calls made by a NegaMax search of the given depth on
+/// each of the [CONNECT4_POSITIONS].
+pub fn connect4_negamax_evaluations(depth: u32) -> Vec<u64> {
+    connect4_positions()
+        .iter()
+        .map(|state| {
+            let mut negamax =
+                NegaMax::<Connect4, _>::new(depth, CountingEvaluator::new(C4Heuristic));
+            negamax.evaluate(state);
+            negamax.evaluator().calls()
+        })
+        .collect()
+}
+
+/// Number of evaluator calls made by a NegaMax search of the given depth on
+/// each of the [TICTACTOE_POSITIONS].
+pub fn tictactoe_negamax_evaluations(depth: u32) -> Vec<u64> {
+    tictactoe_positions()
+        .iter()
+        .map(|state| {
+            let mut negamax =
+                NegaMax::<TicTacToe, _>::new(depth, CountingEvaluator::new(TTTHeuristic));
+            negamax.evaluate(state);
+            negamax.evaluator().calls()
+        })
+        .collect()
+}
+
+/// All Connect4 states reachable within `depth` plies, in breadth-first order
+/// and including duplicates. These give a realistic key distribution for
+/// transposition table benchmarks.
+pub fn connect4_keys(depth: u32) -> Vec<C4State> {
+    let mut keys = vec![Connect4::initial_state()];
+    let mut frontier = keys.clone();
+    for _ in 0..depth {
+        frontier = frontier
+            .iter()
+            .flat_map(|state| state.substates())
+            .collect();
+        keys.extend(frontier.iter().cloned());
+    }
+    keys
+}
@@ -0,0 +1,377 @@
+use glasswing::agents::scores::{loss_in, win_in};
+use glasswing::agents::Evaluator;
+use glasswing::core::{Game, GameResult, GwState, Team};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+/// Breakthrough on an 8x8 board. Pawns move one square forward, straight or
+/// diagonally, and capture only diagonally. A team wins by reaching the
+/// opposite rank or capturing every opposing pawn.
+///
+/// Squares are numbered `rank * 8 + file`. White ([Team::One]) starts on the
+/// first two ranks, moves first and moves up the board.
+#[derive(Clone, Debug)]
+pub struct Breakthrough;
+
+impl Game for Breakthrough {
+    type State = BTState;
+    type Action = BTAction;
+    type Team = Team;
+    type GameResult = GameResult<Self::Team>;
+    type EvalType = i32;
+
+    /// Three moves for each of the 16 pawns.
+    const MAX_ACTIONS: Option<usize> = Some(48);
+
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("Breakthrough")
+    }
+
+    fn initial_state() -> Self::State {
+        BTState {
+            white: 0x0000_0000_0000_FFFF,
+            black: 0xFFFF_0000_0000_0000,
+            player: Team::One,
+            plies: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BTAction {
+    from: u8,
+    to: u8,
+}
+
+impl BTAction {
+    pub fn new(from: u8, to: u8) -> Self {
+        BTAction { from, to }
+    }
+
+    pub fn from(&self) -> usize {
+        self.from as usize
+    }
+
+    pub fn to(&self) -> usize {
+        self.to as usize
+    }
+}
+
+impl Display for BTAction {
+    /// Uses algebraic notation, e.g. `a2-a3`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = |square: u8| {
+            let file = (b'a' + square % 8) as char;
+            format!("{}{}", file, square / 8 + 1)
+        };
+        write!(f, "{}-{}", name(self.from), name(self.to))
+    }
+}
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = 0x8080_8080_8080_8080;
+const RANK_1: u64 = 0x0000_0000_0000_00FF;
+const RANK_8: u64 = 0xFF00_0000_0000_0000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BTState {
+    white: u64,
+    black: u64,
+    player: Team,
+    plies: u32,
+}
+
+impl BTState {
+    /// Builds a position from sets of squares.
+    ///
+    /// # Panics
+    /// Panics if a square is occupied by both teams.
+    pub fn new(white: u64, black: u64, player: Team) -> Self {
+        assert_eq!(white & black, 0, "Square occupied by both teams");
+        BTState {
+            white,
+            black,
+            player,
+            plies: 0,
+        }
+    }
+
+    pub fn pieces(&self, team: Team) -> u64 {
+        match team {
+            Team::One => self.white,
+            Team::Two => self.black,
+        }
+    }
+
+    /// Number of plies played since this state was created.
+    pub fn plies(&self) -> u32 {
+        self.plies
+    }
+
+    /// Targets of the moves of `team`, as `(straight, left, right)` sets
+    /// together with the shift that leads back to the origin of each.
+    #[inline]
+    fn targets(&self, team: Team) -> [(u64, i32); 3] {
+        let empty = !(self.white | self.black);
+        match team {
+            Team::One => {
+                let own = self.white;
+                let not_own = !own;
+                [
+                    ((own << 8) & empty, 8),
+                    (((own & !FILE_A) << 7) & not_own, 7),
+                    (((own & !FILE_H) << 9) & not_own, 9),
+                ]
+            }
+            Team::Two => {
+                let own = self.black;
+                let not_own = !own;
+                [
+                    ((own >> 8) & empty, -8),
+                    (((own & !FILE_H) >> 7) & not_own, -7),
+                    (((own & !FILE_A) >> 9) & not_own, -9),
+                ]
+            }
+        }
+    }
+
+    fn has_actions(&self) -> bool {
+        self.targets(self.player)
+            .iter()
+            .any(|&(targets, _)| targets != 0)
+    }
+}
+
+impl Display for BTState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                let mask = 1u64 << (rank * 8 + file);
+                let c = if self.white & mask != 0 {
+                    'W'
+                } else if self.black & mask != 0 {
+                    'B'
+                } else {
+                    '·'
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl GwState<Breakthrough> for BTState {
+    type ActionIter = Vec<BTAction>;
+
+    fn actions(&self) -> Self::ActionIter {
+        let mut buf = Vec::new();
+        self.actions_into(&mut buf);
+        buf
+    }
+
+    #[inline]
+    fn actions_into(&self, buf: &mut Vec<BTAction>) {
+        buf.clear();
+        if self.is_terminal() {
+            return;
+        }
+        for (mut targets, shift) in self.targets(self.player) {
+            while targets != 0 {
+                let to = targets.trailing_zeros() as u8;
+                targets &= targets - 1;
+                let from = (to as i32 - shift) as u8;
+                buf.push(BTAction { from, to });
+            }
+        }
+    }
+
+    #[inline]
+    fn count_actions(&self) -> usize {
+        if self.is_terminal() {
+            return 0;
+        }
+        self.targets(self.player)
+            .iter()
+            .map(|&(targets, _)| targets.count_ones() as usize)
+            .sum()
+    }
+
+    #[inline]
+    fn team_to_move(&self) -> Team {
+        self.player
+    }
+
+    /// Assumes the action is legal in this state.
+    fn apply_action(&self, action: &BTAction) -> Self {
+        let (from, to) = (1u64 << action.from, 1u64 << action.to);
+        let mut next = self.clone();
+        match self.player {
+            Team::One => {
+                next.white = (self.white & !from) | to;
+                next.black &= !to;
+            }
+            Team::Two => {
+                next.black = (self.black & !from) | to;
+                next.white &= !to;
+            }
+        }
+        next.player = self.player.opponent();
+        next.plies += 1;
+        next
+    }
+
+    /// A team wins by reaching the opposite rank or capturing all opposing
+    /// pawns. A team that cannot move, which is rare, loses.
+    fn game_result(&self) -> Option<GameResult<Team>> {
+        if self.white & RANK_8 != 0 || self.black == 0 {
+            Some(GameResult::Win(Team::One))
+        } else if self.black & RANK_1 != 0 || self.white == 0 {
+            Some(GameResult::Win(Team::Two))
+        } else if !self.has_actions() {
+            Some(GameResult::Win(self.player.opponent()))
+        } else {
+            None
+        }
+    }
+}
+
+use ahash::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// randomly generated numbers
+const RANDOM_STATE: RandomState = RandomState::with_seeds(
+    0x9b05688c2b3e6c1f,
+    0x5be0cd19137e2179,
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+);
+
+/// randomly generated numbers, independent of [RANDOM_STATE]
+const AUDIT_STATE: RandomState = RandomState::with_seeds(
+    0x3c79ac492ba7b653,
+    0x1c69b3f74ac4ae35,
+    0xf39cc0605cedc834,
+    0x1082276bf3a27251,
+);
+
+impl BTState {
+    /// The ply counter is not hashed, so transpositions hash equally.
+    #[inline]
+    fn hash_with(&self, random_state: &RandomState) -> u64 {
+        let mut x = random_state.build_hasher();
+        x.write_u64(self.white);
+        x.write_u64(self.black);
+        x.write_u8(self.player as u8);
+        x.finish()
+    }
+}
+
+impl cachewing::TranspositionHash for BTState {
+    #[inline]
+    fn hash(&self) -> u64 {
+        self.hash_with(&RANDOM_STATE)
+    }
+}
+
+impl glasswing::perft::AuditHash for BTState {
+    #[inline]
+    fn audit_hash(&self) -> u64 {
+        self.hash_with(&AUDIT_STATE)
+    }
+}
+
+/// Scores the pawn count and how far the pawns have advanced.
+///
+/// Decisive results are scored with [win_in] and [loss_in].
+pub struct BTHeuristic;
+
+impl BTHeuristic {
+    const PAWN: i32 = 10;
+    const ADVANCEMENT: i32 = 1;
+
+    fn material(state: &BTState, team: Team) -> i32 {
+        let mut pawns = state.pieces(team);
+        let mut eval = 0;
+        while pawns != 0 {
+            let rank = pawns.trailing_zeros() as i32 / 8;
+            pawns &= pawns - 1;
+            let advanced = match team {
+                Team::One => rank,
+                Team::Two => 7 - rank,
+            };
+            eval += Self::PAWN + Self::ADVANCEMENT * advanced * advanced;
+        }
+        eval
+    }
+}
+
+impl Evaluator<Breakthrough> for BTHeuristic {
+    fn evaluate_for(&mut self, state: &BTState, team: &Team) -> i32 {
+        match state.game_result() {
+            Some(GameResult::Win(winner)) if winner == *team => win_in(state.plies),
+            Some(GameResult::Win(_)) => loss_in(state.plies),
+            Some(GameResult::Draw) => 0,
+            None => Self::material(state, *team) - Self::material(state, team.opponent()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glasswing::agents::{MaximisingAgent, NegaMax, TieBreak};
+    use glasswing::perft::perft;
+    use glasswing::train::MatchSeries;
+
+    #[test]
+    fn perft_from_initial_position() {
+        // computed with a separate implementation that moves pawns between
+        // sets of coordinates
+        let expected = [22, 484, 11132, 256036, 6182818];
+        let root = Breakthrough::initial_state();
+        for (depth, &nodes) in (1..).zip(expected.iter()) {
+            assert_eq!(
+                perft::<Breakthrough>(&root, depth),
+                nodes,
+                "depth {}",
+                depth
+            );
+        }
+    }
+
+    #[test]
+    fn captures_are_diagonal() {
+        // a white pawn on d4 facing black pawns on c5, d5 and e5
+        let state = BTState::new(1 << 27, (1 << 34) | (1 << 35) | (1 << 36), Team::One);
+        let mut targets: Vec<_> = state.actions().iter().map(|action| action.to()).collect();
+        targets.sort();
+        assert_eq!(targets, [34, 36]);
+
+        let next = state.apply_action(&BTAction::new(27, 36));
+        assert_eq!(next.pieces(Team::Two), (1 << 34) | (1 << 35));
+    }
+
+    #[test]
+    fn reaching_the_last_rank_wins() {
+        let state = BTState::new(1 << 49, 1 << 7, Team::One);
+        let next = state.apply_action(&BTAction::new(49, 57));
+        assert_eq!(next.game_result(), Some(GameResult::Win(Team::One)));
+        assert!(next.actions().is_empty());
+    }
+
+    #[test]
+    fn deeper_search_wins_series() {
+        let agent = |depth| {
+            move |seed| {
+                MaximisingAgent::new(NegaMax::new(depth, BTHeuristic))
+                    .with_tie_break(TieBreak::Random(seed))
+            }
+        };
+        let result = MatchSeries::new(agent(3), agent(2), 10).with_seed(7).run();
+        let total = result.total();
+        assert_eq!(result.errors, 0);
+        assert!(total.wins > total.losses, "{:?}", total);
+    }
+}
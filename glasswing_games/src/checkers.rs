@@ -0,0 +1,502 @@
+use glasswing::agents::scores::{loss_in, win_in};
+use glasswing::agents::Evaluator;
+use glasswing::core::{Game, GameResult, GwState, ProgressState, Team};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+/// Number of consecutive plies without a capture or a man moving after
+/// which a game should be adjudicated as a draw, see
+/// [Pit::with_draw_after_no_progress](glasswing::train::Pit::with_draw_after_no_progress).
+pub const NO_PROGRESS_LIMIT: usize = 40;
+
+/// English draughts on an 8x8 board. Captures are compulsory and a sequence
+/// of jumps is a single action. A man that reaches the far row is crowned
+/// and its move ends there.
+///
+/// The 32 dark squares are numbered 0 to 31, row by row from the top. Black
+/// ([Team::One]) starts on squares 0 to 11, moves first and moves down the
+/// board; white starts on squares 20 to 31.
+#[derive(Clone, Debug)]
+pub struct Checkers;
+
+impl Game for Checkers {
+    type State = CheckersState;
+    type Action = CheckersAction;
+    type Team = Team;
+    type GameResult = GameResult<Self::Team>;
+    type EvalType = i32;
+
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("Checkers")
+    }
+
+    fn initial_state() -> Self::State {
+        CheckersState {
+            black: 0x0000_0FFF,
+            white: 0xFFF0_0000,
+            kings: 0,
+            player: Team::One,
+            plies: 0,
+        }
+    }
+}
+
+/// Moves a piece from one square to another, capturing every piece in
+/// `captured` along the way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CheckersAction {
+    from: u8,
+    to: u8,
+    captured: u32,
+}
+
+impl CheckersAction {
+    pub fn from(&self) -> usize {
+        self.from as usize
+    }
+
+    pub fn to(&self) -> usize {
+        self.to as usize
+    }
+
+    /// The captured pieces as a set of squares.
+    pub fn captured(&self) -> u32 {
+        self.captured
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.captured != 0
+    }
+}
+
+impl Display for CheckersAction {
+    /// Uses the customary numbering from 1 to 32, e.g. `11-15` or `15x24`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let separator = if self.is_capture() { 'x' } else { '-' };
+        write!(f, "{}{}{}", self.from + 1, separator, self.to + 1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckersState {
+    black: u32,
+    white: u32,
+    kings: u32,
+    player: Team,
+    plies: u32,
+}
+
+const DIRECTIONS: [(i32, i32); 4] = [(1, -1), (1, 1), (-1, -1), (-1, 1)];
+
+#[inline]
+fn coordinates(square: u8) -> (i32, i32) {
+    let row = square as i32 / 4;
+    let col = 2 * (square as i32 % 4) + (row + 1) % 2;
+    (row, col)
+}
+
+#[inline]
+fn square(row: i32, col: i32) -> Option<u8> {
+    ((0..8).contains(&row) && (0..8).contains(&col) && (row + col) % 2 == 1)
+        .then(|| (row * 4 + col / 2) as u8)
+}
+
+#[inline]
+fn bit(square: u8) -> u32 {
+    1 << square
+}
+
+impl CheckersState {
+    /// Builds a position from sets of squares. `kings` must be a subset of
+    /// the occupied squares.
+    ///
+    /// # Panics
+    /// Panics if a square is occupied by both teams.
+    pub fn new(black: u32, white: u32, kings: u32, player: Team) -> Self {
+        assert_eq!(black & white, 0, "Square occupied by both teams");
+        CheckersState {
+            black,
+            white,
+            kings: kings & (black | white),
+            player,
+            plies: 0,
+        }
+    }
+
+    pub fn pieces(&self, team: Team) -> u32 {
+        match team {
+            Team::One => self.black,
+            Team::Two => self.white,
+        }
+    }
+
+    pub fn kings(&self) -> u32 {
+        self.kings
+    }
+
+    /// Number of plies played since this state was created.
+    pub fn plies(&self) -> u32 {
+        self.plies
+    }
+
+    #[inline]
+    fn occupied(&self) -> u32 {
+        self.black | self.white
+    }
+
+    /// The row on which men of `team` are crowned.
+    #[inline]
+    fn crowning_row(team: Team) -> i32 {
+        match team {
+            Team::One => 7,
+            Team::Two => 0,
+        }
+    }
+
+    #[inline]
+    fn directions(&self, from: u8) -> &'static [(i32, i32)] {
+        if self.kings & bit(from) != 0 {
+            &DIRECTIONS
+        } else {
+            match self.player {
+                Team::One => &DIRECTIONS[..2],
+                Team::Two => &DIRECTIONS[2..],
+            }
+        }
+    }
+
+    fn push_actions(&self, buf: &mut Vec<CheckersAction>) {
+        let own = self.pieces(self.player);
+        let mut pieces = own;
+        while pieces != 0 {
+            let from = pieces.trailing_zeros() as u8;
+            pieces &= pieces - 1;
+            self.push_captures(from, from, 0, buf);
+        }
+        if !buf.is_empty() {
+            return;
+        }
+
+        let mut pieces = own;
+        while pieces != 0 {
+            let from = pieces.trailing_zeros() as u8;
+            pieces &= pieces - 1;
+            let (row, col) = coordinates(from);
+            for &(dr, dc) in self.directions(from) {
+                if let Some(to) = square(row + dr, col + dc) {
+                    if self.occupied() & bit(to) == 0 {
+                        buf.push(CheckersAction {
+                            from,
+                            to,
+                            captured: 0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Extends the jump sequence of the piece that started on `origin` and
+    /// is now on `at`, having captured `captured` so far.
+    fn push_captures(&self, origin: u8, at: u8, captured: u32, buf: &mut Vec<CheckersAction>) {
+        let opponents = self.pieces(self.player.opponent());
+        // the moving piece has left its origin, captured pieces stay on the
+        // board until the move is complete
+        let occupied = self.occupied() & !bit(origin);
+        let (row, col) = coordinates(at);
+        let mut extended = false;
+        for &(dr, dc) in self.directions(origin) {
+            let (Some(over), Some(to)) = (
+                square(row + dr, col + dc),
+                square(row + 2 * dr, col + 2 * dc),
+            ) else {
+                continue;
+            };
+            if opponents & bit(over) == 0 || captured & bit(over) != 0 || occupied & bit(to) != 0 {
+                continue;
+            }
+            extended = true;
+            let captured = captured | bit(over);
+            let crowned = self.kings & bit(origin) == 0
+                && coordinates(to).0 == Self::crowning_row(self.player);
+            if crowned {
+                Self::push_capture(
+                    CheckersAction {
+                        from: origin,
+                        to,
+                        captured,
+                    },
+                    buf,
+                );
+            } else {
+                self.push_captures(origin, to, captured, buf);
+            }
+        }
+        if !extended && captured != 0 {
+            Self::push_capture(
+                CheckersAction {
+                    from: origin,
+                    to: at,
+                    captured,
+                },
+                buf,
+            );
+        }
+    }
+
+    /// Pushes a capture unless it is already in `buf`. A king can capture
+    /// the same pieces and land on the same square along different paths,
+    /// e.g. around a ring of four men in either direction.
+    fn push_capture(action: CheckersAction, buf: &mut Vec<CheckersAction>) {
+        if !buf.contains(&action) {
+            buf.push(action);
+        }
+    }
+}
+
+impl Display for CheckersState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in 0..8 {
+            for col in 0..8 {
+                let c = match square(row, col) {
+                    None => ' ',
+                    Some(sq) if self.black & bit(sq) != 0 => {
+                        if self.kings & bit(sq) != 0 {
+                            'B'
+                        } else {
+                            'b'
+                        }
+                    }
+                    Some(sq) if self.white & bit(sq) != 0 => {
+                        if self.kings & bit(sq) != 0 {
+                            'W'
+                        } else {
+                            'w'
+                        }
+                    }
+                    Some(_) => '·',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl GwState<Checkers> for CheckersState {
+    type ActionIter = Vec<CheckersAction>;
+
+    fn actions(&self) -> Self::ActionIter {
+        let mut buf = Vec::new();
+        self.push_actions(&mut buf);
+        buf
+    }
+
+    #[inline]
+    fn actions_into(&self, buf: &mut Vec<CheckersAction>) {
+        buf.clear();
+        self.push_actions(buf);
+    }
+
+    #[inline]
+    fn team_to_move(&self) -> Team {
+        self.player
+    }
+
+    /// Assumes the action is legal in this state.
+    fn apply_action(&self, action: &CheckersAction) -> Self {
+        let (from, to) = (bit(action.from), bit(action.to));
+        let mut next = self.clone();
+        let (own, opponents) = match self.player {
+            Team::One => (&mut next.black, &mut next.white),
+            Team::Two => (&mut next.white, &mut next.black),
+        };
+        *own = (*own & !from) | to;
+        *opponents &= !action.captured;
+
+        let was_king = self.kings & from != 0;
+        next.kings &= !(from | action.captured);
+        if was_king || coordinates(action.to).0 == Self::crowning_row(self.player) {
+            next.kings |= to;
+        }
+        next.player = self.player.opponent();
+        next.plies += 1;
+        next
+    }
+
+    /// A team that cannot move, because it has no pieces left or all of them
+    /// are blocked, loses.
+    fn game_result(&self) -> Option<GameResult<Team>> {
+        let mut buf = Vec::new();
+        self.push_actions(&mut buf);
+        buf.is_empty()
+            .then(|| GameResult::Win(self.player.opponent()))
+    }
+}
+
+impl ProgressState<Checkers> for CheckersState {
+    /// Captures and moves of men are irreversible.
+    fn is_progress(&self, action: &CheckersAction, _next: &Self) -> bool {
+        action.is_capture() || self.kings & bit(action.from) == 0
+    }
+}
+
+use ahash::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// randomly generated numbers
+const RANDOM_STATE: RandomState = RandomState::with_seeds(
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+);
+
+/// randomly generated numbers, independent of [RANDOM_STATE]
+const AUDIT_STATE: RandomState = RandomState::with_seeds(
+    0xe7037ed1a0b428db,
+    0x8ebc6af09c88c6e3,
+    0x589965cc75374cc3,
+    0x1d8e4e27c47d124f,
+);
+
+impl CheckersState {
+    /// The ply counter is not hashed, so transpositions hash equally.
+    #[inline]
+    fn hash_with(&self, random_state: &RandomState) -> u64 {
+        let mut x = random_state.build_hasher();
+        x.write_u32(self.black);
+        x.write_u32(self.white);
+        x.write_u32(self.kings);
+        x.write_u8(self.player as u8);
+        x.finish()
+    }
+}
+
+impl cachewing::TranspositionHash for CheckersState {
+    #[inline]
+    fn hash(&self) -> u64 {
+        self.hash_with(&RANDOM_STATE)
+    }
+}
+
+impl glasswing::perft::AuditHash for CheckersState {
+    #[inline]
+    fn audit_hash(&self) -> u64 {
+        self.hash_with(&AUDIT_STATE)
+    }
+}
+
+/// Scores material, with kings worth more than men, and rewards men for
+/// advancing towards their crowning row.
+///
+/// Decisive results are scored with [win_in] and [loss_in].
+pub struct CheckersHeuristic;
+
+impl CheckersHeuristic {
+    const MAN: i32 = 100;
+    const KING: i32 = 160;
+    const ADVANCEMENT: i32 = 3;
+
+    fn material(state: &CheckersState, team: Team) -> i32 {
+        let pieces = state.pieces(team);
+        let kings = (pieces & state.kings).count_ones() as i32;
+        let mut men = pieces & !state.kings;
+        let mut eval = kings * Self::KING;
+        while men != 0 {
+            let square = men.trailing_zeros() as u8;
+            men &= men - 1;
+            let row = coordinates(square).0;
+            let advanced = match team {
+                Team::One => row,
+                Team::Two => 7 - row,
+            };
+            eval += Self::MAN + Self::ADVANCEMENT * advanced;
+        }
+        eval
+    }
+}
+
+impl Evaluator<Checkers> for CheckersHeuristic {
+    fn evaluate_for(&mut self, state: &CheckersState, team: &Team) -> i32 {
+        match state.game_result() {
+            Some(GameResult::Win(winner)) if winner == *team => win_in(state.plies),
+            Some(GameResult::Win(_)) => loss_in(state.plies),
+            Some(GameResult::Draw) => 0,
+            None => Self::material(state, *team) - Self::material(state, team.opponent()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glasswing::perft::perft;
+
+    fn squares(squares: &[u8]) -> u32 {
+        squares.iter().map(|&sq| bit(sq)).fold(0, |set, b| set | b)
+    }
+
+    #[test]
+    fn perft_from_initial_position() {
+        let expected = [7, 49, 302, 1469, 7361, 36768, 179740, 845931];
+        let root = Checkers::initial_state();
+        for (depth, &nodes) in (1..).zip(expected.iter()) {
+            assert_eq!(perft::<Checkers>(&root, depth), nodes, "depth {}", depth);
+        }
+    }
+
+    #[test]
+    fn captures_are_forced() {
+        let state = CheckersState::new(squares(&[0, 9]), squares(&[13]), 0, Team::One);
+        let actions = state.actions();
+        assert_eq!(actions.len(), 1);
+        assert_eq!((actions[0].from(), actions[0].to()), (9, 16));
+        assert_eq!(actions[0].captured(), bit(13));
+        assert_eq!(actions[0].to_string(), "10x17");
+    }
+
+    #[test]
+    fn multi_jump_is_one_action() {
+        let state = CheckersState::new(squares(&[1]), squares(&[5, 13, 21]), 0, Team::One);
+        let actions = state.actions();
+        assert_eq!(actions.len(), 1);
+        assert_eq!((actions[0].from(), actions[0].to()), (1, 24));
+        assert_eq!(actions[0].captured(), squares(&[5, 13, 21]));
+
+        let next = state.apply_action(&actions[0]);
+        assert_eq!(next.pieces(Team::Two), 0);
+        assert_eq!(next.pieces(Team::One), bit(24));
+        assert_eq!(next.game_result(), Some(GameResult::Win(Team::One)));
+    }
+
+    #[test]
+    fn men_are_crowned_on_the_far_row() {
+        let state = CheckersState::new(squares(&[25]), squares(&[2]), 0, Team::One);
+        let crowning = state
+            .actions()
+            .into_iter()
+            .find(|action| action.to() == 29)
+            .unwrap();
+        let next = state.apply_action(&crowning);
+        assert_eq!(next.kings(), bit(29));
+
+        // the king moves backwards on its next turn
+        let king = CheckersState::new(next.pieces(Team::One), 0, next.kings(), Team::One);
+        let mut targets: Vec<_> = king.actions().iter().map(|action| action.to()).collect();
+        targets.sort();
+        assert_eq!(targets, [24, 25]);
+    }
+
+    #[test]
+    fn crowning_ends_the_move() {
+        // after crowning on 30, the new king could jump 26 but may not
+        let state = CheckersState::new(squares(&[21]), squares(&[25, 26]), 0, Team::One);
+        let actions = state.actions();
+        assert_eq!(actions.len(), 1);
+        assert_eq!((actions[0].from(), actions[0].to()), (21, 30));
+        assert_eq!(actions[0].captured(), bit(25));
+        assert_eq!(state.apply_action(&actions[0]).kings(), bit(30));
+    }
+}
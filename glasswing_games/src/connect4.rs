@@ -1,5 +1,7 @@
+use glasswing::agents::scores::{loss_in, win_in};
 use glasswing::agents::Evaluator;
-use glasswing::core::{Game, GameResult, GwState, Team};
+use glasswing::core::{DiffableState, Game, GameResult, GwState, Team};
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::ops::Index;
 
@@ -13,6 +15,12 @@ impl Game for Connect4 {
     type GameResult = GameResult<Self::Team>;
     type EvalType = i32;
 
+    const MAX_ACTIONS: Option<usize> = Some(7);
+
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("Connect4")
+    }
+
     fn initial_state() -> Self::State {
         C4State {
             board: [Column {
@@ -89,20 +97,48 @@ impl GwState<Connect4> for C4State {
         C4ActionIter::new(self.clone())
     }
 
+    #[inline]
+    fn actions_into(&self, buf: &mut Vec<C4Action>) {
+        buf.clear();
+        if self.is_terminal() {
+            return;
+        }
+        for (column, col) in self.board.iter().enumerate() {
+            if col.height < 6 {
+                buf.push(C4Action {
+                    column: column as u8,
+                });
+            }
+        }
+    }
+
+    #[inline]
+    fn count_actions(&self) -> usize {
+        if self.is_terminal() {
+            return 0;
+        }
+        self.board.iter().filter(|col| col.height < 6).count()
+    }
+
     #[inline]
     fn team_to_move(&self) -> Team {
         self.player
     }
 
+    /// Central columns first, as they take part in the most lines.
+    #[inline]
+    fn static_order_key(&self, action: &C4Action) -> i32 {
+        3 - (action.column as i32 - 3).abs()
+    }
+
     /// assume that the action is valid, therefore this is not a terminal game state.
     #[inline]
-    #[must_use]
     fn apply_action(&self, action: &C4Action) -> Self {
         assert!(!self.is_terminal()); // applying an action to a terminal state is undefined.
 
         //// Step 1: generate new board ////
         let idx = action.column as usize;
-        let mut new_board = self.board.clone();
+        let mut new_board = self.board;
 
         let new_col = &mut new_board[idx];
         let old_height = new_col.height;
@@ -157,8 +193,8 @@ impl GwState<Connect4> for C4State {
 
         // check for horizontal win
         let mut bits = 0;
-        for i in 0..7 {
-            bits |= (proxy[i] >> old_height) & 0x1;
+        for column in proxy {
+            bits |= (column >> old_height) & 0x1;
             bits <<= 1;
         }
 
@@ -210,11 +246,43 @@ impl GwState<Connect4> for C4State {
 
     #[inline]
     fn game_result(&self) -> Option<GameResult<Team>> {
-        self.game_result.clone()
+        self.game_result
     }
 }
 
 impl C4State {
+    /// Builds a state by playing the given columns (`'0'` to `'6'`) from the
+    /// initial position, e.g. `"3342"`.
+    ///
+    /// # Panics
+    /// Panics if a character is not a column, or the move is not legal.
+    pub fn from_moves(moves: &str) -> Self {
+        let mut state = Connect4::initial_state();
+        for c in moves.chars() {
+            let column = match c.to_digit(10) {
+                Some(column) if column < 7 => column as u8,
+                _ => panic!("Invalid column in move string for Connect4: {}", c),
+            };
+            assert!(
+                !state.is_terminal() && state.board[column as usize].height < 6,
+                "Illegal move in move string for Connect4: {}",
+                c
+            );
+            state = state.apply_action(&C4Action { column });
+        }
+        state
+    }
+
+    /// The state reflected left to right, the only symmetry of the board.
+    pub fn mirrored(&self) -> Self {
+        let mut board = self.board;
+        board.reverse();
+        C4State {
+            board,
+            ..self.clone()
+        }
+    }
+
     pub fn from_pretty(pretty: &str, game_result: Option<GameResult<Team>>) -> Self {
         let mut new_state = Self {
             board: [Column {
@@ -284,7 +352,7 @@ pub fn check_diagonals(board: &[u8; 7], x: u8, y: u8) -> bool {
         0, board[6], board[5], board[4], board[3], board[2], board[1], board[0],
     ];
     let board = u64::from_be_bytes(board);
-    return check_up_diagonal(board, x, y) || check_down_diagonal(board, x, y);
+    check_up_diagonal(board, x, y) || check_down_diagonal(board, x, y)
 }
 
 #[inline]
@@ -303,7 +371,7 @@ pub fn check_up_diagonal(board: u64, x: u8, y: u8) -> bool {
             count = 0;
         }
     }
-    return count >= 4;
+    count >= 4
 }
 
 #[inline]
@@ -322,7 +390,7 @@ pub fn check_down_diagonal(board: u64, x: u8, y: u8) -> bool {
             count = 0;
         }
     }
-    return count >= 4;
+    count >= 4
 }
 
 const DP: u64 = 0x8040201008040201;
@@ -418,29 +486,61 @@ impl Iterator for C4ActionIter {
                 return Some(action);
             }
         }
-        return None;
+        None
     }
 }
 
+/// The tile added by a single move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct C4Delta {
+    pub column: u8,
+    pub team: Team,
+}
+
+impl DiffableState<Connect4> for C4State {
+    type Delta = C4Delta;
+
+    fn diff(&self, prev: &Self) -> C4Delta {
+        let mut changed = (0..7).filter(|&i| self.board[i].height != prev.board[i].height);
+        let column = changed.next();
+        assert!(
+            column.is_some_and(|i| self.board[i].height == prev.board[i].height + 1)
+                && changed.next().is_none(),
+            "states do not differ by a single move"
+        );
+        C4Delta {
+            column: column.unwrap() as u8,
+            team: prev.player,
+        }
+    }
+
+    fn apply_delta(&self, delta: &C4Delta) -> Self {
+        debug_assert!(delta.team == self.player, "delta is for the other team");
+        self.apply_action(&C4Action::new(delta.column))
+    }
+}
+
+/// Scores decisive results with [win_in] and [loss_in], counting the plies
+/// since the start of the game.
 pub struct C4Heuristic;
 
 impl Evaluator<Connect4> for C4Heuristic {
     #[inline]
     fn evaluate_for(&mut self, state: &C4State, team: &Team) -> i32 {
-        return match state.game_result {
+        match state.game_result {
             Some(ref x) => match x {
                 GameResult::Win(winner) => {
-                    let turn = state.board.iter().map(|x| x.height).sum::<u8>() as i32;
+                    let turn = state.board.iter().map(|x| x.height).sum::<u8>() as u32;
                     if *winner == *team {
-                        1000 - turn
+                        win_in(turn)
                     } else {
-                        -1000 + turn
+                        loss_in(turn)
                     }
                 }
                 GameResult::Draw => 0,
             },
             None => 0,
-        };
+        }
     }
 }
 
@@ -455,9 +555,17 @@ const RANDOM_STATE: RandomState = RandomState::with_seeds(
     0x92b31779a806a6ba,
 );
 
-impl cachewing::TranspositionHash for C4State {
+/// randomly generated numbers, independent of [RANDOM_STATE]
+const AUDIT_STATE: RandomState = RandomState::with_seeds(
+    0x2545f4914f6cdd1d,
+    0x7a2c1f6b3e5d9a08,
+    0xd1b54a32d192ed03,
+    0x8cb92ba72f3d8dd7,
+);
+
+impl C4State {
     #[inline]
-    fn hash(&self) -> u64 {
+    fn hash_with(&self, random_state: &RandomState) -> u64 {
         let board1 = u64::from_be_bytes([
             self.board[0].one,
             self.board[1].one,
@@ -479,9 +587,382 @@ impl cachewing::TranspositionHash for C4State {
             0,
         ]);
 
-        let mut x = RANDOM_STATE.build_hasher();
+        let mut x = random_state.build_hasher();
         x.write_u64(board1);
         x.write_u64(board2);
-        return x.finish();
+        x.finish()
+    }
+}
+
+impl cachewing::TranspositionHash for C4State {
+    #[inline]
+    fn hash(&self) -> u64 {
+        self.hash_with(&RANDOM_STATE)
+    }
+}
+
+impl glasswing::perft::AuditHash for C4State {
+    #[inline]
+    fn audit_hash(&self) -> u64 {
+        self.hash_with(&AUDIT_STATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cachewing::TranspositionHash;
+    use glasswing::agents::{
+        random_states, Agent, MaximisingAgent, NegaMax, RandomAgent, SearchCounters, SearchError,
+        SimpleAgent,
+    };
+    use glasswing::analysis::{blunder_report, OpeningTree};
+    use glasswing::testing::check_symmetry;
+    use glasswing::train::{
+        find_equalizing_budget, Adjudication, MatchSeries, Odds, OpeningSuite, Pit, TurnRecord,
+    };
+    use glasswing::variants::{InstrumentedEvaluator, InstrumentedState};
+    use rand::rngs::StdRng;
+    use rand::seq::IteratorRandom;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    fn history(moves: &str) -> Vec<TurnRecord<Connect4>> {
+        let mut state = Connect4::initial_state();
+        moves
+            .chars()
+            .enumerate()
+            .map(|(ply, c)| {
+                let action = C4Action::new(c.to_digit(10).unwrap() as u8);
+                let post = state.apply_action(&action);
+                let turn = TurnRecord {
+                    ply,
+                    team: state.team_to_move(),
+                    agent: ply % 2,
+                    action,
+                    pre: state.clone(),
+                    post: post.clone(),
+                    elapsed: Duration::ZERO,
+                    reported: None,
+                    budget: None,
+                };
+                state = post;
+                turn
+            })
+            .collect()
+    }
+
+    /// The node budget at which a depth 6 search scores even against a
+    /// search of the given depth, over every two ply opening.
+    fn equalizing_nodes(reference_depth: u32) -> (u64, bool) {
+        let openings = (0..49)
+            .map(|i| C4State::from_moves(&format!("{}{}", i / 7, i % 7)))
+            .collect::<Vec<_>>();
+        let search = find_equalizing_budget(
+            |nodes| {
+                MatchSeries::new(
+                    |_| MaximisingAgent::new(NegaMax::new(6, C4Heuristic)),
+                    |_| MaximisingAgent::new(NegaMax::new(reference_depth, C4Heuristic)),
+                    98,
+                )
+                .with_openings(OpeningSuite::new(openings.clone()))
+                .with_odds(Odds::Nodes {
+                    a: nodes,
+                    b: u64::MAX,
+                })
+                .run()
+            },
+            1..=100_000,
+            0.5,
+            0.1,
+            20,
+        );
+        (search.budget, search.converged)
+    }
+
+    #[test]
+    fn node_odds_grow_with_the_strength_of_the_reference() {
+        let (weak, weak_converged) = equalizing_nodes(1);
+        let (strong, strong_converged) = equalizing_nodes(3);
+        assert!(weak_converged && strong_converged);
+        assert!(
+            weak < strong,
+            "{} against depth 1, {} against depth 3",
+            weak,
+            strong
+        );
+    }
+
+    #[test]
+    fn evaluators_are_invariant_under_mirroring() {
+        let states = random_states::<Connect4, _>(30, &mut StdRng::seed_from_u64(5));
+        let mirror = [C4State::mirrored as fn(&C4State) -> C4State];
+        let violations = check_symmetry(&mut C4Heuristic, &mirror, &states, 0.0);
+        assert!(violations.is_empty(), "{:?}", violations.first());
+
+        let mut search = NegaMax::new(4, C4Heuristic);
+        let violations = check_symmetry(&mut search, &mirror, &states, 0.0);
+        assert!(violations.is_empty(), "{:?}", violations.first());
+    }
+
+    #[test]
+    fn blunder_report_finds_planted_blunder() {
+        // the second team fails to block the column at ply 5
+        let history = history("010106");
+        let mut reference = NegaMax::new(3, C4Heuristic);
+        let report = blunder_report(&history, &mut reference, 1000.0);
+        assert!(report.errors.is_empty());
+        assert_eq!(report.blunders.len(), 1);
+        assert_eq!(report.blunders[0].ply, 5);
+        assert_eq!(report.blunders[0].played, C4Action::new(6));
+        assert_eq!(report.blunders[0].best, C4Action::new(0));
+    }
+
+    #[test]
+    fn blunder_report_skips_aborted_positions() {
+        let history = history("010106");
+        let mut reference = NegaMax::new(6, C4Heuristic).with_hard_node_cap(50);
+        let report = blunder_report(&history, &mut reference, 1000.0);
+        assert!(report.blunders.is_empty());
+        assert_eq!(report.errors.len(), history.len());
+        assert_eq!(
+            report.errors[0],
+            (0, SearchError::NodeCapExceeded { nodes: 50 })
+        );
+    }
+
+    #[test]
+    fn first_matching_adjudicator_ends_the_game() {
+        // both agents play the leftmost open column, filling column 0 first
+        let mut pit = Pit::new(
+            SimpleAgent::<Connect4>::new(),
+            SimpleAgent::new(),
+            Connect4::initial_state(),
+        )
+        .with_adjudicator(|_, _| None)
+        .with_adjudicator(|state: &C4State, plies| {
+            (state.board[0].height == 6).then(|| {
+                assert_eq!(plies, 6);
+                (GameResult::Win(Team::One), "column filled".to_string())
+            })
+        })
+        .with_adjudicator(|_, plies| {
+            (plies >= 6).then(|| (GameResult::Draw, "too late".to_string()))
+        });
+
+        let plies = pit.by_ref().count();
+        assert_eq!(plies, 6);
+        assert_eq!(
+            pit.adjudication(),
+            Some(Adjudication::Custom("column filled".to_string()))
+        );
+        assert_eq!(pit.game_result(), Some(GameResult::Win(Team::One)));
+        assert!(!pit.state().is_terminal());
+    }
+
+    /// Scores states like [C4Heuristic], but orders actions by their result
+    /// and then by their distance to the centre. Winning and central actions
+    /// come first if `best_first` is set and last otherwise.
+    struct Ordering {
+        best_first: bool,
+    }
+
+    impl Evaluator<Connect4> for Ordering {
+        fn evaluate_for(&mut self, state: &C4State, team: &Team) -> i32 {
+            C4Heuristic.evaluate_for(state, team)
+        }
+
+        fn evaluate_action_for(&mut self, state: &C4State, action: &C4Action, team: &Team) -> i32 {
+            let eval = self.evaluate_for(&state.apply_action(action), team);
+            let centrality = 3 - (action.column as i32 - 3).abs();
+            let key = if eval == 0 { centrality } else { eval };
+            if self.best_first {
+                key
+            } else {
+                -key
+            }
+        }
+    }
+
+    #[test]
+    fn search_counters_reflect_move_ordering() {
+        let states = random_states::<Connect4, _>(6, &mut StdRng::seed_from_u64(11))
+            .into_iter()
+            .filter(|state| !state.is_terminal())
+            .step_by(5)
+            .collect::<Vec<C4State>>();
+        let (mut good, mut bad) = (SearchCounters::default(), SearchCounters::default());
+        for state in states {
+            let search = |best_first| {
+                let mut search = NegaMax::new(6, Ordering { best_first });
+                let eval = search.negamax(&state, 6, i32::MIN, i32::MAX);
+                let counters = *search.counters();
+                assert!(counters.leaves <= counters.nodes, "{:?}", counters);
+                assert!(counters.cutoffs < counters.nodes - counters.leaves);
+                (eval, counters)
+            };
+            let (good_eval, good_counters) = search(true);
+            let (bad_eval, bad_counters) = search(false);
+            assert_eq!(good_eval, bad_eval, "{}", state);
+            for (total, counters) in [(&mut good, good_counters), (&mut bad, bad_counters)] {
+                total.nodes += counters.nodes;
+                total.leaves += counters.leaves;
+                total.cutoffs += counters.cutoffs;
+            }
+        }
+
+        // better ordering prunes more per leaf and so visits fewer nodes
+        assert!(good.nodes < bad.nodes, "{:?} {:?}", good, bad);
+        assert!(
+            good.cutoffs * bad.leaves > bad.cutoffs * good.leaves,
+            "{:?} {:?}",
+            good,
+            bad
+        );
+    }
+
+    #[test]
+    fn deltas_round_trip_from_the_initial_state() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..20 {
+            let mut state = Connect4::initial_state();
+            let mut replica = state.clone();
+            while let Some(action) = state.actions().choose(&mut rng) {
+                let next = state.apply_action(&action);
+                let delta = next.diff(&state);
+                assert_eq!(delta.column, action.column);
+                assert_eq!(delta.team, state.team_to_move());
+                replica = replica.apply_delta(&delta);
+                state = next;
+                assert_eq!(replica.hash(), state.hash(), "{}", state);
+                assert_eq!(replica.game_result(), state.game_result());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "states do not differ by a single move")]
+    fn diff_of_unrelated_states_panics() {
+        C4State::from_moves("33").diff(&Connect4::initial_state());
+    }
+
+    #[test]
+    fn opening_tree_counts_seeded_self_play() {
+        const GAMES: usize = 60;
+        const MAX_PLY: usize = 3;
+        let games = (0..GAMES as u64)
+            .map(|seed| {
+                let agent = |seed| RandomAgent::new(StdRng::seed_from_u64(seed));
+                let mut pit = Pit::new(
+                    agent(seed),
+                    agent(seed + GAMES as u64),
+                    Connect4::initial_state(),
+                );
+                let history = pit.by_ref().collect::<Vec<TurnRecord<Connect4>>>();
+                (history, pit.game_result())
+            })
+            .collect::<Vec<_>>();
+        let tree = OpeningTree::from_games(
+            games
+                .iter()
+                .map(|(history, result)| (history.as_slice(), result.as_ref())),
+            MAX_PLY,
+        );
+
+        let root = Connect4::initial_state();
+        assert_eq!(tree.games(), GAMES);
+        assert_eq!(tree.games_through(&root), GAMES);
+        let children = tree.children(&root);
+        assert!(children.len() > 1);
+        let total = children.iter().map(|m| m.wdl.total()).sum::<usize>();
+        assert_eq!(total, GAMES);
+        for m in children {
+            let after = root.apply_action(&m.action);
+            assert_eq!(tree.games_through(&after), m.wdl.total());
+        }
+
+        // the suite holds every position reached after the opening, most
+        // frequent first
+        let suite = tree.to_suite(1);
+        let replayed = OpeningSuite::<Connect4>::from_lines(games.iter().map(|(history, _)| {
+            history
+                .iter()
+                .take(MAX_PLY)
+                .map(|turn| turn.action.clone())
+                .collect::<Vec<C4Action>>()
+        }))
+        .unwrap();
+        let hashes = |positions: &[C4State]| {
+            positions
+                .iter()
+                .map(|state| state.hash())
+                .collect::<HashSet<u64>>()
+        };
+        assert_eq!(hashes(suite.positions()), hashes(replayed.positions()));
+        let counts = suite
+            .positions()
+            .iter()
+            .map(|state| tree.games_through(state))
+            .collect::<Vec<usize>>();
+        assert_eq!(counts.iter().sum::<usize>(), GAMES);
+        assert!(counts.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        let frequent = tree.to_suite(2);
+        assert!(frequent.len() < suite.len());
+        assert!(frequent
+            .positions()
+            .iter()
+            .all(|state| tree.games_through(state) >= 2));
+    }
+
+    #[test]
+    fn instrumented_calls_agree_with_search_counters() {
+        for moves in ["", "3344", "0123456"] {
+            let state = InstrumentedState::<Connect4>::new(C4State::from_moves(moves));
+            let mut search = NegaMax::new(5, InstrumentedEvaluator(C4Heuristic));
+            search.negamax(&state, 5, i32::MIN, i32::MAX);
+            let counters = *search.counters();
+            let calls = state.counters().report();
+
+            // actions are generated once per interior node, and applied
+            // once for ordering and once more for each searched child
+            let interior = counters.nodes - counters.leaves;
+            assert_eq!(calls.action_calls, interior, "{}", moves);
+            assert_eq!(
+                calls.apply_calls,
+                calls.actions_generated + counters.nodes - 1,
+                "{}",
+                moves
+            );
+            assert!(calls.terminal_checks >= interior && calls.terminal_checks <= counters.nodes);
+            assert!(calls.average_branching() > 1.0 && calls.average_branching() <= 7.0);
+        }
+    }
+
+    #[test]
+    fn node_cap_aborts_deep_search() {
+        let search = NegaMax::new(10, C4Heuristic).with_hard_node_cap(1000);
+        let mut agent = MaximisingAgent::new(search);
+        let err = agent.select_action(&Connect4::initial_state()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<SearchError>(),
+            Some(&SearchError::NodeCapExceeded { nodes: 1000 })
+        );
+        // the cap counts the nodes of the whole move, not of each action
+        assert_eq!(agent.evaluator().counters().nodes, 1001);
+    }
+
+    #[test]
+    fn node_cap_can_return_best_so_far() {
+        let search = NegaMax::new(10, C4Heuristic)
+            .with_hard_node_cap(1000)
+            .with_return_best_on_cap(true);
+        let mut agent = MaximisingAgent::new(search);
+        let state = Connect4::initial_state();
+        let action = agent.select_action(&state).unwrap();
+        assert!(state.actions().into_iter().any(|a| a == action));
+        assert_eq!(agent.evaluator().counters().nodes, 1001);
+        assert!(agent.evaluator_mut().take_error().is_none());
     }
 }
@@ -0,0 +1,469 @@
+use glasswing::agents::scores::{loss_in, win_in};
+use glasswing::agents::Evaluator;
+use glasswing::core::{Game, GameResult, GwState, Team};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+/// Dots and Boxes on a grid of `W` by `H` boxes. A player who completes a
+/// box claims it and moves again, so turns do not strictly alternate.
+///
+/// Edges are stored in a 128 bit set, which limits the board to 128 edges,
+/// e.g. 7 by 7 boxes.
+#[derive(Clone, Debug)]
+pub struct DotsAndBoxes<const W: usize, const H: usize>;
+
+impl<const W: usize, const H: usize> DotsAndBoxes<W, H> {
+    /// Number of horizontal edges, which are numbered first.
+    pub const HORIZONTAL_EDGES: usize = W * (H + 1);
+    pub const EDGES: usize = W * (H + 1) + (W + 1) * H;
+}
+
+impl<const W: usize, const H: usize> Game for DotsAndBoxes<W, H> {
+    type State = DBState<W, H>;
+    type Action = DBAction;
+    type Team = Team;
+    type GameResult = GameResult<Self::Team>;
+    type EvalType = i32;
+
+    const MAX_ACTIONS: Option<usize> = Some(Self::EDGES);
+
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("DotsAndBoxes<{W}, {H}>"))
+    }
+
+    fn initial_state() -> Self::State {
+        assert!(
+            W > 0 && H > 0 && Self::EDGES <= 128,
+            "Unsupported Dots and Boxes board size: {}x{}",
+            W,
+            H
+        );
+        DBState {
+            edges: 0,
+            boxes: [[None; W]; H],
+            player: Team::One,
+        }
+    }
+}
+
+/// Draws a single edge. Horizontal edges are numbered row by row from the
+/// top, followed by the vertical edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DBAction {
+    edge: u8,
+}
+
+impl DBAction {
+    /// The horizontal edge above box `(row, col)`. `row` may equal the board
+    /// height for the bottom edge of the last row.
+    pub fn horizontal<const W: usize, const H: usize>(row: usize, col: usize) -> Self {
+        assert!(row <= H && col < W, "Invalid horizontal edge");
+        DBAction {
+            edge: (row * W + col) as u8,
+        }
+    }
+
+    /// The vertical edge to the left of box `(row, col)`. `col` may equal the
+    /// board width for the right edge of the last column.
+    pub fn vertical<const W: usize, const H: usize>(row: usize, col: usize) -> Self {
+        assert!(row < H && col <= W, "Invalid vertical edge");
+        DBAction {
+            edge: (DotsAndBoxes::<W, H>::HORIZONTAL_EDGES + row * (W + 1) + col) as u8,
+        }
+    }
+
+    pub fn edge(&self) -> usize {
+        self.edge as usize
+    }
+}
+
+impl Display for DBAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DBAction {{ edge: {} }}", self.edge)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DBState<const W: usize, const H: usize> {
+    edges: u128,
+    boxes: [[Option<Team>; W]; H],
+    player: Team,
+}
+
+impl<const W: usize, const H: usize> DBState<W, H> {
+    const ALL_EDGES: u128 = if DotsAndBoxes::<W, H>::EDGES >= 128 {
+        u128::MAX
+    } else {
+        (1 << DotsAndBoxes::<W, H>::EDGES) - 1
+    };
+
+    #[inline]
+    fn has_edge(&self, edge: usize) -> bool {
+        self.edges & (1 << edge) != 0
+    }
+
+    /// The four edges around box `(row, col)`: top, bottom, left and right.
+    #[inline]
+    fn box_edges(row: usize, col: usize) -> [usize; 4] {
+        let vertical = DotsAndBoxes::<W, H>::HORIZONTAL_EDGES + row * (W + 1) + col;
+        [row * W + col, (row + 1) * W + col, vertical, vertical + 1]
+    }
+
+    /// The boxes on either side of an edge, `None` on the border.
+    #[inline]
+    fn adjacent_boxes(edge: usize) -> [Option<(usize, usize)>; 2] {
+        if edge < DotsAndBoxes::<W, H>::HORIZONTAL_EDGES {
+            let (row, col) = (edge / W, edge % W);
+            [
+                (row > 0).then(|| (row - 1, col)),
+                (row < H).then_some((row, col)),
+            ]
+        } else {
+            let edge = edge - DotsAndBoxes::<W, H>::HORIZONTAL_EDGES;
+            let (row, col) = (edge / (W + 1), edge % (W + 1));
+            [
+                (col > 0).then(|| (row, col - 1)),
+                (col < W).then_some((row, col)),
+            ]
+        }
+    }
+
+    /// Number of drawn edges around box `(row, col)`.
+    #[inline]
+    pub fn sides(&self, row: usize, col: usize) -> usize {
+        Self::box_edges(row, col)
+            .iter()
+            .filter(|&&edge| self.has_edge(edge))
+            .count()
+    }
+
+    /// The team that completed box `(row, col)`, if any.
+    pub fn owner(&self, row: usize, col: usize) -> Option<Team> {
+        self.boxes[row][col]
+    }
+
+    /// Number of boxes claimed by `team`.
+    pub fn score(&self, team: Team) -> usize {
+        self.boxes
+            .iter()
+            .flatten()
+            .filter(|&&owner| owner == Some(team))
+            .count()
+    }
+
+    /// Number of drawn edges, i.e. plies played since the start of the game.
+    pub fn edges_drawn(&self) -> u32 {
+        self.edges.count_ones()
+    }
+
+    /// Number of long chains: maximal runs of at least three boxes with
+    /// exactly two sides drawn, connected through their undrawn sides.
+    /// Loops are counted as chains.
+    pub fn long_chains(&self) -> usize {
+        let mut visited = [[false; W]; H];
+        let mut chains = 0;
+        for row in 0..H {
+            for col in 0..W {
+                if visited[row][col] || self.sides(row, col) != 2 {
+                    continue;
+                }
+                let mut length = 0;
+                let mut stack = vec![(row, col)];
+                visited[row][col] = true;
+                while let Some((r, c)) = stack.pop() {
+                    length += 1;
+                    for edge in Self::box_edges(r, c) {
+                        if self.has_edge(edge) {
+                            continue;
+                        }
+                        for (nr, nc) in Self::adjacent_boxes(edge).into_iter().flatten() {
+                            if !visited[nr][nc] && self.sides(nr, nc) == 2 {
+                                visited[nr][nc] = true;
+                                stack.push((nr, nc));
+                            }
+                        }
+                    }
+                }
+                if length >= 3 {
+                    chains += 1;
+                }
+            }
+        }
+        chains
+    }
+}
+
+impl<const W: usize, const H: usize> Display for DBState<W, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in 0..=H {
+            for col in 0..W {
+                let drawn = self.has_edge(row * W + col);
+                write!(f, "·{}", if drawn { "─" } else { " " })?;
+            }
+            writeln!(f, "·")?;
+            if row == H {
+                break;
+            }
+            for col in 0..=W {
+                let edge = DotsAndBoxes::<W, H>::HORIZONTAL_EDGES + row * (W + 1) + col;
+                write!(f, "{}", if self.has_edge(edge) { "│" } else { " " })?;
+                if col < W {
+                    match self.boxes[row][col] {
+                        Some(Team::One) => write!(f, "1")?,
+                        Some(Team::Two) => write!(f, "2")?,
+                        None => write!(f, " ")?,
+                    }
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const W: usize, const H: usize> GwState<DotsAndBoxes<W, H>> for DBState<W, H> {
+    type ActionIter = DBActionIter;
+
+    #[inline]
+    fn actions(&self) -> Self::ActionIter {
+        DBActionIter {
+            edges: Self::ALL_EDGES & !self.edges,
+        }
+    }
+
+    #[inline]
+    fn count_actions(&self) -> usize {
+        (Self::ALL_EDGES & !self.edges).count_ones() as usize
+    }
+
+    #[inline]
+    fn team_to_move(&self) -> Team {
+        self.player
+    }
+
+    /// Assumes the edge has not been drawn yet. The player keeps the move if
+    /// the edge completes a box.
+    fn apply_action(&self, action: &DBAction) -> Self {
+        let mut next = self.clone();
+        next.edges |= 1 << action.edge;
+        let mut completed = false;
+        for (row, col) in Self::adjacent_boxes(action.edge()).into_iter().flatten() {
+            if next.sides(row, col) == 4 {
+                next.boxes[row][col] = Some(self.player);
+                completed = true;
+            }
+        }
+        if !completed {
+            next.player = self.player.opponent();
+        }
+        next
+    }
+
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        self.edges == Self::ALL_EDGES
+    }
+
+    fn game_result(&self) -> Option<GameResult<Team>> {
+        if !self.is_terminal() {
+            return None;
+        }
+        let (one, two) = (self.score(Team::One), self.score(Team::Two));
+        Some(match one.cmp(&two) {
+            std::cmp::Ordering::Greater => GameResult::Win(Team::One),
+            std::cmp::Ordering::Less => GameResult::Win(Team::Two),
+            std::cmp::Ordering::Equal => GameResult::Draw,
+        })
+    }
+}
+
+pub struct DBActionIter {
+    edges: u128,
+}
+
+impl Iterator for DBActionIter {
+    type Item = DBAction;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.edges == 0 {
+            None
+        } else {
+            let edge = self.edges.trailing_zeros() as u8;
+            self.edges &= self.edges - 1;
+            Some(DBAction { edge })
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.edges.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+use ahash::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// randomly generated numbers
+const RANDOM_STATE: RandomState = RandomState::with_seeds(
+    0x5be0cd19137e2179,
+    0x1f83d9abfb41bd6b,
+    0x9b05688c2b3e6c1f,
+    0x510e527fade682d1,
+);
+
+/// randomly generated numbers, independent of [RANDOM_STATE]
+const AUDIT_STATE: RandomState = RandomState::with_seeds(
+    0xb492b66fbe98f273,
+    0x9ae16a3b2f90404f,
+    0xc3a5c85c97cb3127,
+    0x4f1bbcdcbfa54a3b,
+);
+
+impl<const W: usize, const H: usize> DBState<W, H> {
+    /// Hashes the drawn edges, the team to move and the score difference,
+    /// which is all that matters for the rest of the game. Which boxes each
+    /// team owns is not hashed.
+    #[inline]
+    fn hash_with(&self, random_state: &RandomState) -> u64 {
+        let difference = self.score(Team::One) as i64 - self.score(Team::Two) as i64;
+        let mut x = random_state.build_hasher();
+        x.write_u128(self.edges);
+        x.write_i64(difference);
+        x.write_u8(self.player as u8);
+        x.finish()
+    }
+}
+
+impl<const W: usize, const H: usize> cachewing::TranspositionHash for DBState<W, H> {
+    #[inline]
+    fn hash(&self) -> u64 {
+        self.hash_with(&RANDOM_STATE)
+    }
+}
+
+impl<const W: usize, const H: usize> glasswing::perft::AuditHash for DBState<W, H> {
+    #[inline]
+    fn audit_hash(&self) -> u64 {
+        self.hash_with(&AUDIT_STATE)
+    }
+}
+
+/// Scores the box difference, counting boxes with three sides drawn for
+/// the team to move, which can take them. The long chain rule breaks ties:
+/// the first player wants the number of dots plus long chains to be even.
+///
+/// Decisive results are scored with [win_in] and [loss_in].
+pub struct DBHeuristic;
+
+impl<const W: usize, const H: usize> Evaluator<DotsAndBoxes<W, H>> for DBHeuristic {
+    fn evaluate_for(&mut self, state: &DBState<W, H>, team: &Team) -> i32 {
+        match state.game_result() {
+            Some(GameResult::Win(winner)) if winner == *team => return win_in(state.edges_drawn()),
+            Some(GameResult::Win(_)) => return loss_in(state.edges_drawn()),
+            Some(GameResult::Draw) => return 0,
+            None => {}
+        }
+
+        let capturable = (0..H)
+            .flat_map(|row| (0..W).map(move |col| (row, col)))
+            .filter(|&(row, col)| state.sides(row, col) == 3)
+            .count() as i32;
+        let mut eval = 10 * (state.score(*team) as i32 - state.score(team.opponent()) as i32);
+        if state.team_to_move() == *team {
+            eval += 10 * capturable;
+        } else {
+            eval -= 10 * capturable;
+        }
+
+        let dots = (W + 1) * (H + 1);
+        let first_player_controls = (dots + state.long_chains()).is_multiple_of(2);
+        if first_player_controls == (*team == Team::One) {
+            eval += 5;
+        } else {
+            eval -= 5;
+        }
+        eval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glasswing::agents::SimpleAgent;
+    use glasswing::train::Pit;
+
+    /// Two boxes side by side. Edges 0 and 1 are on top, 2 and 3 at the
+    /// bottom, and 4, 5 and 6 are the vertical edges from left to right.
+    type Domino = DotsAndBoxes<2, 1>;
+
+    fn play(edges: &[usize]) -> DBState<2, 1> {
+        edges.iter().fold(Domino::initial_state(), |state, &edge| {
+            state.apply_action(&DBAction { edge: edge as u8 })
+        })
+    }
+
+    #[test]
+    fn edge_numbering() {
+        assert_eq!(Domino::EDGES, 7);
+        assert_eq!(DBAction::horizontal::<2, 1>(1, 1).edge(), 3);
+        assert_eq!(DBAction::vertical::<2, 1>(0, 1).edge(), 5);
+    }
+
+    #[test]
+    fn double_cross_claims_both_boxes() {
+        let state = play(&[0, 1, 2, 3, 4, 6]);
+        assert_eq!(state.team_to_move(), Team::One);
+        assert_eq!((state.sides(0, 0), state.sides(0, 1)), (3, 3));
+
+        // the middle edge completes both boxes at once
+        let state = state.apply_action(&DBAction::vertical::<2, 1>(0, 1));
+        assert_eq!(state.owner(0, 0), Some(Team::One));
+        assert_eq!(state.owner(0, 1), Some(Team::One));
+        assert_eq!(state.score(Team::One), 2);
+        assert_eq!(state.game_result(), Some(GameResult::Win(Team::One)));
+    }
+
+    #[test]
+    fn completing_a_box_keeps_the_move() {
+        let state = play(&[5, 0, 1, 2]);
+        assert_eq!(state.team_to_move(), Team::One);
+        let state = state.apply_action(&DBAction { edge: 4 });
+        assert_eq!(state.owner(0, 0), Some(Team::One));
+        assert_eq!(state.team_to_move(), Team::One);
+        let state = state.apply_action(&DBAction { edge: 3 });
+        assert_eq!(state.team_to_move(), Team::Two);
+    }
+
+    #[test]
+    fn split_boxes_are_a_draw() {
+        let state = play(&[5, 0, 1, 2, 4, 3, 6]);
+        assert_eq!(state.owner(0, 0), Some(Team::One));
+        assert_eq!(state.owner(0, 1), Some(Team::Two));
+        assert_eq!(state.game_result(), Some(GameResult::Draw));
+        assert_eq!(DBHeuristic.evaluate_for(&state, &Team::One), 0);
+    }
+
+    #[test]
+    fn extra_turns_in_a_match() {
+        let mut pit = Pit::new(
+            SimpleAgent::<Domino>::new(),
+            SimpleAgent::new(),
+            Domino::initial_state(),
+        );
+        let mut turns = Vec::new();
+        while let Some(turn) = pit.step().unwrap() {
+            assert_eq!(turn.team, turn.pre.team_to_move());
+            turns.push((turn.agent, turn.action.edge()));
+        }
+        // edge 5 completes the left box and 6 the right one, so agent B
+        // moves twice in a row
+        assert_eq!(
+            turns,
+            [(0, 0), (1, 1), (0, 2), (1, 3), (0, 4), (1, 5), (1, 6)]
+        );
+        assert_eq!(pit.state().score(Team::Two), 2);
+        assert_eq!(pit.game_result(), Some(GameResult::Win(Team::Two)));
+    }
+}
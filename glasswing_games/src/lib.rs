@@ -1,4 +1,8 @@
 #![cfg_attr(feature = "simd_support", feature(portable_simd))]
+pub mod bench;
+pub mod breakthrough;
+pub mod checkers;
 pub mod connect4;
+pub mod dots_and_boxes;
 pub mod nxn_tictactoe;
 pub mod tictactoe;
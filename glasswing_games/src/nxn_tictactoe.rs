@@ -1,5 +1,6 @@
-use glasswing::agents::Evaluator;
+use glasswing::agents::{AsSymmetric, Evaluator, SymmetricEvaluator};
 use glasswing::core::{Game, GameResult, GwState, Team};
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::ops::Index;
 
@@ -13,6 +14,12 @@ impl<const N: usize> Game for NTicTacToe<N> {
     type GameResult = GameResult<Self::Team>;
     type EvalType = i32;
 
+    const MAX_ACTIONS: Option<usize> = Some(N * N);
+
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("NTicTacToe<{N}>"))
+    }
+
     fn initial_state() -> Self::State {
         NTTTState {
             board: [[None; N]; N],
@@ -95,6 +102,17 @@ impl<const N: usize> GwState<NTicTacToe<N>> for NTTTState<N> {
         NTTTActionIter::new(self.clone())
     }
 
+    fn count_actions(&self) -> usize {
+        if self.is_terminal() {
+            return 0;
+        }
+        self.board
+            .iter()
+            .flatten()
+            .filter(|cell| cell.is_none())
+            .count()
+    }
+
     fn team_to_move(&self) -> Team {
         self.player
     }
@@ -102,7 +120,7 @@ impl<const N: usize> GwState<NTicTacToe<N>> for NTTTState<N> {
     // Assume that the action is legal in this state.
     // therefore, the state is not terminal.
     fn apply_action(&self, action: &NTTTAction) -> Self {
-        let mut new_board = self.board.clone();
+        let mut new_board = self.board;
         new_board[action.row][action.col] = Some(self.team_to_move());
         Self {
             board: new_board,
@@ -199,14 +217,23 @@ impl<const N: usize> Iterator for NTTTActionIter<N> {
     }
 }
 
+/// Symmetric evaluator. It can be used directly as an [Evaluator], which
+/// scores like [AsSymmetric]`(NTTTEvaluator)`.
 pub struct NTTTEvaluator;
 
 impl<const N: usize> Evaluator<NTicTacToe<N>> for NTTTEvaluator {
+    #[inline]
     fn evaluate_for(&mut self, state: &NTTTState<N>, team: &Team) -> i32 {
+        AsSymmetric(NTTTEvaluator).evaluate_for(state, team)
+    }
+}
+
+impl<const N: usize> SymmetricEvaluator<NTicTacToe<N>> for NTTTEvaluator {
+    fn evaluate_relative(&mut self, state: &NTTTState<N>) -> i32 {
         match state.game_result() {
             Some(result) => match result {
                 GameResult::Win(winner) => {
-                    if winner == *team {
+                    if winner == state.team_to_move() {
                         100
                     } else {
                         -100
@@ -218,3 +245,124 @@ impl<const N: usize> Evaluator<NTicTacToe<N>> for NTTTEvaluator {
         }
     }
 }
+
+/// The SplitMix64 finaliser, used to derive Zobrist keys on the fly.
+#[inline]
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+impl<const N: usize> NTTTState<N> {
+    /// Zobrist hash over the cells and the team to move, with the keys
+    /// derived from `seed`.
+    fn zobrist(&self, seed: u64) -> u64 {
+        // keys 0 and 1 are the base key and the key for team two to move
+        let key = |i: u64| splitmix64(seed.wrapping_add(i));
+        let mut hash = key(0);
+        if self.player == Team::Two {
+            hash ^= key(1);
+        }
+        for (i, cell) in self.board.iter().flatten().enumerate() {
+            match cell {
+                Some(Team::One) => hash ^= key(2 + 2 * i as u64),
+                Some(Team::Two) => hash ^= key(3 + 2 * i as u64),
+                None => {}
+            }
+        }
+        hash
+    }
+}
+
+impl<const N: usize> cachewing::TranspositionHash for NTTTState<N> {
+    fn hash(&self) -> u64 {
+        self.zobrist(0)
+    }
+}
+
+impl<const N: usize> glasswing::perft::AuditHash for NTTTState<N> {
+    fn audit_hash(&self) -> u64 {
+        // far enough from 0 that the two key sets never overlap
+        self.zobrist(1 << 63)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cachewing::{QuadraticProbingTable64, TranspositionHash};
+    use glasswing::perft::{perft, perft_with_cache, perft_with_cache_audited, AuditHash};
+    use std::collections::{HashMap, HashSet};
+
+    /// A collision free code of the board and the team to move, in base 3.
+    fn code<const N: usize>(state: &NTTTState<N>) -> u64 {
+        state
+            .board
+            .iter()
+            .flatten()
+            .fold((state.player == Team::Two) as u64, |code, cell| {
+                3 * code
+                    + match cell {
+                        None => 0,
+                        Some(Team::One) => 1,
+                        Some(Team::Two) => 2,
+                    }
+            })
+    }
+
+    fn reachable<const N: usize>() -> Vec<NTTTState<N>> {
+        let mut states = vec![NTicTacToe::<N>::initial_state()];
+        let mut seen = HashSet::from([code(&states[0])]);
+        let mut i = 0;
+        while i < states.len() {
+            for next in states[i].substates() {
+                if seen.insert(code(&next)) {
+                    states.push(next);
+                }
+            }
+            i += 1;
+        }
+        states
+    }
+
+    #[test]
+    fn hashes_of_all_3x3_positions_are_distinct() {
+        let states = reachable::<3>();
+        assert_eq!(states.len(), 5478);
+
+        let mut hashes = HashMap::new();
+        let mut audit_hashes = HashSet::new();
+        for state in &states {
+            if let Some(other) = hashes.insert(state.hash(), state) {
+                panic!("{:?} and {:?} collide", state, other);
+            }
+            assert!(audit_hashes.insert(state.audit_hash()), "{:?}", state);
+        }
+        assert!(hashes.keys().all(|hash| !audit_hashes.contains(hash)));
+    }
+
+    #[test]
+    fn cached_perft_agrees_with_perft() {
+        let root = NTicTacToe::<3>::initial_state();
+        let mut table = QuadraticProbingTable64::new(1 << 14);
+        assert_eq!(
+            perft_with_cache::<NTicTacToe<3>, _>(&root, 9, &mut table),
+            255_168
+        );
+
+        let mut table = QuadraticProbingTable64::new(1 << 14);
+        let (count, stats) = perft_with_cache_audited::<NTicTacToe<3>, _>(&root, 9, &mut table);
+        assert_eq!(count, 255_168);
+        assert_eq!(stats.collisions, 0);
+        assert!(stats.hits > 0);
+
+        let root = NTicTacToe::<4>::initial_state();
+        let mut table = QuadraticProbingTable64::new(1 << 16);
+        assert_eq!(
+            perft_with_cache::<NTicTacToe<4>, _>(&root, 6, &mut table),
+            perft::<NTicTacToe<4>>(&root, 6)
+        );
+    }
+}
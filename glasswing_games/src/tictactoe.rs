@@ -1,22 +1,26 @@
+use glasswing::agents::scores::{loss_in, win_in};
 use glasswing::agents::Evaluator;
 use glasswing::core::Team::{One, Two};
-use glasswing::core::{Game, GameResult, GwState, Team};
+use glasswing::core::{DiffableState, Game, GameResult, GwState, ReverseState, Team};
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 
+/// Scores decisive results with [win_in] and [loss_in], counting the plies
+/// since the start of the game.
 pub struct TTTHeuristic;
 
 impl Evaluator<TicTacToe> for TTTHeuristic {
     fn evaluate_for(&mut self, state: &TTTState, team: &Team) -> i32 {
         match state.game_result() {
             Some(GameResult::Win(winner)) => {
+                let turn = (state.crosses | state.noughts).count_ones();
                 if winner == *team {
-                    100
+                    win_in(turn)
                 } else {
-                    -100
+                    loss_in(turn)
                 }
             }
-            Some(GameResult::Draw) => 1,
-            None => 0,
+            Some(GameResult::Draw) | None => 0,
         }
     }
 }
@@ -48,6 +52,12 @@ impl Game for TicTacToe {
     type Action = TTTAction;
     type EvalType = i32;
 
+    const MAX_ACTIONS: Option<usize> = Some(9);
+
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("TicTacToe")
+    }
+
     fn initial_state() -> Self::State {
         TTTState {
             crosses: 0,
@@ -58,6 +68,13 @@ impl Game for TicTacToe {
     }
 }
 
+/// The cell marked by a single move, numbered 0 to 8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TTTDelta {
+    pub cell: u8,
+    pub team: Team,
+}
+
 #[derive(Clone, Debug)]
 pub struct TTTState {
     crosses: u16,
@@ -74,6 +91,18 @@ impl GwState<TicTacToe> for TTTState {
         TTTActionIter::new(self.clone())
     }
 
+    #[inline]
+    fn actions_into(&self, buf: &mut Vec<TTTAction>) {
+        buf.clear();
+        let mut fields =
+            (!self.is_terminal) as u16 * (0b111111111 & !(self.crosses | self.noughts));
+        while fields != 0 {
+            let mask = fields & !(fields - 1);
+            fields ^= mask;
+            buf.push(TTTAction { mask });
+        }
+    }
+
     #[inline]
     fn count_actions(&self) -> usize {
         (!self.is_terminal as usize) * (9 - (self.crosses | self.noughts).count_ones() as usize)
@@ -84,8 +113,17 @@ impl GwState<TicTacToe> for TTTState {
         self.player
     }
 
+    /// The centre first, then the corners, then the edges.
+    #[inline]
+    fn static_order_key(&self, action: &TTTAction) -> i32 {
+        match action.mask.trailing_zeros() {
+            4 => 2,
+            0 | 2 | 6 | 8 => 1,
+            _ => 0,
+        }
+    }
+
     #[inline]
-    #[must_use]
     fn apply_action(&self, action: &TTTAction) -> Self {
         match self.player {
             One => {
@@ -132,6 +170,128 @@ impl GwState<TicTacToe> for TTTState {
     }
 }
 
+impl DiffableState<TicTacToe> for TTTState {
+    type Delta = TTTDelta;
+
+    fn diff(&self, prev: &Self) -> TTTDelta {
+        let added = (self.crosses | self.noughts) & !(prev.crosses | prev.noughts);
+        assert!(
+            added.count_ones() == 1,
+            "states do not differ by a single move"
+        );
+        TTTDelta {
+            cell: added.trailing_zeros() as u8,
+            team: prev.player,
+        }
+    }
+
+    fn apply_delta(&self, delta: &TTTDelta) -> Self {
+        debug_assert!(delta.team == self.player, "delta is for the other team");
+        self.apply_action(&TTTAction {
+            mask: 1 << delta.cell,
+        })
+    }
+}
+
+impl ReverseState<TicTacToe> for TTTState {
+    fn predecessors(&self) -> Vec<Self> {
+        // the last move was made by the team not to move
+        let last_moved = match self.player {
+            One => self.noughts,
+            Two => self.crosses,
+        };
+
+        let mut predecessors = Vec::new();
+        let mut pieces = last_moved;
+        while pieces != 0 {
+            let mask = pieces & !(pieces - 1);
+            pieces ^= mask;
+
+            let (crosses, noughts) = match self.player {
+                One => (self.crosses, self.noughts ^ mask),
+                Two => (self.crosses ^ mask, self.noughts),
+            };
+            let is_terminal = win_condition(crosses)
+                || win_condition(noughts)
+                || (crosses | noughts) == 0b111111111;
+            if !is_terminal {
+                predecessors.push(TTTState {
+                    crosses,
+                    noughts,
+                    player: self.player.opponent(),
+                    is_terminal,
+                });
+            }
+        }
+        predecessors
+    }
+}
+
+impl cachewing::TranspositionHash for TTTState {
+    /// A perfect hash, since the team to move follows from the piece counts.
+    #[inline]
+    fn hash(&self) -> u64 {
+        // the extra bit keeps the empty board from hashing to 0
+        (1 << 32) | (self.noughts as u64) << 16 | self.crosses as u64
+    }
+}
+
+impl glasswing::perft::AuditHash for TTTState {
+    /// The perfect hash, reversed, so it is just as collision free.
+    #[inline]
+    fn audit_hash(&self) -> u64 {
+        cachewing::TranspositionHash::hash(self).reverse_bits()
+    }
+}
+
+impl TTTState {
+    /// Builds a state by playing the given cells (`'0'` to `'8'`, row by row)
+    /// from the initial position, e.g. `"408"`.
+    ///
+    /// # Panics
+    /// Panics if a character is not a cell, or the move is not legal.
+    pub fn from_moves(moves: &str) -> Self {
+        let mut state = TicTacToe::initial_state();
+        for c in moves.chars() {
+            let mask = match c.to_digit(10) {
+                Some(pos) if pos < 9 => 1 << pos,
+                _ => panic!("Invalid cell in move string for TicTacToe: {}", c),
+            };
+            assert!(
+                !state.is_terminal && (state.crosses | state.noughts) & mask == 0,
+                "Illegal move in move string for TicTacToe: {}",
+                c
+            );
+            state = state.apply_action(&TTTAction { mask });
+        }
+        state
+    }
+
+    /// The state reflected left to right.
+    pub fn mirrored(&self) -> Self {
+        self.permuted(|row, col| row * 3 + 2 - col)
+    }
+
+    /// The state rotated a quarter turn clockwise. Together with
+    /// [TTTState::mirrored], this generates all symmetries of the board.
+    pub fn rotated(&self) -> Self {
+        self.permuted(|row, col| col * 3 + 2 - row)
+    }
+
+    fn permuted(&self, cell: impl Fn(u16, u16) -> u16) -> Self {
+        let permute = |mask: u16| {
+            (0..9)
+                .filter(|i| mask & (1 << i) != 0)
+                .fold(0, |acc, i| acc | 1 << cell(i / 3, i % 3))
+        };
+        TTTState {
+            crosses: permute(self.crosses),
+            noughts: permute(self.noughts),
+            ..self.clone()
+        }
+    }
+}
+
 impl std::fmt::Display for TTTState {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut board_str = String::new();
@@ -147,9 +307,9 @@ impl std::fmt::Display for TTTState {
             });
 
             if i % 3 == 2 {
-                board_str.push_str("\n");
+                board_str.push('\n');
             } else {
-                board_str.push_str(" ");
+                board_str.push(' ');
             }
         }
 
@@ -175,13 +335,13 @@ impl Iterator for TTTActionIter {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        return if self.fields == 0 {
+        if self.fields == 0 {
             None
         } else {
             let mask = self.fields & !(self.fields - 1);
             self.fields ^= mask;
             Some(TTTAction { mask })
-        };
+        }
     }
 
     #[inline]
@@ -224,6 +384,523 @@ fn win_condition(mask: u16) -> bool {
         0b001010100,
     ];
     win_masks
-        .iter()
-        .any(|&win_mask| mask & win_mask == win_mask)
+        .into_iter()
+        .any(|win_mask| mask & win_mask == win_mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cachewing::QuadraticProbingTable64;
+    use cachewing::TranspositionHash;
+    use glasswing::agents::scores::plies_to_win;
+    use glasswing::agents::{Agent, MaximisingAgent, NegaMax};
+    use glasswing::artifact::ArtifactError;
+    use glasswing::perft::{
+        divide, enumerate_states, enumerate_unique_states, perft, perft_with_cache_audited,
+        AuditHash, EnumerationError,
+    };
+    use glasswing::tablebase::{build, Tablebase, TablebaseEvaluator, TbValue};
+    use glasswing::testing::{check_symmetry, Transform};
+    use glasswing::train::{Adjudication, Pit};
+    use glasswing::variants::{Misere, MisereEvaluator, MisereState};
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Arc;
+
+    fn all_states() -> Vec<TTTState> {
+        let mut states = vec![TicTacToe::initial_state()];
+        let mut seen = HashSet::from([states[0].hash()]);
+        let mut i = 0;
+        while i < states.len() {
+            for next in states[i].substates() {
+                if seen.insert(next.hash()) {
+                    states.push(next);
+                }
+            }
+            i += 1;
+        }
+        states
+    }
+
+    /// The value of a state by exhaustive search, preferring the fastest
+    /// win and the slowest loss.
+    fn solve(state: &TTTState, memo: &mut HashMap<u64, TbValue>) -> TbValue {
+        if let Some(&value) = memo.get(&state.hash()) {
+            return value;
+        }
+        let value = match state.game_result() {
+            Some(GameResult::Win(winner)) if winner == state.player => TbValue::Win(0),
+            Some(GameResult::Win(_)) => TbValue::Loss(0),
+            Some(GameResult::Draw) => TbValue::Draw,
+            None => {
+                let children = state
+                    .substates()
+                    .map(|next| solve(&next, memo))
+                    .collect::<Vec<TbValue>>();
+                let fastest_win = children
+                    .iter()
+                    .filter_map(|value| match value {
+                        TbValue::Loss(distance) => Some(distance + 1),
+                        _ => None,
+                    })
+                    .min();
+                let slowest_loss = children
+                    .iter()
+                    .filter_map(|value| match value {
+                        TbValue::Win(distance) => Some(distance + 1),
+                        _ => None,
+                    })
+                    .max();
+                match fastest_win {
+                    Some(distance) => TbValue::Win(distance),
+                    None if children.contains(&TbValue::Draw) => TbValue::Draw,
+                    None => TbValue::Loss(slowest_loss.unwrap()),
+                }
+            }
+        };
+        memo.insert(state.hash(), value);
+        value
+    }
+
+    #[test]
+    fn tablebase_matches_exhaustive_search() {
+        let tablebase = build::<TicTacToe>(usize::MAX);
+        assert_eq!(tablebase.len(), 5478);
+        assert_eq!(
+            tablebase.probe(&TicTacToe::initial_state()),
+            Some(TbValue::Draw)
+        );
+        assert_eq!(
+            tablebase.probe(&TTTState::from_moves("01")),
+            Some(TbValue::Win(5))
+        );
+
+        let mut memo = HashMap::new();
+        for state in all_states() {
+            assert_eq!(
+                tablebase.probe(&state),
+                Some(solve(&state, &mut memo)),
+                "{}",
+                state
+            );
+        }
+    }
+
+    fn ply(state: &TTTState) -> u32 {
+        (state.crosses | state.noughts).count_ones()
+    }
+
+    #[test]
+    fn tablebase_scores_count_from_the_start_like_the_heuristic() {
+        let tablebase = Arc::new(build::<TicTacToe>(usize::MAX));
+        let mut evaluator = TablebaseEvaluator::new(tablebase.clone(), TTTHeuristic, ply);
+        for state in all_states() {
+            let team = state.team_to_move();
+            let score = evaluator.evaluate_for(&state, &team);
+            match tablebase.probe(&state).unwrap() {
+                TbValue::Win(distance) => {
+                    assert_eq!(
+                        plies_to_win(score),
+                        Some(ply(&state) + distance),
+                        "{}",
+                        state
+                    )
+                }
+                TbValue::Loss(_) if state.is_terminal() => {
+                    assert_eq!(score, TTTHeuristic.evaluate_for(&state, &team), "{}", state)
+                }
+                _ => {}
+            }
+        }
+
+        // a win at the fifth ply scores above one at the seventh
+        let quick = TTTState::from_moves("0314");
+        let slow = TTTState::from_moves("013645");
+        assert_eq!(tablebase.probe(&quick), Some(TbValue::Win(1)));
+        assert_eq!(tablebase.probe(&slow), Some(TbValue::Win(1)));
+        assert!(
+            evaluator.evaluate_for(&quick, &quick.team_to_move())
+                > evaluator.evaluate_for(&slow, &slow.team_to_move())
+        );
+    }
+
+    #[test]
+    fn truncated_tablebase_agrees_with_full_build() {
+        let full = build::<TicTacToe>(usize::MAX);
+        let truncated = build::<TicTacToe>(3000);
+        assert!(!truncated.is_empty());
+        assert!(truncated.probe(&TicTacToe::initial_state()).is_none());
+        for state in all_states() {
+            if let Some(value) = truncated.probe(&state) {
+                assert_eq!(Some(value), full.probe(&state), "{}", state);
+            }
+        }
+    }
+
+    #[test]
+    fn tablebase_round_trips() {
+        let tablebase = build::<TicTacToe>(usize::MAX);
+        let mut bytes = Vec::new();
+        tablebase.write_to(&mut bytes).unwrap();
+        let read = Tablebase::<TicTacToe>::read_from(&mut &bytes[..]).unwrap();
+        assert_eq!(read.len(), tablebase.len());
+        for state in all_states() {
+            assert_eq!(read.probe(&state), tablebase.probe(&state));
+        }
+    }
+
+    #[test]
+    fn tablebase_adjudication_matches_perfect_playouts() {
+        let tablebase = Arc::new(build::<TicTacToe>(usize::MAX));
+        let perfect = || MaximisingAgent::new(NegaMax::new(9, TTTHeuristic));
+        let starts = all_states()
+            .into_iter()
+            .filter(|state| !state.is_terminal())
+            .step_by(37)
+            .collect::<Vec<TTTState>>();
+        assert!(starts.len() > 50);
+
+        let mut shortened = 0;
+        for start in starts {
+            let mut full = Pit::new(perfect(), perfect(), start.clone());
+            let full_result = full.playout();
+            let full_plies = ply(full.state()) - ply(&start);
+
+            let mut adjudicated = Pit::new(perfect(), perfect(), start.clone())
+                .with_tablebase_adjudication(tablebase.clone());
+            let plies = adjudicated.by_ref().count() as u32;
+            assert_eq!(adjudicated.game_result(), full_result, "{}", start);
+            if adjudicated.is_adjudicated() {
+                assert_eq!(adjudicated.adjudication(), Some(Adjudication::Tablebase));
+                assert_eq!(plies, 1, "{}", start);
+                assert!(full_plies > 1, "{}", start);
+                shortened += 1;
+            } else {
+                assert_eq!(plies, full_plies, "{}", start);
+            }
+        }
+        assert!(shortened > 0);
+    }
+
+    #[test]
+    fn deltas_round_trip_from_the_initial_state() {
+        for state in all_states() {
+            for action in state.actions() {
+                let next = state.apply_action(&action);
+                let delta = next.diff(&state);
+                assert_eq!(1 << delta.cell, action.mask);
+                assert_eq!(delta.team, state.team_to_move());
+                assert_eq!(state.apply_delta(&delta).hash(), next.hash(), "{}", next);
+            }
+        }
+
+        // a whole game replayed from deltas alone
+        let moves = [4, 0, 8, 2, 1, 7, 6, 3, 5];
+        let mut state = TicTacToe::initial_state();
+        let mut replica = state.clone();
+        for cell in moves {
+            let next = state.apply_action(&TTTAction { mask: 1 << cell });
+            replica = replica.apply_delta(&next.diff(&state));
+            state = next;
+            assert_eq!(replica.hash(), state.hash());
+        }
+        assert_eq!(replica.game_result(), Some(GameResult::Draw));
+    }
+
+    #[test]
+    #[should_panic(expected = "states do not differ by a single move")]
+    fn diff_of_unrelated_states_panics() {
+        TTTState::from_moves("012").diff(&TicTacToe::initial_state());
+    }
+
+    #[test]
+    fn tablebases_of_other_games_are_rejected() {
+        let mut bytes = Vec::new();
+        build::<TicTacToe>(usize::MAX).write_to(&mut bytes).unwrap();
+        let err = Tablebase::<crate::connect4::Connect4>::read_from(&mut bytes.as_slice())
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&err, ArtifactError::WrongGame { expected, found }
+                if expected == "Connect4" && found == "TicTacToe"),
+            "{}",
+            err
+        );
+
+        // the format version follows the magic
+        bytes[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = Tablebase::<TicTacToe>::read_from(&mut bytes.as_slice())
+            .err()
+            .unwrap();
+        assert!(
+            matches!(
+                err,
+                ArtifactError::UnsupportedVersion {
+                    found: u32::MAX,
+                    ..
+                }
+            ),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn corrupt_tablebase_lengths_are_errors() {
+        let all_ones = [0xffu8; 16];
+        assert!(Tablebase::<TicTacToe>::read_from(&mut &all_ones[..]).is_err());
+        let huge = 0x10_0000_0000u64.to_le_bytes();
+        assert!(Tablebase::<TicTacToe>::read_from(&mut &huge[..]).is_err());
+    }
+
+    #[test]
+    fn unique_states_per_depth() {
+        let root = TicTacToe::initial_state();
+        let expected = [1, 10, 82, 334, 1090, 2350, 3870, 5010, 5400, 5478];
+        for (depth, &count) in (0..).zip(&expected) {
+            let states = enumerate_unique_states::<TicTacToe>(&root, depth, usize::MAX).unwrap();
+            assert_eq!(states.len(), count, "depth {}", depth);
+            for (state, plies) in &states {
+                assert_eq!((state.crosses | state.noughts).count_ones(), *plies);
+            }
+        }
+        let all = enumerate_unique_states::<TicTacToe>(&root, 20, usize::MAX).unwrap();
+        assert_eq!(all.len(), 5478);
+    }
+
+    #[test]
+    fn enumeration_is_consistent_with_perft() {
+        let root = TicTacToe::initial_state();
+        let mut previous = enumerate_states::<TicTacToe>(&root, 0, usize::MAX).unwrap();
+        assert_eq!(previous.len(), 1);
+        for depth in 1..=9 {
+            let states = enumerate_states::<TicTacToe>(&root, depth, usize::MAX).unwrap();
+            // perft counts the new nodes, and the terminal nodes found earlier
+            let terminals = previous.iter().filter(|s| s.is_terminal()).count();
+            assert_eq!(
+                (states.len() - previous.len() + terminals) as u64,
+                perft::<TicTacToe>(&root, depth),
+                "depth {}",
+                depth
+            );
+
+            let unique = enumerate_unique_states::<TicTacToe>(&root, depth, usize::MAX).unwrap();
+            let hashes = states.iter().map(|s| s.hash()).collect::<HashSet<u64>>();
+            assert_eq!(hashes.len(), unique.len());
+            assert!(unique.iter().all(|(s, _)| hashes.contains(&s.hash())));
+            previous = states;
+        }
+    }
+
+    #[test]
+    fn enumeration_respects_node_limit() {
+        let root = TicTacToe::initial_state();
+        let limit = EnumerationError::NodeLimitExceeded { limit: 82 };
+        assert_eq!(
+            enumerate_states::<TicTacToe>(&root, 2, 82).unwrap().len(),
+            82
+        );
+        assert_eq!(
+            enumerate_states::<TicTacToe>(&root, 2, 81).unwrap_err(),
+            EnumerationError::NodeLimitExceeded { limit: 81 }
+        );
+        assert_eq!(
+            enumerate_unique_states::<TicTacToe>(&root, 3, 82).unwrap_err(),
+            limit
+        );
+        assert_eq!(
+            enumerate_unique_states::<TicTacToe>(&root, 2, 82)
+                .unwrap()
+                .len(),
+            82
+        );
+    }
+
+    /// TicTacToe with a deliberately bad 8-bit hash.
+    #[derive(Debug)]
+    struct BadlyHashed;
+
+    #[derive(Clone, Debug)]
+    struct BadState(TTTState);
+
+    impl Game for BadlyHashed {
+        type State = BadState;
+        type Action = TTTAction;
+        type Team = Team;
+        type GameResult = GameResult<Team>;
+        type EvalType = i32;
+
+        fn initial_state() -> BadState {
+            BadState(TicTacToe::initial_state())
+        }
+    }
+
+    impl GwState<BadlyHashed> for BadState {
+        type ActionIter = TTTActionIter;
+
+        fn actions(&self) -> TTTActionIter {
+            self.0.actions()
+        }
+
+        fn team_to_move(&self) -> Team {
+            self.0.team_to_move()
+        }
+
+        fn apply_action(&self, action: &TTTAction) -> Self {
+            BadState(self.0.apply_action(action))
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.0.is_terminal()
+        }
+
+        fn game_result(&self) -> Option<GameResult<Team>> {
+            self.0.game_result()
+        }
+    }
+
+    impl TranspositionHash for BadState {
+        fn hash(&self) -> u64 {
+            1 + self.0.hash() % 251
+        }
+    }
+
+    impl AuditHash for BadState {
+        fn audit_hash(&self) -> u64 {
+            self.0.audit_hash()
+        }
+    }
+
+    #[test]
+    fn audit_detects_bad_hash() {
+        let root = BadlyHashed::initial_state();
+        let mut table = QuadraticProbingTable64::new(1 << 10);
+        let (count, stats) = perft_with_cache_audited::<BadlyHashed, _>(&root, 9, &mut table);
+        assert_eq!(count, 255_168);
+        assert!(stats.collisions > 0, "{:?}", stats);
+
+        // the perfect hash never collides
+        let root = TicTacToe::initial_state();
+        let mut table = QuadraticProbingTable64::new(1 << 14);
+        let (count, stats) = perft_with_cache_audited::<TicTacToe, _>(&root, 9, &mut table);
+        assert_eq!(count, 255_168);
+        assert_eq!(stats.collisions, 0);
+        assert!(stats.hits > 0);
+    }
+
+    #[test]
+    fn tree_recorder_exports_search_tree() {
+        let mut search = NegaMax::new(3, TTTHeuristic).with_tree_recorder(3, 100_000);
+        search.negamax(&TicTacToe::initial_state(), 3, i32::MIN, i32::MAX);
+        let recorder = search.tree_recorder().unwrap();
+        let nodes = recorder.nodes();
+
+        let root = &nodes[0];
+        assert!(root.parent.is_none() && root.action.is_none());
+        assert_eq!(root.eval, Some(0));
+        let mut cells = nodes
+            .iter()
+            .filter(|node| node.parent == Some(0))
+            .map(|node| node.action.as_ref().unwrap().mask.trailing_zeros())
+            .collect::<Vec<u32>>();
+        cells.sort_unstable();
+        assert_eq!(cells, (0..9).collect::<Vec<u32>>());
+        assert!(nodes
+            .iter()
+            .all(|node| node.ply <= 3 && node.eval.is_some()));
+        assert!(nodes.iter().any(|node| node.cutoff));
+
+        let dot = recorder.to_dot();
+        assert!(dot.starts_with("digraph search {"));
+        assert_eq!(dot.matches("    n0 -> ").count(), 9);
+        assert_eq!(dot.matches(" -> ").count(), nodes.len() - 1);
+        assert!(dot.contains("\\ncutoff"));
+    }
+
+    #[test]
+    fn divide_splits_perft_by_first_move() {
+        let split = divide::<TicTacToe>(&TicTacToe::initial_state(), 9);
+        assert_eq!(split.len(), 9);
+        assert_eq!(split.iter().map(|(_, count)| count).sum::<u64>(), 255_168);
+        for (action, count) in split {
+            let expected = match action.mask.trailing_zeros() {
+                4 => 25_872,
+                0 | 2 | 6 | 8 => 27_732,
+                _ => 29_592,
+            };
+            assert_eq!(count, expected, "{:?}", action);
+        }
+    }
+
+    const SYMMETRIES: [Transform<TicTacToe>; 2] = [TTTState::mirrored, TTTState::rotated];
+
+    #[test]
+    fn evaluators_are_invariant_under_symmetries() {
+        let states = all_states();
+        let violations = check_symmetry(&mut TTTHeuristic, &SYMMETRIES, &states, 0.0);
+        assert!(violations.is_empty(), "{:?}", violations.first());
+
+        let mut search = NegaMax::new(4, TTTHeuristic);
+        let violations = check_symmetry(&mut search, &SYMMETRIES, states.iter().step_by(10), 0.0);
+        assert!(violations.is_empty(), "{:?}", violations.first());
+    }
+
+    /// Prefers crosses in the top left corner over the other corners.
+    struct CornerEvaluator;
+
+    impl Evaluator<TicTacToe> for CornerEvaluator {
+        fn evaluate_for(&mut self, state: &TTTState, team: &Team) -> i32 {
+            let score = (state.crosses & 1) as i32;
+            if *team == One {
+                score
+            } else {
+                -score
+            }
+        }
+    }
+
+    #[test]
+    fn asymmetric_evaluator_is_flagged() {
+        let state = TTTState::from_moves("0");
+        let violations = check_symmetry(&mut CornerEvaluator, &SYMMETRIES, [&state], 0.0);
+        // both transforms move the cross, for both teams
+        assert_eq!(violations.len(), 4);
+        assert!(violations
+            .iter()
+            .all(|violation| violation.original.abs() == 1.0 && violation.transformed == 0.0));
+
+        let states = all_states();
+        let violations = check_symmetry(&mut CornerEvaluator, &SYMMETRIES, &states, 0.0);
+        assert!(!violations.is_empty());
+    }
+
+    #[test]
+    fn misere_forwards_the_action_bound() {
+        assert_eq!(Misere::<TicTacToe>::MAX_ACTIONS, TicTacToe::MAX_ACTIONS);
+    }
+
+    #[test]
+    fn misere_search_avoids_completing_a_line() {
+        // crosses on 0 and 1, completing the top row on 2 loses
+        let state = MisereState::<TicTacToe>(TTTState::from_moves("0314"));
+        for depth in [1, 5] {
+            let mut agent =
+                MaximisingAgent::new(NegaMax::new(depth, MisereEvaluator(TTTHeuristic)));
+            let action = agent.select_action(&state).unwrap();
+            assert_ne!(action.mask, 1 << 2, "depth {}", depth);
+            assert!(!state.apply_action(&action).is_terminal());
+        }
+
+        let mut search = NegaMax::new(1, MisereEvaluator(TTTHeuristic));
+        let losing = state.apply_action(&TTTAction { mask: 1 << 2 });
+        assert_eq!(losing.game_result(), Some(GameResult::Win(Two)));
+        assert_eq!(search.evaluate_for(&losing, &One), loss_in(5));
+    }
+
+    #[test]
+    fn misere_tictactoe_is_a_draw() {
+        let mut search = NegaMax::new(9, MisereEvaluator(TTTHeuristic));
+        let root = Misere::<TicTacToe>::initial_state();
+        assert_eq!(search.negamax(&root, 9, i32::MIN, i32::MAX), 0);
+    }
 }
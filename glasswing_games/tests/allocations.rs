@@ -0,0 +1,56 @@
+use glasswing::agents::NegaMax;
+use glasswing::core::Game;
+use glasswing_games::connect4::{C4Heuristic, Connect4};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts the allocations of the whole test binary, which therefore holds a
+/// single test.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+#[test]
+fn negamax_reuses_action_buffers() {
+    let depth = 10;
+    let state = Connect4::initial_state();
+    let mut search = NegaMax::new(depth, C4Heuristic);
+
+    // the first search allocates the buffers of every ply once
+    let (first, allocations) =
+        allocations_during(|| search.negamax(&state, depth, i32::MIN, i32::MAX));
+    let nodes = search.counters().nodes;
+    assert!(nodes > 10_000);
+    assert!(
+        allocations <= 4 * (depth as usize + 1),
+        "{} allocations",
+        allocations
+    );
+
+    // later searches allocate nothing at all
+    let (second, allocations) =
+        allocations_during(|| search.negamax(&state, depth, i32::MIN, i32::MAX));
+    assert_eq!(second, first);
+    assert_eq!(search.counters().nodes, nodes);
+    assert_eq!(allocations, 0);
+}
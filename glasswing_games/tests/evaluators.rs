@@ -0,0 +1,58 @@
+use glasswing::agents::{check_contract, random_states, ContractConfig, Evaluator};
+use glasswing::core::Game;
+use glasswing_games::breakthrough::{BTHeuristic, Breakthrough};
+use glasswing_games::checkers::{Checkers, CheckersHeuristic};
+use glasswing_games::connect4::{C4Heuristic, Connect4};
+use glasswing_games::dots_and_boxes::{DBHeuristic, DotsAndBoxes};
+use glasswing_games::nxn_tictactoe::{NTTTEvaluator, NTicTacToe};
+use glasswing_games::tictactoe::{TTTHeuristic, TicTacToe};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn assert_contract<G, E>(mut evaluator: E, games: usize)
+where
+    G: Game,
+    G::EvalType: Into<f64>,
+    E: Evaluator<G>,
+{
+    let states = random_states::<G, _>(games, &mut StdRng::seed_from_u64(3));
+    let violations = check_contract(&mut evaluator, &states, &ContractConfig::default());
+    assert!(
+        violations.is_empty(),
+        "{} violations in {}, first {:?}",
+        violations.len(),
+        G::name(),
+        violations[0]
+    );
+}
+
+#[test]
+fn tictactoe_heuristic_keeps_the_contract() {
+    assert_contract::<TicTacToe, _>(TTTHeuristic, 200);
+}
+
+#[test]
+fn nxn_tictactoe_evaluator_keeps_the_contract() {
+    assert_contract::<NTicTacToe<3>, _>(NTTTEvaluator, 200);
+    assert_contract::<NTicTacToe<4>, _>(NTTTEvaluator, 50);
+}
+
+#[test]
+fn connect4_heuristic_keeps_the_contract() {
+    assert_contract::<Connect4, _>(C4Heuristic, 100);
+}
+
+#[test]
+fn breakthrough_heuristic_keeps_the_contract() {
+    assert_contract::<Breakthrough, _>(BTHeuristic, 10);
+}
+
+#[test]
+fn checkers_heuristic_keeps_the_contract() {
+    assert_contract::<Checkers, _>(CheckersHeuristic, 10);
+}
+
+#[test]
+fn dots_and_boxes_heuristic_keeps_the_contract() {
+    assert_contract::<DotsAndBoxes<3, 3>, _>(DBHeuristic, 50);
+}
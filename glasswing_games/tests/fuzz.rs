@@ -0,0 +1,57 @@
+use glasswing::core::Game;
+use glasswing::testing::fuzz_game;
+use glasswing_games::breakthrough::Breakthrough;
+use glasswing_games::checkers::Checkers;
+use glasswing_games::connect4::Connect4;
+use glasswing_games::dots_and_boxes::DotsAndBoxes;
+use glasswing_games::nxn_tictactoe::NTicTacToe;
+use glasswing_games::tictactoe::TicTacToe;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn fuzz<G>(max_plies: usize, iterations: usize)
+where
+    G: Game,
+    G::Action: PartialEq,
+{
+    let mut rng = StdRng::seed_from_u64(7);
+    if let Err(failure) = fuzz_game::<G, _>(&mut rng, max_plies, iterations) {
+        panic!(
+            "{:?} after {:?} in {}",
+            failure.violation,
+            failure.actions,
+            G::name()
+        );
+    }
+}
+
+#[test]
+fn tictactoe() {
+    fuzz::<TicTacToe>(9, 500);
+}
+
+#[test]
+fn nxn_tictactoe() {
+    fuzz::<NTicTacToe<3>>(9, 500);
+    fuzz::<NTicTacToe<4>>(16, 200);
+}
+
+#[test]
+fn connect4() {
+    fuzz::<Connect4>(42, 300);
+}
+
+#[test]
+fn breakthrough() {
+    fuzz::<Breakthrough>(200, 50);
+}
+
+#[test]
+fn checkers() {
+    fuzz::<Checkers>(200, 50);
+}
+
+#[test]
+fn dots_and_boxes() {
+    fuzz::<DotsAndBoxes<3, 3>>(24, 200);
+}
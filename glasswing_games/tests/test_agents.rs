@@ -0,0 +1,81 @@
+use glasswing::agents::test_agents::{MirrorAgent, StallAgent, SuicideAgent};
+use glasswing::agents::{Agent, NegaMax, PrepareInfo};
+use glasswing::core::{Game, GameResult, GwState, Team};
+use glasswing::train::Pit;
+use glasswing_games::connect4::{C4Action, C4Heuristic, C4State, Connect4};
+use glasswing_games::tictactoe::{TTTHeuristic, TTTState, TicTacToe};
+
+/// Plays a game to the end, failing on any error.
+fn play<G, A, B>(a: A, b: B) -> G::GameResult
+where
+    G: Game,
+    A: Agent<G>,
+    B: Agent<G>,
+{
+    let mut pit = Pit::<G, A, B>::new(a, b, G::initial_state());
+    while pit.step().unwrap().is_some() {}
+    pit.game_result().expect("game did not finish")
+}
+
+#[test]
+fn matches_complete_on_tictactoe() {
+    play::<TicTacToe, _, _>(MirrorAgent::new(), StallAgent::new());
+    play::<TicTacToe, _, _>(StallAgent::new(), SuicideAgent::new(TTTHeuristic));
+    play::<TicTacToe, _, _>(SuicideAgent::new(TTTHeuristic), MirrorAgent::new());
+}
+
+#[test]
+fn matches_complete_on_connect4() {
+    play::<Connect4, _, _>(MirrorAgent::new(), StallAgent::new());
+    play::<Connect4, _, _>(StallAgent::new(), SuicideAgent::new(C4Heuristic));
+    play::<Connect4, _, _>(SuicideAgent::new(C4Heuristic), MirrorAgent::new());
+}
+
+#[test]
+fn mirror_repeats_the_opponents_column() {
+    let mut agent = MirrorAgent::<Connect4>::new();
+    agent.prepare(&Connect4::initial_state(), &PrepareInfo { team: Team::Two });
+    let prev = C4State::from_moves("3333");
+    let next = prev.apply_action(&C4Action::new(3));
+    agent.observe(&prev, &C4Action::new(3), &next);
+    assert_eq!(agent.select_action(&next).unwrap(), C4Action::new(3));
+
+    // once the column is full, the first legal column is played instead
+    let mut agent = MirrorAgent::<Connect4>::new();
+    agent.prepare(&Connect4::initial_state(), &PrepareInfo { team: Team::One });
+    let prev = C4State::from_moves("33333");
+    let next = prev.apply_action(&C4Action::new(3));
+    agent.observe(&prev, &C4Action::new(3), &next);
+    assert_eq!(agent.select_action(&next).unwrap(), C4Action::new(0));
+}
+
+#[test]
+fn stall_avoids_ending_the_game() {
+    // crosses can win by taking cell 2, which leaves no legal actions
+    let state = TTTState::from_moves("0314");
+    let action = StallAgent::<TicTacToe>::new()
+        .select_action(&state)
+        .unwrap();
+    let next = state.apply_action(&action);
+    assert!(!next.is_terminal());
+    assert_eq!(next.count_actions(), 4);
+
+    // filling column 0 would leave the opponent one column fewer
+    let state = C4State::from_moves("00000");
+    let action = StallAgent::<Connect4>::new().select_action(&state).unwrap();
+    assert_ne!(action, C4Action::new(0));
+}
+
+#[test]
+fn suicide_lets_the_opponent_win() {
+    // crosses must take cell 2 to win or cell 5 to block noughts
+    let state = TTTState::from_moves("0314");
+    let mut agent = SuicideAgent::new(NegaMax::new(2, TTTHeuristic));
+    let action = agent.select_action(&state).unwrap();
+    let next = state.apply_action(&action);
+    assert!(!next.is_terminal());
+    assert!(next
+        .actions()
+        .into_iter()
+        .any(|reply| next.apply_action(&reply).game_result() == Some(GameResult::Win(Team::Two))));
+}